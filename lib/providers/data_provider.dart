@@ -423,10 +423,13 @@ Avoid putting await directly in the constructor.
   }
 
   // -------------------------------
-  // Call Rust Cell update function
+  // Call Rust Cell update function,
+  // only the cells Rust reports as dirty are read back
   // -------------------------------
   void callRustCellUpdate(int r, int c, int numRows, int numCols) {
-    rustMatrix.updateCell(r, c, numRows, numCols);
+    for (final idx in rustMatrix.updateCell(r, c, numRows, numCols)) {
+      readCellFromRust(idx ~/ numCols, idx % numCols, numRows, numCols);
+    }
   }
 
   Future<void> shutdown() async {
@@ -54,10 +54,12 @@ import 'package:ffi/ffi.dart'; // for toNativeUtf8()
 */
 
 /* Dart class to map Dart matrix data to the Rust structure
-This mirrors your Rust struct.
-Each Array<Bool> is native memory, so you cannot assign a Dart list directly.
-Access individual elements with indexing: cell.selectedCandState[i]. */
-final class DartToRustElementFFI extends Struct {
+This mirrors GridElementFFI of rust/rust_lib/src/ffi.rs, the cell of the
+session buffers. The candidate lists hold constMaxGridSize entries, entries
+above the grid size stay 0.
+Each Array<Uint8> is native memory, so you cannot assign a Dart list directly.
+Access individual elements with indexing: cell.selectedCandList[i]. */
+final class GridElementFFI extends Struct {
   @Uint8()
   external int row;
 
@@ -70,7 +72,7 @@ final class DartToRustElementFFI extends Struct {
   @Array(constSelectedNumStateListSize)
   external Array<Uint8> selectedNumStateList;
 
-  @Array(constSelectedNumberListSize)
+  @Array(constMaxGridSize)
   external Array<Uint8> selectedCandList;
 
   @Array(constSelectedPatternListSize)
@@ -79,7 +81,7 @@ final class DartToRustElementFFI extends Struct {
   @Array(constRequestedElementHighLightTypeListSize)
   external Array<Uint8> requestedElementHighLightType;
 
-  @Array(constRequestedCandHighLightTypeListSize)
+  @Array(constMaxGridSize)
   external Array<Uint8> requestedCandHighLightType;
 }
 
@@ -116,193 +118,336 @@ class DartToRustElement {
 //////////////////////////////////////////////////////
 // Native bindings
 //////////////////////////////////////////////////////
+
+// Matches the exact C/Rust function signature
+typedef AbiVersionNative = Uint32 Function();
+// Dart-friendly version
+typedef AbiVersionDart = int Function();
+
+// Matches the exact C/Rust function signature
+typedef SessionCreateSizedNative = Uint64 Function(Uint8 size);
+// Dart-friendly version
+typedef SessionCreateSizedDart = int Function(int size);
+
 // Matches the exact C/Rust function signature
+typedef SessionCallNative = Int32 Function(Uint64 handle);
+// Dart-friendly version
+typedef SessionCallDart = int Function(int handle);
 
 // Matches the exact C/Rust function signature
-typedef CreateMatrixNative = Pointer<DartToRustElementFFI> Function(
-    Uint8 numRows, Uint8 numCols);
+typedef SessionBufferNative = Int32 Function(
+    Uint64 handle, Pointer<GridElementFFI> ptr, Uint32 len);
 // Dart-friendly version
-typedef CreateMatrixDart = Pointer<DartToRustElementFFI> Function(
-    int numRows, int numCols);
+typedef SessionBufferDart = int Function(
+    int handle, Pointer<GridElementFFI> ptr, int len);
 
 // Matches the exact C/Rust function signature
-typedef UpdateMatrixNative = Void Function(
-    Pointer<DartToRustElementFFI> ptr, Uint8 numRows, Uint8 numCols);
+typedef SessionEraseNative = Int32 Function(Uint64 handle, Uint8 eraseGivens);
 // Dart-friendly version
-typedef UpdateMatrixDart = void Function(
-    Pointer<DartToRustElementFFI> ptr, int numRows, int numCols);
+typedef SessionEraseDart = int Function(int handle, int eraseGivens);
 
 // Matches the exact C/Rust function signature
-typedef EraseMatrixNative = Void Function(Pointer<DartToRustElementFFI> ptr,
-    Uint8 numRows, Uint8 numCols, Uint8 eraseGivens);
+typedef SessionSetValueNative = Int32 Function(
+    Uint64 handle, Uint8 idx, Uint8 value);
 // Dart-friendly version
-typedef EraseMatrixDart = void Function(Pointer<DartToRustElementFFI> ptr,
-    int numRows, int numCols, int eraseGivens);
+typedef SessionSetValueDart = int Function(int handle, int idx, int value);
 
 // Matches the exact C/Rust function signature
-typedef SetAllCandidatesNative = Void Function(
-    Pointer<DartToRustElementFFI> ptr, Uint8 numRows, Uint8 numCols);
+typedef SessionDirtyCellsNative = Int32 Function(
+    Uint64 handle, Pointer<Uint8> out, Uint32 len);
 // Dart-friendly version
-typedef SetAllCandidatesDart = void Function(
-    Pointer<DartToRustElementFFI> ptr, int numRows, int numCols);
+typedef SessionDirtyCellsDart = int Function(
+    int handle, Pointer<Uint8> out, int len);
 
 // Matches the exact C/Rust function signature
-typedef UpdateCellNative = Void Function(
-    Pointer<DartToRustElementFFI> ptr, Uint8 numRows, Uint8 numCols, Uint8 idx);
+typedef SessionSetSettingsNative = Int32 Function(Uint64 handle,
+    Uint8 autoPatterns, Uint8 autoRemoveCandidates, Uint32 historyLimit);
 // Dart-friendly version
-typedef UpdateCellDart = void Function(
-    Pointer<DartToRustElementFFI> ptr, int numRows, int numCols, int idx);
+typedef SessionSetSettingsDart = int Function(
+    int handle, int autoPatterns, int autoRemoveCandidates, int historyLimit);
 
 // Matches the exact C/Rust function signature
-typedef FreeMatrixNative = Void Function(
-    Pointer<DartToRustElementFFI> ptr, Uint8 numRows, Uint8 numCols);
+typedef SessionSaveNative = Int32 Function(
+    Uint64 handle,
+    Pointer<Utf8> path, // use Pointer<Utf8> for strings
+    Uint8 format);
 // Dart-friendly version
-typedef FreeMatrixDart = void Function(
-    Pointer<DartToRustElementFFI> ptr, int numRows, int numCols);
+typedef SessionSaveDart = int Function(
+    int handle, Pointer<Utf8> path, int format);
 
 // Matches the exact C/Rust function signature
-typedef SaveDataNative = Void Function(
-    Pointer<DartToRustElementFFI> ptr,
-    Uint8 numRows,
-    Uint8 numCols,
-    Pointer<Utf8> path); // use Pointer<Utf8> for strings
+typedef SessionLoadNative = Int32 Function(
+    Uint64 handle, Pointer<Utf8> path); // use Pointer<Utf8> for strings
 // Dart-friendly version
-typedef SaveDataDart = void Function(Pointer<DartToRustElementFFI> ptr,
-    int numRows, int numCols, Pointer<Utf8> path);
+typedef SessionLoadDart = int Function(int handle, Pointer<Utf8> path);
 
 // Matches the exact C/Rust function signature
-typedef LoadDataNative = Void Function(
-    Pointer<DartToRustElementFFI> ptr,
-    Uint8 numRows,
-    Uint8 numCols,
-    Pointer<Utf8> path); // use Pointer<Utf8> for strings
+typedef SessionNewPuzzleNative = Int32 Function(
+    Uint64 handle, Pointer<Utf8> puzzle); // use Pointer<Utf8> for strings
 // Dart-friendly version
-typedef LoadDataDart = void Function(Pointer<DartToRustElementFFI> ptr,
-    int numRows, int numCols, Pointer<Utf8> path);
+typedef SessionNewPuzzleDart = int Function(int handle, Pointer<Utf8> puzzle);
+
+// Matches the exact C/Rust function signature
+// Text for a negative return code, released with free_rust_string
+typedef ErrorCodeMessageNative = Pointer<Utf8> Function(Int32 code);
+// Dart-friendly version
+typedef ErrorCodeMessageDart = Pointer<Utf8> Function(int code);
+
+// Matches the exact C/Rust function signature
+typedef FreeRustStringNative = Void Function(Pointer<Utf8> s);
+// Dart-friendly version
+typedef FreeRustStringDart = void Function(Pointer<Utf8> s);
 
 /*
-Helper to store matrix metadata for Finalizer
 RustMatrix class :
-Wraps the FFI pointer into a Dart class.
-Uses a Finalizer to automatically free Rust memory when Dart object is garbage collected.
-update() → calls Rust’s update_matrix.
-printMatrix() → reads values directly from Rust memory.
-✅ Key design: you own the pointer on Dart side, and you must free it either via dispose() or let the Finalizer handle it.
+Wraps one Rust game session (session_* functions of session.rs).
+Rust owns the game, Dart only holds an opaque u64 handle plus a cell buffer
+of numRows * numCols GridElementFFI in Dart owned native memory.
+Every Rust call first writes the buffer into the session (session_write),
+runs, and reads the session back into the buffer (session_read).
+Uses a Finalizer to free the session and the buffer when the Dart object is garbage collected.
+update() → calls Rust’s session_update_patterns.
+✅ Key design: Rust never sees a pointer it did not get for the length of one call.
 */
 
 /*
-This is a simple metadata container for the Rust matrix.
-The Finalizer needs this to know which pointer to free when the Dart object is garbage collected.
-You never use _MatrixHandle directly; it’s only for the Finalizer.
+This is a simple metadata container for the Rust session.
+The Finalizer needs this to know which session and buffer to free when the Dart object is garbage collected.
+You never use _SessionHandle directly; it’s only for the Finalizer.
 */
-class _MatrixHandle {
-  final Pointer<DartToRustElementFFI> ptr;
-  final int numRows;
-  final int numCols;
-  const _MatrixHandle(this.ptr, this.numRows, this.numCols);
+class _SessionHandle {
+  final int handle;
+  final Pointer<GridElementFFI> ptr;
+  const _SessionHandle(this.handle, this.ptr);
 }
 
 /*
-Allocate a Rust matrix
+Create a Rust session
 final rustMatrix = RustMatrix(dylib, numRows, numCols);
-Calls create_matrix in Rust.
-Stores the returned pointer (ptr) in Dart.
+Calls session_create_sized in Rust and allocates the cell buffer.
 Automatically free memory
-static final Finalizer<_MatrixHandle> _finalizer = Finalizer<_MatrixHandle>((handle) {
-    _freeMatrix(handle.ptr, handle.numRows, handle.numCols);
-});
-When Dart GC collects the RustMatrix object, the Rust memory is automatically freed.
-_MatrixHandle tells the finalizer what pointer to free.
+When Dart GC collects the RustMatrix object, session_free is called and the buffer released.
 Manual cleanup (optional)
 rustMatrix.dispose();
-Detaches the finalizer and calls free_matrix immediately.
+Detaches the finalizer and frees session and buffer immediately.
 Useful if you want deterministic memory release.
 Call Rust update function
 rustMatrix.update();
-Calls your Rust update_matrix function to modify the matrix on the Rust side.
-Debug / inspect matrix
-rustMatrix.printMatrix();
-Reads matrix values from Rust memory and prints them.
-Useful for testing.
+Recomputes the pattern highlights of the whole grid on the Rust side.
 */
 class RustMatrix {
-  final Pointer<DartToRustElementFFI> ptr;
+  final int handle;
+  final Pointer<GridElementFFI> ptr;
   final int numRows;
   final int numCols;
 
-  static late final UpdateMatrixDart _updateMatrix;
-  static late final EraseMatrixDart _eraseMatrix;
-  static late final SetAllCandidatesDart _setAllCandidates;
-  static late final UpdateCellDart _updateCell;
-  static late final SaveDataDart _saveData;
-  static late final LoadDataDart _loadData;
-  static late final FreeMatrixDart _freeMatrix;
-
-  // Finalizer to free Rust memory
-  static final Finalizer<_MatrixHandle> _finalizer =
-      Finalizer<_MatrixHandle>((handle) {
-    _freeMatrix(handle.ptr, handle.numRows, handle.numCols);
+  static late final SessionBufferDart _sessionRead;
+  static late final SessionBufferDart _sessionWrite;
+  static late final SessionCallDart _sessionUpdatePatterns;
+  static late final SessionEraseDart _sessionErase;
+  static late final SessionCallDart _sessionSetAllCandidates;
+  static late final SessionSetValueDart _sessionSetValue;
+  static late final SessionDirtyCellsDart _sessionDirtyCells;
+  static late final SessionSaveDart _sessionSave;
+  static late final SessionLoadDart _sessionLoad;
+  static late final SessionNewPuzzleDart _sessionNewPuzzle;
+  static late final SessionCallDart _sessionGridSize;
+  static late final SessionCallDart _sessionFree;
+  static late final ErrorCodeMessageDart _errorCodeMessage;
+  static late final FreeRustStringDart _freeRustString;
+
+  // Finalizer to free the Rust session and the cell buffer
+  static final Finalizer<_SessionHandle> _finalizer =
+      Finalizer<_SessionHandle>((handle) {
+    _sessionFree(handle.handle);
+    malloc.free(handle.ptr);
   });
 
-  RustMatrix._(this.ptr, this.numRows, this.numCols);
+  RustMatrix._(this.handle, this.ptr, this.numRows, this.numCols);
 
-  /// Factory: creates Rust matrix from dynamic library
-  factory RustMatrix(DynamicLibrary dylib, int numRows, int numCols) {
-    assert(CONST_MATRIX_ELEMENTS >= numRows * numCols,
-        'Matrix element size exceeds maximum allowed size $CONST_MATRIX_ELEMENTS!');
+  int get numElements => numRows * numCols;
 
+  /// Factory: creates a Rust session from dynamic library
+  factory RustMatrix(DynamicLibrary dylib, int numRows, int numCols) {
     // --- Size checks ---
-    if (numRows <= 0 || numRows > constSudokuNumRow) {
+    if (numRows <= 0 || numRows > constMaxGridSize) {
       throw RangeError(
-          'Invalid number of rows: $numRows (max ${constSudokuNumRow})');
+          'Invalid number of rows: $numRows (max ${constMaxGridSize})');
     }
-    if (numCols <= 0 || numCols > constSudokuNumCol) {
+    if (numCols != numRows) {
       throw RangeError(
-          'Invalid number of columns: $numCols (max ${constSudokuNumCol})');
+          'Invalid number of columns: $numCols (must be $numRows)');
+    }
+
+    // --- ABI check, refuse a library built from other sources ---
+    final abiVersion =
+        dylib.lookupFunction<AbiVersionNative, AbiVersionDart>('abi_version');
+    final elementSize = dylib.lookupFunction<AbiVersionNative, AbiVersionDart>(
+        'grid_element_ffi_size');
+
+    if (abiVersion() != CONST_RUST_ABI_VERSION) {
+      throw Exception(
+          'Rust library ABI version ${abiVersion()} does not match $CONST_RUST_ABI_VERSION!');
+    }
+    if (elementSize() != sizeOf<GridElementFFI>()) {
+      throw Exception(
+          'Rust element size ${elementSize()} does not match Dart size ${sizeOf<GridElementFFI>()}!');
     }
 
     // --- Lookup FFI functions ---
-    final createMatrix = dylib
-        .lookupFunction<CreateMatrixNative, CreateMatrixDart>('create_matrix');
+    final sessionCreateSized =
+        dylib.lookupFunction<SessionCreateSizedNative, SessionCreateSizedDart>(
+            'session_create_sized');
+
+    final sessionSetSettings =
+        dylib.lookupFunction<SessionSetSettingsNative, SessionSetSettingsDart>(
+            'session_set_settings');
+
+    _sessionRead = dylib
+        .lookupFunction<SessionBufferNative, SessionBufferDart>('session_read');
+
+    _sessionWrite = dylib.lookupFunction<SessionBufferNative,
+        SessionBufferDart>('session_write');
+
+    _sessionUpdatePatterns =
+        dylib.lookupFunction<SessionCallNative, SessionCallDart>(
+            'session_update_patterns');
+
+    _sessionErase = dylib
+        .lookupFunction<SessionEraseNative, SessionEraseDart>('session_erase');
+
+    _sessionSetAllCandidates =
+        dylib.lookupFunction<SessionCallNative, SessionCallDart>(
+            'session_set_all_candidates');
+
+    _sessionSetValue =
+        dylib.lookupFunction<SessionSetValueNative, SessionSetValueDart>(
+            'session_set_value');
 
-    _updateMatrix = dylib
-        .lookupFunction<UpdateMatrixNative, UpdateMatrixDart>('update_matrix');
+    _sessionDirtyCells =
+        dylib.lookupFunction<SessionDirtyCellsNative, SessionDirtyCellsDart>(
+            'session_dirty_cells');
 
-    _eraseMatrix = dylib
-        .lookupFunction<EraseMatrixNative, EraseMatrixDart>('erase_matrix');
+    _sessionSave = dylib
+        .lookupFunction<SessionSaveNative, SessionSaveDart>('session_save');
 
-    _setAllCandidates =
-        dylib.lookupFunction<SetAllCandidatesNative, SetAllCandidatesDart>(
-            'set_all_candidates');
+    _sessionLoad = dylib
+        .lookupFunction<SessionLoadNative, SessionLoadDart>('session_load');
 
-    _updateCell =
-        dylib.lookupFunction<UpdateCellNative, UpdateCellDart>('update_cell');
+    _sessionNewPuzzle =
+        dylib.lookupFunction<SessionNewPuzzleNative, SessionNewPuzzleDart>(
+            'session_new_puzzle');
 
-    _saveData = dylib.lookupFunction<SaveDataNative, SaveDataDart>('save_data');
+    _sessionGridSize = dylib.lookupFunction<SessionCallNative,
+        SessionCallDart>('session_grid_size');
 
-    _loadData = dylib.lookupFunction<LoadDataNative, LoadDataDart>('load_data');
+    _sessionFree = dylib
+        .lookupFunction<SessionCallNative, SessionCallDart>('session_free');
 
-    _freeMatrix =
-        dylib.lookupFunction<FreeMatrixNative, FreeMatrixDart>('free_matrix');
+    _errorCodeMessage =
+        dylib.lookupFunction<ErrorCodeMessageNative, ErrorCodeMessageDart>(
+            'error_code_message');
 
-    // --- Allocate Rust matrix ---
-    final ptr = createMatrix(numRows, numCols);
-    if (ptr.address == 0) {
-      throw Exception("Rust failed to allocate matrix!");
+    _freeRustString =
+        dylib.lookupFunction<FreeRustStringNative, FreeRustStringDart>(
+            'free_rust_string');
+
+    // --- Create the Rust session, 0 is never a valid handle ---
+    final handle = sessionCreateSized(numRows);
+    if (handle == 0) {
+      throw Exception("Rust failed to create a session of size $numRows!");
     }
 
+    // Patterns after every change, placing a number only clears the
+    // peers' candidates through updateCell(removeCandidates: true)
+    sessionSetSettings(handle, 1, 1, CONST_RUST_HISTORY_LIMIT);
+
     // --- Wrap in RustMatrix and attach finalizer ---
-    final matrix = RustMatrix._(ptr, numRows, numCols);
-    _finalizer.attach(matrix, _MatrixHandle(ptr, numRows, numCols),
-        detach: matrix);
+    final ptr = malloc<GridElementFFI>(numRows * numCols);
+    final matrix = RustMatrix._(handle, ptr, numRows, numCols);
+    _finalizer.attach(matrix, _SessionHandle(handle, ptr), detach: matrix);
+    matrix._readSession();
     return matrix;
   }
 
+  // -------------------------------
+  // Check the return code of a Rust call, negative is an error
+  // -------------------------------
+  bool _checkStatus(int code, String call) {
+    if (code >= 0) {
+      return true;
+    }
+
+    final messagePtr = _errorCodeMessage(code);
+    final message =
+        messagePtr.address == 0 ? 'unknown error' : messagePtr.toDartString();
+    if (messagePtr.address != 0) {
+      _freeRustString(messagePtr);
+    }
+
+    log.warning('$call failed: $message ($code)');
+    return false;
+  }
+
+  // -------------------------------
+  // Buffer → session, one undo step if a cell changed
+  // -------------------------------
+  bool _writeSession() {
+    return _checkStatus(
+        _sessionWrite(handle, ptr, numElements), 'session_write');
+  }
+
+  // -------------------------------
+  // Session → buffer
+  // -------------------------------
+  bool _readSession() {
+    return _checkStatus(_sessionRead(handle, ptr, numElements), 'session_read');
+  }
+
+  // -------------------------------
+  // Run a session call on the current buffer content
+  // -------------------------------
+  bool _run(int Function() call, String name) {
+    _writeSession();
+    final ok = _checkStatus(call(), name);
+    _readSession();
+    return ok;
+  }
+
+  // -------------------------------
+  // Empty grid of numRows x numCols, history starts over
+  // -------------------------------
+  void _clearSession() {
+    final emptyUtf8 = ('0' * numElements).toNativeUtf8();
+
+    try {
+      _checkStatus(_sessionNewPuzzle(handle, emptyUtf8), 'session_new_puzzle');
+    } finally {
+      malloc.free(emptyUtf8);
+    }
+    _readSession();
+  }
+
+  // Cells changed by the last session call
+  List<int> _dirtyCells() {
+    // Room for every cell, Rust writes the dirty indices and returns their count
+    final dirty = malloc<Uint8>(numElements);
+    try {
+      final count = _sessionDirtyCells(handle, dirty, numElements);
+      if (!_checkStatus(count, 'session_dirty_cells')) {
+        return const [];
+      }
+      return List<int>.generate(count, (i) => dirty[i]);
+    } finally {
+      malloc.free(dirty);
+    }
+  }
+
   // -------------------------------
   // Call Rust matrix update function
   // -------------------------------
   void update() {
-    _updateMatrix(ptr, numRows, numCols);
+    _run(() => _sessionUpdatePatterns(handle), 'session_update_patterns');
   }
 
   // -------------------------------
@@ -310,31 +455,52 @@ class RustMatrix {
   // -------------------------------
   void erase(bool eraseGivens) {
     final int eraseGivensU8 = boolToU8(eraseGivens);
-    _eraseMatrix(ptr, numRows, numCols, eraseGivensU8);
+    _run(() => _sessionErase(handle, eraseGivensU8), 'session_erase');
   }
 
   // -------------------------------
-  // Call Rust matrix erase function
+  // Call Rust set all candidates function
   // -------------------------------
   void setAllCandidates() {
-    _setAllCandidates(ptr, numRows, numCols);
+    _run(() => _sessionSetAllCandidates(handle), 'session_set_all_candidates');
   }
 
   // -------------------------------
-  // Call Rust Cell update function
+  // Hand the edited cell to Rust,
+  // returns the indices of the cells to redraw.
+  // removeCandidates also takes the number out
+  // of the peers' pencil marks
   // -------------------------------
-  void updateCell(int row, int col, int numRows, int numCols) {
+  List<int> updateCell(int row, int col, int numRows, int numCols,
+      {bool removeCandidates = false}) {
     final idx = row * numCols + col;
 
     assert(row < numRows, 'row exceeds maximum allowed size!');
     assert(col < numCols, 'col exceeds maximum allowed size!');
-    assert(idx < CONST_MATRIX_ELEMENTS, 'idx exceeds maximum allowed size!');
+    assert(idx < numElements, 'idx exceeds maximum allowed size!');
 
-    _updateCell(ptr, numRows, numCols, idx);
+    if (!_writeSession()) {
+      return const [];
+    }
+    final dirty = _dirtyCells().toSet();
+
+    final cell = ptr[idx];
+    final isGiven =
+        cell.selectedNumStateList[SelectedNumStateListIndex.Givens] != 0;
+    if (removeCandidates && cell.selectedNum > 0 && !isGiven) {
+      // Same number again, only the peers change
+      if (_checkStatus(_sessionSetValue(handle, idx, cell.selectedNum),
+          'session_set_value')) {
+        dirty.addAll(_dirtyCells());
+      }
+    }
+
+    _readSession();
+    return dirty.toList()..sort();
   }
 
   void writeCellToRust(
-      Pointer<DartToRustElementFFI> ptr,
+      Pointer<GridElementFFI> ptr,
       List<List<DartToRustElement>> dartMatrix,
       int row,
       int col,
@@ -344,9 +510,9 @@ class RustMatrix {
 
     assert(row < numRows, 'row exceeds maximum allowed size!');
     assert(col < numCols, 'col exceeds maximum allowed size!');
-    assert(idx < CONST_MATRIX_ELEMENTS, 'idx exceeds maximum allowed size!');
+    assert(idx < numElements, 'idx exceeds maximum allowed size!');
 
-    final cellPtr = ptr.elementAt(idx).ref;
+    final cellPtr = ptr[idx];
     final dartCell = dartMatrix[row][col];
 
     // Copy scalar values
@@ -375,17 +541,18 @@ class RustMatrix {
   }
 
 /*
-Writing the complete Dart matrix to Rust
-We can optimize writeMatrixToRust so it writes the entire Dart matrix into Rust memory efficiently, 
+Writing the complete Dart matrix to the buffer
+We can optimize writeMatrixToRust so it writes the entire Dart matrix into the buffer efficiently, 
 without calling writeCell repeatedly. Instead, we calculate the flat index once and copy arrays directly.
 Why this is efficient:
 No repeated writeCell calls — avoids function overhead for every cell.
 Flat memory indexing: uses idx = r * numCols + c once per cell.
-Element-by-element copy for arrays: required for FFI safety, since Array<Bool> can’t be assigned directly.
+Element-by-element copy for arrays: required for FFI safety, since Array<Uint8> can’t be assigned directly.
 Scales better for larger matrices while keeping all logic in one loop.
+The session takes the buffer over with the next Rust call.
 */
   void writeMatrixToRust(
-    Pointer<DartToRustElementFFI> ptr,
+    Pointer<GridElementFFI> ptr,
     List<List<DartToRustElement>> dartMatrix,
     int numRows,
     int numCols,
@@ -394,7 +561,7 @@ Scales better for larger matrices while keeping all logic in one loop.
     for (int r = 0; r < numRows; r++) {
       for (int c = 0; c < numCols; c++) {
         final idx = r * numCols + c;
-        final cellPtr = ptr.elementAt(idx).ref;
+        final cellPtr = ptr[idx];
         final dartCell = dartMatrix[r][c];
 
         // Copy scalar values
@@ -426,7 +593,7 @@ Scales better for larger matrices while keeping all logic in one loop.
 
   // Write the intial Givens numbers into Rust. The numbers that come with the sudokku quiz
   void writeGivensToRust(
-    Pointer<DartToRustElementFFI> ptr,
+    Pointer<GridElementFFI> ptr,
     List<List<DartToRustElement>> dartMatrix,
     int numRows,
     int numCols,
@@ -435,7 +602,7 @@ Scales better for larger matrices while keeping all logic in one loop.
     for (int r = 0; r < numRows; r++) {
       for (int c = 0; c < numCols; c++) {
         final idx = r * numCols + c;
-        final cellPtr = ptr.elementAt(idx).ref;
+        final cellPtr = ptr[idx];
         final dartCell = dartMatrix[r][c];
 
         if (dartCell.selectedNum > 0) {
@@ -447,15 +614,15 @@ Scales better for larger matrices while keeping all logic in one loop.
   }
 
   // -------------------------------
-  // Read a single cell from Rust into Dart
+  // Read a single cell of the last session state into Dart
   // -------------------------------
   DartToRustElement readCellFromRust(int r, int c, int numRows, int numCols) {
     final idx = r * numCols + c;
     assert(r < numRows, 'row exceeds maximum allowed size!');
     assert(c < numCols, 'col exceeds maximum allowed size!');
-    assert(idx < CONST_MATRIX_ELEMENTS, 'idx exceeds maximum allowed size!');
+    assert(idx < numElements, 'idx exceeds maximum allowed size!');
 
-    final cellPtr = ptr.elementAt(idx).ref;
+    final cellPtr = ptr[idx];
     return DartToRustElement(cellPtr.row, cellPtr.col)
       ..selectedNum = cellPtr.selectedNum
       ..selectedCandList = List.generate(constSelectedCandListSize,
@@ -471,14 +638,14 @@ Scales better for larger matrices while keeping all logic in one loop.
   }
 
   // -------------------------------
-  // Convert entire Rust matrix into Dart list
+  // Convert the last session state into Dart list
   // -------------------------------
   /* 
-Reading the Rust matrix into Dart.
-Convert Rust matrix → Dart list of lists.
+Reading the Rust session into Dart.
+Convert buffer → Dart list of lists.
 Creates a 2D Dart list of DartToRustElement.
 */
-// Reads the entire Rust matrix into Dart
+// Reads the entire session grid into Dart
   List<List<DartToRustElement>> readMatrixFromRust(
     int numRows,
     int numCols,
@@ -495,7 +662,7 @@ Creates a 2D Dart list of DartToRustElement.
 
     for (int r = 0; r < numRows; r++) {
       for (int c = 0; c < numCols; c++) {
-        final cellPtr = ptr.elementAt(r * numCols + c).ref;
+        final cellPtr = ptr[r * numCols + c];
         result[r][c] = DartToRustElement(cellPtr.row, cellPtr.col)
           ..selectedNum = cellPtr.selectedNum
           ..selectedNumStateList = List.generate(constSelectedNumStateListSize,
@@ -519,12 +686,16 @@ Creates a 2D Dart list of DartToRustElement.
   // -------------------------------
   // Save to JSON upon shutdown
   // -------------------------------
-  void saveToJSON(String appJsonPath) {
+  bool saveToJSON(String appJsonPath) {
+    _writeSession();
+
     // Allocates native memory
     final appJsonPathUtf8 = appJsonPath.toNativeUtf8();
 
     try {
-      _saveData(ptr, numRows, numCols, appJsonPathUtf8);
+      return _checkStatus(
+          _sessionSave(handle, appJsonPathUtf8, CONST_RUST_SAVE_FORMAT_JSON),
+          'session_save');
     } finally {
       malloc.free(appJsonPathUtf8);
     }
@@ -537,8 +708,21 @@ Creates a 2D Dart list of DartToRustElement.
     final appJsonPathUtf8 = appJsonPath.toNativeUtf8();
 
     try {
-      // Call Rust function
-      _loadData(ptr, numRows, numCols, appJsonPathUtf8);
+      // Call Rust function, a failed load leaves the session untouched
+      final loaded = _checkStatus(
+          _sessionLoad(handle, appJsonPathUtf8), 'session_load');
+      if (!loaded) {
+        return false; // ❌ failure
+      }
+
+      // The buffer only fits numRows x numCols, start over with an empty grid
+      final size = _sessionGridSize(handle);
+      if (size != numRows) {
+        log.warning('session_load: grid size $size does not match $numRows');
+        _clearSession();
+        return false; // ❌ failure
+      }
+      _readSession();
 
       print("Loading JSON from: ${appJsonPathUtf8.toDartString()}");
 
@@ -561,7 +745,8 @@ Creates a 2D Dart list of DartToRustElement.
   // -------------------------------
   void dispose() {
     _finalizer.detach(this);
-    _freeMatrix(ptr, numRows, numCols);
+    _sessionFree(handle);
+    malloc.free(ptr);
   }
 }
 
@@ -41,6 +41,19 @@ const int CONST_MATRIX_SIZE = 9;
 const int CONST_MATRIX_ELEMENTS = 81;
 const int MAX_UINT8 = 255;
 
+// Must match ABI_VERSION in rust/rust_lib/src/ffi.rs
+const int CONST_RUST_ABI_VERSION = 6;
+
+// Must match MAX_GRID_SIZE in rust/rust_lib/src/grid.rs,
+// list length of GridElementFFI
+const int constMaxGridSize = 16;
+
+// Undo steps the Rust session keeps (DEFAULT_HISTORY_LIMIT in session.rs)
+const int CONST_RUST_HISTORY_LIMIT = 200;
+
+// SaveFormat::JSON in rust/rust_lib/src/store_data.rs
+const int CONST_RUST_SAVE_FORMAT_JSON = 0;
+
 // to handle app states
 enum DataStatus { loading, ready, error }
 
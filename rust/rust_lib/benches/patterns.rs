@@ -0,0 +1,106 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Pattern and candidate benchmarks
+check_all_elements is what rust_matrix.dart calls after every edit, it is
+measured on the FFI buffer like the app passes it. Candidates are measured
+on the grid layer : set_all_candidates (FFI and Grid) and cell_options,
+classic and with a variant so the Rules path is part of the numbers.
+
+  cargo bench --bench patterns
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use rust_backend::check_all_elements;
+use rust_backend::constraint::{Variant, VariantKind};
+use rust_backend::grid::{CellIndex, Grid, GRID_CELLS};
+use rust_backend::patterns::{cell_options, check_grid_for_patterns, update_patterns_at};
+use rust_backend::set_all_candidates;
+use rust_backend::storage::parse_puzzle_string;
+
+const PUZZLE: &str = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+
+fn puzzle() -> Grid {
+    let mut grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+    grid.set_all_candidates();
+    grid
+}
+
+fn patterns(c: &mut Criterion) {
+    let grid = puzzle();
+    let elements = grid.to_ffi();
+    let mut group = c.benchmark_group("patterns");
+
+    group.bench_function("check_all_elements", |b| {
+        b.iter_batched_ref(
+            || elements.clone(),
+            |elements| unsafe { check_all_elements(elements.as_mut_ptr(), GRID_CELLS) },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("check_grid_for_patterns", |b| {
+        b.iter_batched_ref(|| grid.clone(), check_grid_for_patterns, BatchSize::SmallInput)
+    });
+    let changed = [CellIndex::new(40).unwrap()];
+    group.bench_function("update_patterns_at", |b| {
+        b.iter_batched_ref(|| grid.clone(), |grid| update_patterns_at(grid, &changed), BatchSize::SmallInput)
+    });
+    group.finish();
+}
+
+fn candidates(c: &mut Criterion) {
+    let grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+    let mut variant = grid.clone();
+    variant.add_variant(Variant::from_kind(VariantKind::ANTI_KNIGHT).unwrap()).unwrap();
+    let elements = grid.to_ffi();
+    let mut group = c.benchmark_group("candidates");
+
+    group.bench_function("set_all_candidates ffi", |b| {
+        b.iter_batched_ref(
+            || elements.clone(),
+            |elements| unsafe { set_all_candidates(elements.as_mut_ptr(), 9, 9) },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("set_all_candidates", |b| {
+        b.iter_batched_ref(|| grid.clone(), Grid::set_all_candidates, BatchSize::SmallInput)
+    });
+    group.bench_function("cell_options", |b| b.iter(|| cell_options(black_box(&grid))));
+    group.bench_function("cell_options anti-knight", |b| b.iter(|| cell_options(black_box(&variant))));
+    group.finish();
+}
+
+criterion_group!(benches, patterns, candidates);
+criterion_main!(benches);
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,91 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Solver benchmarks
+Every backend of solver::BACKENDS on a corpus of hard classic puzzles
+(the ones that take the most search), solve and count_solutions with the
+limit the generator uses (2), so backend_for can be checked against numbers.
+
+  cargo bench --bench solver
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use rust_backend::grid::Grid;
+use rust_backend::solver::BACKENDS;
+use rust_backend::storage::parse_puzzle_string;
+
+const HARD: [(&str, &str); 6] = [
+    ("inkala", "800000000003600000070090200050007000000045700000100030001000068008500010090000400"),
+    ("easter monster", "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"),
+    ("escargot", "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3.."),
+    ("top95 1", "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......"),
+    ("top95 2", "52...6.........7.13...........4..8..6......5...........418.........3..2...87....."),
+    ("top95 3", "6.....8.3.4.7.................5.4.7.3..2.....1.6.......2.....5.....8.6......1...."),
+];
+
+fn corpus() -> Vec<(&'static str, Grid)> {
+    HARD.iter()
+        .map(|&(name, puzzle)| (name, Grid::from_givens(&parse_puzzle_string(puzzle).unwrap()).unwrap()))
+        .collect()
+}
+
+fn solve(c: &mut Criterion) {
+    let corpus = corpus();
+    let mut group = c.benchmark_group("solve");
+    for backend in BACKENDS {
+        for (name, grid) in &corpus {
+            group.bench_with_input(BenchmarkId::new(backend.name(), name), grid, |b, grid| {
+                b.iter(|| backend.solve(black_box(grid)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn count_solutions(c: &mut Criterion) {
+    let corpus = corpus();
+    let mut group = c.benchmark_group("count_solutions");
+    for backend in BACKENDS {
+        for (name, grid) in &corpus {
+            group.bench_with_input(BenchmarkId::new(backend.name(), name), grid, |b, grid| {
+                b.iter(|| backend.count_solutions(black_box(grid), 2))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, solve, count_solutions);
+criterion_main!(benches);
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
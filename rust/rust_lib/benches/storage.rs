@@ -0,0 +1,91 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Save / load benchmarks
+Round trips of a game in progress (givens and candidates) :
+save_grid + load_grid through a file for both save formats, and the
+in memory encodings alone (AppData JSON, encode_binary / decode_binary)
+to tell serialization from file system time.
+
+  cargo bench --bench storage
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rust_backend::grid::Grid;
+use rust_backend::storage::{decode_binary, encode_binary, load_grid, parse_puzzle_string, save_grid, AppData, SaveFormat};
+
+const PUZZLE: &str = "52...6.........7.13...........4..8..6......5...........418.........3..2...87.....";
+
+fn game() -> Grid {
+    let mut grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+    grid.set_all_candidates();
+    grid
+}
+
+fn round_trip(c: &mut Criterion) {
+    let grid = game();
+    let dir = std::env::temp_dir();
+    let mut group = c.benchmark_group("save_load");
+
+    for (name, format) in [("json", SaveFormat::JSON), ("binary", SaveFormat::BINARY)] {
+        let path = dir.join(format!("rust_backend_bench.{name}"));
+        let path = path.to_str().unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                save_grid(black_box(&grid), path, format).unwrap();
+                load_grid(path).unwrap()
+            })
+        });
+        let _ = std::fs::remove_file(path);
+    }
+    group.finish();
+}
+
+fn encoding(c: &mut Criterion) {
+    let grid = game();
+    let mut group = c.benchmark_group("encoding");
+
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            let json = serde_json::to_string(&AppData::from_grid(black_box(&grid))).unwrap();
+            serde_json::from_str::<AppData>(&json).unwrap().to_grid().unwrap()
+        })
+    });
+    group.bench_function("binary", |b| b.iter(|| decode_binary(&encode_binary(black_box(&grid))).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, round_trip, encoding);
+criterion_main!(benches);
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,58 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Build script
+With the "c-header" feature the C header for all exported functions and
+DartToRustElementFFI is regenerated into include/rust_backend.h (cbindgen,
+settings in cbindgen.toml). Normal builds do nothing here.
+*/
+
+fn main() {
+    #[cfg(feature = "c-header")]
+    generate_c_header();
+}
+
+#[cfg(feature = "c-header")]
+fn generate_c_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).expect("valid cbindgen.toml");
+
+    cbindgen::generate_with_config(&crate_dir, config)
+        .expect("C header generation failed")
+        .write_to_file(format!("{crate_dir}/include/rust_backend.h"));
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
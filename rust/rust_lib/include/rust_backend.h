@@ -0,0 +1,492 @@
+#ifndef RUST_BACKEND_H
+#define RUST_BACKEND_H
+
+/* Generated by cbindgen from rust/rust_lib, do not edit. Regenerate with: cargo build --features c-header */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define MAX_UINT 255
+
+#define CONST_MATRIX_SIZE 9
+
+#define CONST_MATRIX_ELEMENTS 81
+
+#define constSelectedNumberListSize CONST_MATRIX_SIZE
+
+#define constSelectedNumStateListSize 2
+
+#define constSelectedPatternListSize 4
+
+#define constRequestedElementHighLightTypeSize 5
+
+#define constRequestedCandHighLightTypeSize CONST_MATRIX_SIZE
+
+#define constPatternListOff MAX_UINT
+
+#define ABI_VERSION 6
+
+#define ELEMENT_FFI_SIZE 32
+
+#define GRID_ELEMENT_FFI_SIZE 46
+
+#define PatternList_HI_LIGHT_ON 0
+
+#define PatternList_PAIRS 1
+
+#define PatternList_SINGLES 2
+
+#define PatternList_GIVENS 3
+
+#define NumStateListIndex_GIVENS 0
+
+#define NumStateListIndex_FUTUREUSE 1
+
+#if defined(RUST_BACKEND_STORAGE)
+#define SaveFormat_JSON 0
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define SaveFormat_BINARY 1
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define PUZZLE_STRING_EMPTY '.'
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define PENCILMARK_FLAT_LEN ((size_t)CONST_MATRIX_ELEMENTS * (size_t)constSelectedNumberListSize)
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define PencilMarkFormat_FLAT 0
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define PencilMarkFormat_GRID 1
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_UNGRADED 0
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_EASY 1
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_MEDIUM 2
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_HARD 3
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_EXPERT 4
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_EXTREME 5
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define DifficultyGrade_ANY MAX_UINT
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define BINARY_VERSION 4
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define SHARE_CODE_VERSION 1
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+#define SHARE_CODE_VERSION_SIZED 2
+#endif
+
+#define VariantKind_DIAGONAL 1
+
+#define VariantKind_ANTI_KNIGHT 2
+
+#define VariantKind_ANTI_KING 3
+
+#define VariantKind_NON_CONSECUTIVE 4
+
+#define VariantKind_REGIONS 5
+
+#define VariantKind_THERMO 6
+
+#define VariantKind_ARROW 7
+
+#define VariantKind_LITTLE_KILLER 8
+
+#define SAMURAI_BOARD_SIZE 21
+
+#if defined(RUST_BACKEND_SESSION)
+#define DEFAULT_HISTORY_LIMIT 200
+#endif
+
+typedef struct {
+  uint8_t row;
+  uint8_t col;
+  uint8_t selectedNum;
+  uint8_t selectedNumStateList[constSelectedNumStateListSize];
+  uint8_t selectedCandList[constSelectedNumberListSize];
+  uint8_t selectedPatternList[constSelectedPatternListSize];
+  uint8_t requestedElementHighLightType[constRequestedElementHighLightTypeSize];
+  uint8_t requestedCandHighLightType[constRequestedCandHighLightTypeSize];
+} DartToRustElementFFI;
+
+#if defined(RUST_BACKEND_SESSION)
+typedef uint64_t SessionHandle;
+#endif
+
+typedef struct {
+  uint8_t row;
+  uint8_t col;
+  uint8_t selectedNum;
+  uint8_t selectedNumStateList[constSelectedNumStateListSize];
+  uint8_t selectedCandList[MAX_GRID_SIZE];
+  uint8_t selectedPatternList[constSelectedPatternListSize];
+  uint8_t requestedElementHighLightType[constRequestedElementHighLightTypeSize];
+  uint8_t requestedCandHighLightType[MAX_GRID_SIZE];
+} GridElementFFI;
+
+
+
+
+
+#if defined(RUST_BACKEND_SESSION)
+#define INVALID_SESSION 0
+#endif
+
+uint32_t abi_version(void);
+
+uint32_t element_ffi_size(void);
+
+uint32_t grid_element_ffi_size(void);
+
+DartToRustElementFFI *create_matrix(uint8_t rows, uint8_t cols);
+
+#if defined(RUST_BACKEND_PATTERNS)
+int update_cell(DartToRustElementFFI *ptr,
+                uint8_t rows,
+                uint8_t cols,
+                uint8_t idx,
+                uint8_t remove_candidates,
+                uint8_t *dirty,
+                uint32_t dirty_len);
+#endif
+
+int erase_matrix(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, uint8_t erase_givens);
+
+int set_all_candidates(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols);
+
+#if defined(RUST_BACKEND_PATTERNS)
+int update_matrix(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols);
+#endif
+
+void free_matrix(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols);
+
+void free_rust_string(char *s);
+
+#if defined(RUST_BACKEND_PATTERNS)
+int check_all_elements(DartToRustElementFFI *ptr, size_t len);
+#endif
+
+#if defined(RUST_BACKEND_PATTERNS)
+int check_one_element(DartToRustElementFFI *ptr, size_t idx);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int save_data(const DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, const char *path);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int save_data_format(const DartToRustElementFFI *ptr,
+                     uint8_t rows,
+                     uint8_t cols,
+                     const char *path,
+                     uint8_t format);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int load_data(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, const char *path);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int save_sdk(const DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, const char *path);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int load_sdk(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, const char *path);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int sdm_puzzle_count(const char *path);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int load_sdm_puzzle(DartToRustElementFFI *ptr,
+                    uint8_t rows,
+                    uint8_t cols,
+                    const char *path,
+                    uint32_t index);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int append_sdm_puzzle(const DartToRustElementFFI *ptr,
+                      uint8_t rows,
+                      uint8_t cols,
+                      const char *path);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int import_puzzle_string(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, const char *puzzle);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+char *export_puzzle_string(const DartToRustElementFFI *ptr,
+                           uint8_t rows,
+                           uint8_t cols,
+                           uint8_t givens_only);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int import_pencilmark_string(DartToRustElementFFI *ptr,
+                             uint8_t rows,
+                             uint8_t cols,
+                             const char *pencilmarks);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+char *export_pencilmark_string(const DartToRustElementFFI *ptr,
+                               uint8_t rows,
+                               uint8_t cols,
+                               uint8_t format);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_add_puzzle(const char *library_path,
+                       const DartToRustElementFFI *ptr,
+                       uint8_t rows,
+                       uint8_t cols,
+                       const char *id,
+                       uint8_t difficulty,
+                       const char *tags);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_import_sdm(const char *library_path,
+                       const char *sdm_path,
+                       const char *id_prefix,
+                       uint8_t difficulty);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_count(const char *library_path, uint8_t difficulty, uint8_t unsolved_only);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_next_unsolved(const char *library_path,
+                          uint8_t difficulty,
+                          DartToRustElementFFI *ptr,
+                          uint8_t rows,
+                          uint8_t cols);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_load_puzzle(const char *library_path,
+                        uint32_t index,
+                        DartToRustElementFFI *ptr,
+                        uint8_t rows,
+                        uint8_t cols);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_mark_solved(const char *library_path, uint32_t index, uint64_t time_ms);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int library_find(const char *library_path, const char *id);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+char *library_puzzle_id(const char *library_path, uint32_t index);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+char *encode_share_code(const DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols);
+#endif
+
+#if defined(RUST_BACKEND_STORAGE)
+int decode_share_code(DartToRustElementFFI *ptr, uint8_t rows, uint8_t cols, const char *code);
+#endif
+
+char *last_error_message(void);
+
+void clear_last_error(void);
+
+char *error_code_message(int code);
+
+#if defined(RUST_BACKEND_SOLVER)
+int samurai_solve(uint8_t *numbers, uint32_t len);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+SessionHandle session_create(void);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+SessionHandle session_create_sized(uint8_t size);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_grid_size(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_get_value(SessionHandle handle, uint8_t idx);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_get_candidates(SessionHandle handle, uint8_t idx);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_add_cage(SessionHandle handle, const uint8_t *cells, uint32_t len, uint16_t sum);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_clear_cages(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_cage_count(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_cage_of(SessionHandle handle, uint8_t idx);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_cage_sum(SessionHandle handle, uint32_t cage);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_cage_candidates(SessionHandle handle, uint8_t idx);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_add_variant(SessionHandle handle, uint8_t kind);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_add_line(SessionHandle handle,
+                     uint8_t kind,
+                     const uint8_t *cells,
+                     uint32_t len,
+                     uint16_t sum);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_hint_count(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_remove_variant(SessionHandle handle, uint8_t kind);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_has_variant(SessionHandle handle, uint8_t kind);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_set_regions(SessionHandle handle, const uint8_t *map, uint32_t len);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_region_of(SessionHandle handle, uint8_t idx);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_free(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_read(SessionHandle handle, GridElementFFI *ptr, uint32_t len);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_write(SessionHandle handle, const GridElementFFI *ptr, uint32_t len);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_set_value(SessionHandle handle, uint8_t idx, uint8_t value);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_toggle_candidate(SessionHandle handle, uint8_t idx, uint8_t value);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_erase(SessionHandle handle, uint8_t erase_givens);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_set_all_candidates(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_update_patterns(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_dirty_cells(SessionHandle handle, uint8_t *out, uint32_t len);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_undo(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_redo(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_set_settings(SessionHandle handle,
+                         uint8_t auto_patterns,
+                         uint8_t auto_remove_candidates,
+                         uint32_t history_limit);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_solution_count(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_conflict_count(SessionHandle handle);
+#endif
+
+#if defined(RUST_BACKEND_SESSION)
+int session_solve(SessionHandle handle);
+#endif
+
+#if (defined(RUST_BACKEND_SESSION) && defined(RUST_BACKEND_STORAGE))
+int session_new_puzzle(SessionHandle handle, const char *puzzle);
+#endif
+
+#if (defined(RUST_BACKEND_SESSION) && defined(RUST_BACKEND_STORAGE))
+int session_save(SessionHandle handle, const char *path, uint8_t format);
+#endif
+
+#if (defined(RUST_BACKEND_SESSION) && defined(RUST_BACKEND_STORAGE))
+int session_load(SessionHandle handle, const char *path);
+#endif
+
+#endif  /* RUST_BACKEND_H */
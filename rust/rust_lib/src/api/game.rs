@@ -0,0 +1,289 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Game API for Dart
+SudokuGame is opaque on the Dart side, it wraps one GameSession.
+Free functions work on puzzle strings ('.' = empty), 81 characters for the
+classic grid, the length selects the grid size (see puzzle_string.rs).
+*/
+
+use flutter_rust_bridge::frb;
+
+use crate::api::types::{AnalysisResult, ApiError, CageInfo, CellState, GameSettings, HintInfo, VariantInfo};
+
+use crate::error::SudokuError;
+
+use crate::generator::{generate, generate_sized};
+
+use crate::grid::{CellIndex, Digit, Geometry, Grid};
+
+use crate::killer::Cage;
+
+use crate::constraint::{Regions, Variant};
+
+use crate::patterns::{cell_peers, variant_hints};
+
+use crate::session::GameSession;
+
+use crate::solver::{count_solutions, solve};
+
+use crate::storage::{parse_puzzle_string, to_puzzle_string};
+
+// Checked against the grid size of the game
+fn cell_index(grid: &Grid, index: u8) -> Result<CellIndex, ApiError> {
+    Ok(grid.geometry().cell(index as usize).ok_or(SudokuError::IndexOutOfRange)?)
+}
+
+fn digit(grid: &Grid, value: u8) -> Result<Digit, ApiError> {
+    Ok(grid.geometry().digit(value).ok_or(SudokuError::IndexOutOfRange)?)
+}
+
+fn geometry(size: u8) -> Result<Geometry, ApiError> {
+    Ok(Geometry::from_size(size as usize).ok_or(SudokuError::DimensionMismatch)?)
+}
+
+fn puzzle_grid(puzzle: &str) -> Result<Grid, ApiError> {
+    Ok(Grid::from_givens(&parse_puzzle_string(puzzle)?)?)
+}
+
+#[frb(opaque)]
+pub struct SudokuGame {
+    session: GameSession,
+}
+
+impl SudokuGame {
+    #[frb(sync)]
+    pub fn new() -> SudokuGame {
+        SudokuGame { session: GameSession::new() }
+    }
+
+    // Empty game, size 4, 6, 9, 12 or 16
+    #[frb(sync)]
+    pub fn with_size(size: u8) -> Result<SudokuGame, ApiError> {
+        Ok(SudokuGame { session: GameSession::with_geometry(geometry(size)?) })
+    }
+
+    #[frb(sync)]
+    pub fn from_puzzle(puzzle: String) -> Result<SudokuGame, ApiError> {
+        Ok(SudokuGame { session: GameSession::from_grid(puzzle_grid(&puzzle)?) })
+    }
+
+    // Rows (= columns) of the grid
+    #[frb(sync)]
+    pub fn size(&self) -> u8 {
+        self.session.grid().size() as u8
+    }
+
+    #[frb(sync)]
+    pub fn cells(&self) -> Vec<CellState> {
+        let grid = self.session.grid();
+        grid.geometry()
+            .all_cells()
+            .map(|idx| CellState::from_cell(grid.geometry(), idx, grid.cell(idx)))
+            .collect()
+    }
+
+    // Cells changed by the last edit, undo or redo, highlights included
+    #[frb(sync)]
+    pub fn dirty_cells(&self) -> Vec<CellState> {
+        let grid = self.session.grid();
+        self.session
+            .dirty_cells()
+            .iter()
+            .map(|&idx| CellState::from_cell(grid.geometry(), idx, grid.cell(idx)))
+            .collect()
+    }
+
+    // One character per cell, givens_only = false includes placed numbers
+    #[frb(sync)]
+    pub fn puzzle_string(&self, givens_only: bool) -> String {
+        to_puzzle_string(self.session.grid(), givens_only)
+    }
+
+    // value 0 clears the cell, returns false if nothing changed
+    #[frb(sync)]
+    pub fn set_value(&mut self, index: u8, value: u8) -> Result<bool, ApiError> {
+        let grid = self.session.grid();
+        let value = if value == 0 { None } else { Some(digit(grid, value)?) };
+        let index = cell_index(grid, index)?;
+        Ok(self.session.set_value(index, value)?)
+    }
+
+    #[frb(sync)]
+    pub fn toggle_candidate(&mut self, index: u8, value: u8) -> Result<bool, ApiError> {
+        let grid = self.session.grid();
+        let (index, digit) = (cell_index(grid, index)?, digit(grid, value)?);
+        Ok(self.session.toggle_candidate(index, digit)?)
+    }
+
+    #[frb(sync)]
+    pub fn erase(&mut self, erase_givens: bool) -> bool {
+        self.session.erase(erase_givens)
+    }
+
+    #[frb(sync)]
+    pub fn set_all_candidates(&mut self) -> bool {
+        self.session.set_all_candidates()
+    }
+
+    #[frb(sync)]
+    pub fn undo(&mut self) -> bool {
+        self.session.undo()
+    }
+
+    #[frb(sync)]
+    pub fn redo(&mut self) -> bool {
+        self.session.redo()
+    }
+
+    // Killer cage, part of the puzzle, clears undo / redo
+    #[frb(sync)]
+    pub fn add_cage(&mut self, cells: Vec<u8>, sum: u16) -> Result<(), ApiError> {
+        let grid = self.session.grid();
+        let cells = cells.into_iter().map(|i| cell_index(grid, i)).collect::<Result<Vec<_>, _>>()?;
+        let cage = Cage::new(grid.geometry(), cells, sum)?;
+        Ok(self.session.add_cage(cage)?)
+    }
+
+    #[frb(sync)]
+    pub fn clear_cages(&mut self) {
+        self.session.clear_cages();
+    }
+
+    #[frb(sync)]
+    pub fn cages(&self) -> Vec<CageInfo> {
+        self.session.grid().cages().iter().map(CageInfo::from).collect()
+    }
+
+    // kind = VariantKind (diagonal, anti-knight, anti-king, non-consecutive), clears undo / redo
+    #[frb(sync)]
+    pub fn add_variant(&mut self, kind: u8) -> Result<(), ApiError> {
+        Ok(self.session.add_variant(Variant::from_kind(kind)?)?)
+    }
+
+    #[frb(sync)]
+    pub fn remove_variant(&mut self, kind: u8) -> bool {
+        self.session.remove_variant(kind)
+    }
+
+    // Thermo, arrow (circle first) or little killer line, sum only for little killers
+    #[frb(sync)]
+    pub fn add_line(&mut self, kind: u8, cells: Vec<u8>, sum: u16) -> Result<(), ApiError> {
+        let variant = Variant::from_parts(self.session.grid().geometry(), kind, &cells, sum)?;
+        Ok(self.session.add_variant(variant)?)
+    }
+
+    #[frb(sync)]
+    pub fn variants(&self) -> Vec<VariantInfo> {
+        self.session.grid().variants().iter().map(VariantInfo::from).collect()
+    }
+
+    // Candidate eliminations of the variant rules, with explanation
+    #[frb(sync)]
+    pub fn variant_hints(&self) -> Vec<HintInfo> {
+        let grid = self.session.grid();
+        variant_hints(grid).iter().map(|hint| HintInfo::from_hint(hint, grid.size())).collect()
+    }
+
+    // One region number 0..size per cell, replaces the boxes, clears undo / redo
+    #[frb(sync)]
+    pub fn set_regions(&mut self, regions: Vec<u8>) -> Result<(), ApiError> {
+        let regions = Regions::new(self.session.grid().geometry(), regions)?;
+        Ok(self.session.set_regions(regions)?)
+    }
+
+    // Region number of every cell, the boxes without jigsaw regions
+    #[frb(sync)]
+    pub fn regions(&self) -> Vec<u8> {
+        let grid = self.session.grid();
+        grid.geometry().all_cells().map(|idx| grid.region_of(idx) as u8).collect()
+    }
+
+    // Cells sharing a unit with cell index, for highlighting
+    #[frb(sync)]
+    pub fn peers(&self, index: u8) -> Result<Vec<u8>, ApiError> {
+        let grid = self.session.grid();
+        let idx = cell_index(grid, index)?;
+        Ok(cell_peers(grid, idx).iter().map(|p| p.get() as u8).collect())
+    }
+
+    // Numbers cell index can still take, peers and cage sum included
+    #[frb(sync)]
+    pub fn candidates(&self, index: u8) -> Result<Vec<u8>, ApiError> {
+        let idx = cell_index(self.session.grid(), index)?;
+        Ok(self.session.candidates(idx).iter().map(Digit::get).collect())
+    }
+
+    #[frb(sync)]
+    pub fn settings(&self) -> GameSettings {
+        self.session.settings().into()
+    }
+
+    #[frb(sync)]
+    pub fn set_settings(&mut self, settings: GameSettings) {
+        self.session.set_settings(settings.into());
+    }
+
+    // Solver runs on first call after a change
+    pub fn analyze(&mut self) -> AnalysisResult {
+        AnalysisResult::from(self.session.analysis())
+    }
+
+    pub fn solve(&mut self) -> Result<bool, ApiError> {
+        Ok(self.session.solve()?)
+    }
+}
+
+// Solution as puzzle string
+pub fn solve_puzzle(puzzle: String) -> Result<String, ApiError> {
+    let solved = solve(&puzzle_grid(&puzzle)?).ok_or(SudokuError::NotFound)?;
+    Ok(to_puzzle_string(&solved, false))
+}
+
+// 0, 1 or 2 (= more than one)
+pub fn solution_count(puzzle: String) -> Result<u32, ApiError> {
+    Ok(count_solutions(&puzzle_grid(&puzzle)?, 2) as u32)
+}
+
+// Same seed gives the same puzzle
+pub fn generate_puzzle(seed: u64, min_givens: u8) -> String {
+    to_puzzle_string(&generate(seed, min_givens as usize), true)
+}
+
+// Puzzle of size 4, 6, 9, 12 or 16, 16x16 takes a few seconds.
+// Never #[frb(sync)] : Dart gets a Future, the UI isolate is not blocked.
+pub fn generate_sized_puzzle(size: u8, seed: u64, min_givens: u16) -> Result<String, ApiError> {
+    Ok(to_puzzle_string(&generate_sized(geometry(size)?, seed, min_givens as usize), true))
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
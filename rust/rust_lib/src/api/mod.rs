@@ -0,0 +1,63 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+flutter_rust_bridge API
+Everything public below crate::api is picked up by flutter_rust_bridge_codegen
+(config : flutter_rust_bridge.yaml in the Flutter project root, run
+scripts/generate_bindings.sh). The Dart classes are generated into lib/src/rust,
+frb_generated.rs is added to this crate by the generator.
+
+Not used by the app yet : no bindings are generated or checked in, the
+Flutter project has no flutter_rust_bridge dependency and the module is
+behind the non-default feature "api". The app runs on the dart:ffi session_*
+functions (rust_matrix.dart). Moving over means generating the bindings,
+adding flutter_rust_bridge 2.11.1 to pubspec.yaml and building the app
+library with --features api.
+
+#[frb(sync)] → plain Dart call, only for cheap functions
+no attribute → Dart Future, runs on the bridge thread pool
+               (solving, generating, analysis)
+*/
+
+pub mod game;
+pub mod types;
+
+use flutter_rust_bridge::frb;
+
+// Called once by RustLib.init() on the Dart side
+#[frb(init)]
+pub fn init_app() {
+    flutter_rust_bridge::setup_default_user_utils();
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
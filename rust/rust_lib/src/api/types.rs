@@ -0,0 +1,194 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Plain data types mirrored as Dart classes by flutter_rust_bridge.
+Only primitive fields and Vecs, so the generated Dart code needs no
+opaque handles for them.
+*/
+
+use crate::error::SudokuError;
+
+use crate::grid::{Cell, CellIndex, Digit, Geometry};
+
+use crate::killer::Cage;
+
+use crate::constraint::Variant;
+
+use crate::patterns::Hint;
+
+use crate::session::{Analysis, SessionSettings};
+
+use crate::storage::to_puzzle_string;
+
+// Error thrown on the Dart side, code is the stable SudokuError number
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl From<SudokuError> for ApiError {
+    fn from(err: SudokuError) -> Self {
+        ApiError { code: err.code(), message: err.message().to_string() }
+    }
+}
+
+// One cell, same content as DartToRustElementFFI
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellState {
+    pub row: u8,
+    pub col: u8,
+    // 0 = empty
+    pub value: u8,
+    pub given: bool,
+    // digits 1..=size
+    pub candidates: Vec<u8>,
+    pub patterns: Vec<u8>,
+    pub element_highlight: Vec<u8>,
+    pub candidate_highlight: Vec<u8>,
+}
+
+impl CellState {
+    pub(crate) fn from_cell(geometry: Geometry, idx: CellIndex, cell: &Cell) -> CellState {
+        CellState {
+            row: geometry.row(idx) as u8,
+            col: geometry.col(idx) as u8,
+            value: cell.value.map_or(0, Digit::get),
+            given: cell.given,
+            candidates: cell.candidates.iter().map(Digit::get).collect(),
+            patterns: cell.patterns.to_vec(),
+            element_highlight: cell.element_highlight.to_vec(),
+            candidate_highlight: cell.candidate_highlight[..geometry.size()].to_vec(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GameSettings {
+    pub auto_patterns: bool,
+    pub auto_remove_candidates: bool,
+    pub history_limit: u32,
+}
+
+impl From<SessionSettings> for GameSettings {
+    fn from(s: SessionSettings) -> Self {
+        GameSettings {
+            auto_patterns: s.auto_patterns,
+            auto_remove_candidates: s.auto_remove_candidates,
+            history_limit: s.history_limit as u32,
+        }
+    }
+}
+
+impl From<GameSettings> for SessionSettings {
+    fn from(s: GameSettings) -> Self {
+        SessionSettings {
+            auto_patterns: s.auto_patterns,
+            auto_remove_candidates: s.auto_remove_candidates,
+            history_limit: s.history_limit as usize,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnalysisResult {
+    // Cell indices, row major
+    pub conflicts: Vec<u8>,
+    // 0, 1 or 2 (= more than one)
+    pub solution_count: u32,
+    // Puzzle string, empty if there is no solution
+    pub solution: String,
+}
+
+impl From<&Analysis> for AnalysisResult {
+    fn from(a: &Analysis) -> Self {
+        AnalysisResult {
+            conflicts: a.conflicts.iter().map(|idx| idx.get() as u8).collect(),
+            solution_count: a.solution_count as u32,
+            solution: a.solution.as_ref().map_or_else(String::new, |g| to_puzzle_string(g, false)),
+        }
+    }
+}
+
+// Killer cage, cell indices row major
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CageInfo {
+    pub cells: Vec<u8>,
+    pub sum: u16,
+}
+
+impl From<&Cage> for CageInfo {
+    fn from(cage: &Cage) -> Self {
+        CageInfo { cells: cage.cells().iter().map(|idx| idx.get() as u8).collect(), sum: cage.sum() }
+    }
+}
+
+// Variant rule, cells = region map for regions, line cells otherwise
+// (arrow circle first), sum only for little killers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantInfo {
+    pub kind: u8,
+    pub cells: Vec<u8>,
+    pub sum: u16,
+}
+
+impl From<&Variant> for VariantInfo {
+    fn from(variant: &Variant) -> Self {
+        let (cells, sum) = variant.parts();
+        VariantInfo { kind: variant.kind(), cells, sum }
+    }
+}
+
+// Candidates one variant rule removes from one cell
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HintInfo {
+    pub kind: u8,
+    pub cell: u8,
+    pub removed: Vec<u8>,
+    pub cause: Vec<u8>,
+    pub message: String,
+}
+
+impl HintInfo {
+    pub fn from_hint(hint: &Hint, size: usize) -> HintInfo {
+        HintInfo {
+            kind: hint.kind,
+            cell: hint.cell.get() as u8,
+            removed: hint.removed.iter().map(Digit::get).collect(),
+            cause: hint.cause.iter().map(|idx| idx.get() as u8).collect(),
+            message: hint.message(size),
+        }
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
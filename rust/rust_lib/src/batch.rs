@@ -0,0 +1,152 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+
+/* 
+Batch solving and grading
+Puzzle packs are curated in bulk : solve_batch and grade_batch take many
+puzzle strings (puzzle_string.rs), work on them across threads and return
+one result per puzzle in input order, a puzzle string that does not parse
+only fails its own entry. The work per puzzle is what the app does :
+solver::backend_for counts and solves, grader::grade grades.
+
+threads = 0 uses the global rayon pool (one thread per core), any other
+number a pool of its own for this call.
+Feature "batch", the app library does not need the thread pool.
+*/
+
+use rayon::prelude::*;
+
+use crate::error::SudokuError;
+
+use crate::grader::grade;
+
+use crate::grid::Grid;
+
+use crate::puzzle_string::{parse_puzzle_string, to_puzzle_string};
+
+use crate::solver::backend_for;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solved {
+    // Puzzle string of the filled grid, None without a solution
+    pub solution: Option<String>,
+    // Number of solutions, counting stops at 2
+    pub solutions: usize,
+}
+
+pub fn solve_batch<S: AsRef<str> + Sync>(puzzles: &[S], threads: usize) -> Result<Vec<Result<Solved, SudokuError>>, SudokuError> {
+    run_batch(puzzles, threads, |grid| {
+        let backend = backend_for(grid);
+        Solved {
+            solution: backend.solve(grid).map(|solved| to_puzzle_string(&solved, false)),
+            solutions: backend.count_solutions(grid, 2),
+        }
+    })
+}
+
+// DifficultyGrade per puzzle, UNGRADED without a unique solution
+pub fn grade_batch<S: AsRef<str> + Sync>(puzzles: &[S], threads: usize) -> Result<Vec<Result<u8, SudokuError>>, SudokuError> {
+    run_batch(puzzles, threads, grade)
+}
+
+// work on every parsed puzzle, Err only if the thread pool can not start
+pub fn run_batch<S, T, F>(puzzles: &[S], threads: usize, work: F) -> Result<Vec<Result<T, SudokuError>>, SudokuError>
+where
+    S: AsRef<str> + Sync,
+    T: Send,
+    F: Fn(&Grid) -> T + Sync,
+{
+    let run = || {
+        puzzles
+            .par_iter()
+            .map(|puzzle| {
+                parse_puzzle_string(puzzle.as_ref()).and_then(|numbers| Grid::from_givens(&numbers)).map(|grid| work(&grid))
+            })
+            .collect()
+    };
+
+    if threads == 0 {
+        return Ok(run());
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|_| SudokuError::AllocationFailed)?;
+    Ok(pool.install(run))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Quick and slow puzzles mixed, the threads finish them out of order
+    const PUZZLES: [&str; 4] = [
+        "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+        "..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97..",
+        "100920000524010000000000070050008102000000000402700090060000000000030945000071006",
+        "not a puzzle",
+    ];
+
+    fn batch() -> Vec<&'static str> {
+        PUZZLES.iter().cycle().take(40).copied().collect()
+    }
+
+    #[test]
+    fn batch_results_keep_the_input_order() {
+        let puzzles = batch();
+        let one_by_one: Vec<Result<u8, SudokuError>> = puzzles
+            .iter()
+            .map(|puzzle| parse_puzzle_string(puzzle).and_then(|numbers| Grid::from_givens(&numbers)).map(|grid| grade(&grid)))
+            .collect();
+
+        for threads in [0, 1, 4] {
+            assert_eq!(grade_batch(&puzzles, threads).unwrap(), one_by_one, "{threads} threads");
+        }
+
+        let solved = solve_batch(&puzzles, 4).unwrap();
+        for (puzzle, result) in puzzles.iter().zip(&solved) {
+            match parse_puzzle_string(puzzle) {
+                Ok(numbers) => {
+                    let result = result.as_ref().unwrap();
+                    assert_eq!(result.solutions, 1);
+                    let solution = parse_puzzle_string(result.solution.as_ref().unwrap()).unwrap();
+                    assert!(numbers.iter().zip(&solution).all(|(&given, &n)| given == 0 || given == n), "{puzzle}");
+                }
+                Err(error) => assert_eq!(result, &Err(error)),
+            }
+        }
+    }
+}
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,275 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+
+/* 
+sudoku-cli
+Command line tool on top of the rust_backend library (feature "cli"), for
+puzzle packs and for debugging save files without the Flutter app :
+  solve    → solution of every puzzle string
+  grade    → difficulty grade of every puzzle string (grader.rs)
+  generate → uniquely solvable puzzles from a seed, optionally of one grade
+  validate → puzzle strings or save files : no conflicts, one solution
+  convert  → save file (JSON or binary) or puzzle string → puzzle string,
+             or → save file with --output
+  hints    → steps of the grader for a puzzle string or save file
+Puzzle strings come from the arguments and --file (one per line, blank
+lines and '#' lines are skipped), results are printed one line per puzzle
+in the same order. Any failed or invalid puzzle gives exit code 1.
+
+  cargo run --features cli --bin sudoku-cli -- grade --file pack.sdm
+*/
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+
+use rust_backend::batch::{grade_batch, solve_batch, Solved};
+use rust_backend::error::SudokuError;
+use rust_backend::generator::generate_sized;
+use rust_backend::grader::{grade, trace};
+use rust_backend::grid::{Geometry, Grid};
+use rust_backend::patterns::find_conflicts;
+use rust_backend::solver::backend_for;
+use rust_backend::storage::{load_grid, parse_puzzle_string, save_grid, to_puzzle_string, DifficultyGrade, SaveFormat};
+
+#[derive(Parser)]
+#[command(name = "sudoku-cli", version, about = "Solve, grade, generate, validate and convert Sudoku puzzles")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve puzzle strings
+    Solve(Puzzles),
+    /// Grade puzzle strings (easy, medium, hard, expert, extreme)
+    Grade(Puzzles),
+    /// Generate uniquely solvable puzzles
+    Generate(Generate),
+    /// Check puzzle strings or save files for conflicts and a unique solution
+    Validate(Puzzles),
+    /// Convert a save file or puzzle string to a puzzle string or save file
+    Convert(Convert),
+    /// Print the steps of the grader for a puzzle string or save file
+    Hints {
+        input: String,
+    },
+}
+
+#[derive(Args)]
+struct Puzzles {
+    /// Puzzle strings (save file paths for validate)
+    inputs: Vec<String>,
+    /// File with one puzzle string per line
+    #[arg(long)]
+    file: Option<String>,
+    /// Worker threads, 0 = one per core
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+}
+
+#[derive(Args)]
+struct Generate {
+    #[arg(long, default_value_t = 1)]
+    count: usize,
+    /// Seed of the first puzzle, the next ones use seed + 1, ...
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    /// Grid size : 4, 6, 9, 12 or 16
+    #[arg(long, default_value_t = 9)]
+    size: usize,
+    #[arg(long, default_value_t = 0)]
+    min_givens: usize,
+    /// Only keep puzzles of this grade
+    #[arg(long)]
+    grade: Option<String>,
+}
+
+#[derive(Args)]
+struct Convert {
+    /// Save file path or puzzle string
+    input: String,
+    /// Write a save file instead of printing the puzzle string
+    #[arg(long)]
+    output: Option<String>,
+    /// Save in the binary format instead of JSON
+    #[arg(long)]
+    binary: bool,
+    /// Puzzle string with every placed number, not only the givens
+    #[arg(long)]
+    all: bool,
+}
+
+// Tries per requested puzzle before generate --grade gives up
+const GENERATE_TRIES: usize = 1000;
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Ok(false) if a puzzle failed or is invalid
+fn run(command: Command) -> Result<bool, SudokuError> {
+    match command {
+        Command::Solve(puzzles) => {
+            let results = solve_batch(&puzzle_list(&puzzles)?, puzzles.threads)?;
+            Ok(print_results(results, |solved| match solved {
+                Solved { solution: Some(solution), solutions: 1 } => (solution, true),
+                Solved { solution: Some(solution), .. } => (format!("{solution} (multiple solutions)"), false),
+                Solved { solution: None, .. } => ("no solution".to_string(), false),
+            }))
+        }
+        Command::Grade(puzzles) => {
+            let results = grade_batch(&puzzle_list(&puzzles)?, puzzles.threads)?;
+            Ok(print_results(results, |grade| (DifficultyGrade::name(grade).to_string(), grade != DifficultyGrade::UNGRADED)))
+        }
+        Command::Generate(options) => generate(&options),
+        Command::Validate(puzzles) => {
+            let mut valid = true;
+            for input in puzzle_list(&puzzles)? {
+                let status = read_grid(&input).map(|grid| validate(&grid));
+                valid &= status.as_ref().is_ok_and(|status| status == "ok");
+                println!("{}", status.unwrap_or_else(|err| format!("error: {err}")));
+            }
+            Ok(valid)
+        }
+        Command::Convert(options) => {
+            let grid = read_grid(&options.input)?;
+            match &options.output {
+                Some(path) => {
+                    let format = if options.binary { SaveFormat::BINARY } else { SaveFormat::JSON };
+                    save_grid(&grid, path, format)?;
+                }
+                None => println!("{}", to_puzzle_string(&grid, !options.all)),
+            }
+            Ok(true)
+        }
+        Command::Hints { input } => {
+            let grid = read_grid(&input)?;
+            for step in trace(&grid) {
+                println!("{}", step.message(grid.size()));
+            }
+            let grade = grade(&grid);
+            println!("grade : {}", DifficultyGrade::name(grade));
+            Ok(grade != DifficultyGrade::UNGRADED)
+        }
+    }
+}
+
+// Arguments first, then the lines of --file
+fn puzzle_list(puzzles: &Puzzles) -> Result<Vec<String>, SudokuError> {
+    let mut list = puzzles.inputs.clone();
+    if let Some(path) = &puzzles.file {
+        let content = fs::read_to_string(path).map_err(|_| SudokuError::ReadFailed)?;
+        let lines = content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+        list.extend(lines.map(String::from));
+    }
+    Ok(list)
+}
+
+// Save file (JSON or binary) if the path exists, puzzle string otherwise
+fn read_grid(input: &str) -> Result<Grid, SudokuError> {
+    if Path::new(input).is_file() {
+        load_grid(input)
+    } else {
+        parse_puzzle_string(input).and_then(|numbers| Grid::from_givens(&numbers))
+    }
+}
+
+// One line per result, the bool of line says if the puzzle passed
+fn print_results<T>(results: Vec<Result<T, SudokuError>>, line: impl Fn(T) -> (String, bool)) -> bool {
+    let mut passed = true;
+    for result in results {
+        let (text, ok) = result.map(&line).unwrap_or_else(|err| (format!("error: {err}"), false));
+        passed &= ok;
+        println!("{text}");
+    }
+    passed
+}
+
+fn validate(grid: &Grid) -> String {
+    let conflicts = find_conflicts(grid);
+    if !conflicts.is_empty() {
+        let cells: Vec<String> = conflicts
+            .iter()
+            .map(|idx| format!("r{}c{}", idx.get() / grid.size() + 1, idx.get() % grid.size() + 1))
+            .collect();
+        return format!("conflicts at {}", cells.join(", "));
+    }
+
+    match backend_for(grid).count_solutions(grid, 2) {
+        0 => "no solution".to_string(),
+        1 => "ok".to_string(),
+        _ => "multiple solutions".to_string(),
+    }
+}
+
+fn generate(options: &Generate) -> Result<bool, SudokuError> {
+    let geometry = Geometry::from_size(options.size).ok_or(SudokuError::DimensionMismatch)?;
+    let wanted = match &options.grade {
+        Some(name) => Some(
+            (DifficultyGrade::EASY..=DifficultyGrade::EXTREME)
+                .find(|&grade| DifficultyGrade::name(grade) == name.to_lowercase())
+                .ok_or(SudokuError::NotFound)?,
+        ),
+        None => None,
+    };
+
+    let mut found = 0;
+    for seed in (options.seed..).take(options.count * GENERATE_TRIES) {
+        if found == options.count {
+            break;
+        }
+        let puzzle = generate_sized(geometry, seed, options.min_givens);
+        if wanted.is_none_or(|wanted| grade(&puzzle) == wanted) {
+            println!("{}", to_puzzle_string(&puzzle, true));
+            found += 1;
+        }
+    }
+
+    if found < options.count {
+        eprintln!("only {found} of {} puzzles found", options.count);
+    }
+    Ok(found == options.count)
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
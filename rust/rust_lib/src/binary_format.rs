@@ -0,0 +1,548 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Compact binary save format
+Alternative to the JSON of save_data. Field names are not repeated and the
+candidate highlights (requestedCandHighLightType) are not stored, like JSON
+they load as 0 and update_matrix recomputes them.
+
+Layout (version 1) :
+  magic "SDKB"                 4 bytes
+  version                      1 byte
+  rows, cols                   2 bytes (grid size, 4, 6, 9, 12 or 16)
+  per cell, bit packed, MSB first, 24 bits per cell for 9x9 :
+    selectedNumStateList       2 bits (givens, future use)
+    selectedNum                4 bits (5 bits for 16x16)
+    selectedCandList           9 bits (one bit per digit of the grid size)
+    selectedPatternList        4 bits
+    requestedElementHighLightType 5 bits
+
+A 9x9 grid needs 250 bytes instead of roughly 13 kB JSON.
+
+Version 2 appends the killer cages after the last cell, same bit stream :
+  cage count                   16 bits (a 16x16 grid holds up to 256 cages)
+  per cage : cell count 8 bits, cell indices 8 bits each, sum 8 bits
+Version 4 appends the variant rules after the cages (cage count 0 without cages) :
+  variant count                16 bits (lines are not limited to 255)
+  per variant : VariantKind 8 bits, then
+    regions                    one region number per cell, bits of selectedNum
+    thermo, arrow              cell count 8 bits, cell indices 8 bits each
+                               (arrow circle first)
+    little killer              as thermo, then the sum 16 bits
+Version 3 files have the same layout with an 8 bit variant count, they
+are still read but no longer written.
+Every save is written with the lowest version that holds its content.
+*/
+
+use crate::error::SudokuError;
+
+use crate::ffi::{constSelectedNumStateListSize,
+constSelectedPatternListSize,
+constRequestedElementHighLightTypeSize};
+
+use crate::grid::{CandidateSet, Digit, Geometry, Grid, MAX_GRID_SIZE};
+
+use crate::killer::Cage;
+
+use crate::constraint::{Variant, VariantKind};
+
+pub const BINARY_MAGIC: &[u8; 4] = b"SDKB";
+// Newest version this build reads
+pub const BINARY_VERSION: u8 = 4;
+// Written for grids without cages, readable by older app versions
+const BINARY_VERSION_PLAIN: u8 = 1;
+// Written for grids with cages but no variants
+const BINARY_VERSION_CAGES: u8 = 2;
+// Variants with an 8 bit count, read only
+const BINARY_VERSION_VARIANTS_8: u8 = 3;
+
+const BINARY_HEADER_LEN: usize = 7;
+
+// Bits for selectedNum, enough for the largest number of the grid
+pub(crate) fn bits_per_num(geometry: Geometry) -> u8 {
+    (u8::BITS - (geometry.size() as u8).leading_zeros()) as u8
+}
+
+// Keep in sync with encode_binary
+fn bits_per_cell(geometry: Geometry) -> usize {
+    (constSelectedNumStateListSize
+        + bits_per_num(geometry)
+        + geometry.size() as u8
+        + constSelectedPatternListSize
+        + constRequestedElementHighLightTypeSize) as usize
+}
+
+// Appends values bit by bit, most significant bit first
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    bit_len: usize,
+}
+
+impl BitWriter {
+    pub(crate) fn write(&mut self, value: u32, bits: u8) {
+        for i in (0..bits).rev() {
+            if self.bit_len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 != 0 {
+                let last = self.bytes.len() - 1;
+                self.bytes[last] |= 0x80 >> (self.bit_len % 8);
+            }
+            self.bit_len += 1;
+        }
+    }
+
+    pub(crate) fn write_flags(&mut self, flags: &[u8]) {
+        for &flag in flags {
+            self.write((flag != 0) as u32, 1);
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub(crate) struct BitReader<'a> {
+    bytes: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, bit_pos: 0 }
+    }
+
+    // None when the data ends before all bits are read
+    pub(crate) fn read(&mut self, bits: u8) -> Option<u32> {
+        let mut value = 0u32;
+
+        for _ in 0..bits {
+            let byte = *self.bytes.get(self.bit_pos / 8)?;
+            let bit = (byte >> (7 - self.bit_pos % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.bit_pos += 1;
+        }
+
+        Some(value)
+    }
+
+    pub(crate) fn read_flags(&mut self, flags: &mut [u8]) -> Option<()> {
+        for flag in flags.iter_mut() {
+            *flag = self.read(1)? as u8;
+        }
+        Some(())
+    }
+}
+
+pub fn encode_binary(grid: &Grid) -> Vec<u8> {
+    let geometry = grid.geometry();
+    let mut out = Vec::with_capacity(BINARY_HEADER_LEN + (geometry.cells() * bits_per_cell(geometry)).div_ceil(8));
+    out.extend_from_slice(BINARY_MAGIC);
+    let version = if !grid.variants().is_empty() {
+        BINARY_VERSION
+    } else if !grid.cages().is_empty() {
+        BINARY_VERSION_CAGES
+    } else {
+        BINARY_VERSION_PLAIN
+    };
+    out.push(version);
+    out.push(geometry.size() as u8);
+    out.push(geometry.size() as u8);
+
+    let mut bits = BitWriter::default();
+
+    for cell in grid.cells() {
+        bits.write_flags(&[cell.given as u8, cell.future_use as u8]);
+        bits.write(cell.value.map_or(0, Digit::get) as u32, bits_per_num(geometry));
+        bits.write_flags(&cell.candidates.to_flag_vec(geometry.size()));
+        bits.write_flags(&cell.patterns);
+        bits.write_flags(&cell.element_highlight);
+    }
+
+    if version >= BINARY_VERSION_CAGES {
+        // Largest cage sum 1 + .. + 16 = 136 fits into 8 bits
+        bits.write(grid.cages().len() as u32, 16);
+        for cage in grid.cages() {
+            bits.write(cage.cells().len() as u32, 8);
+            for idx in cage.cells() {
+                bits.write(idx.get() as u32, 8);
+            }
+            bits.write(cage.sum() as u32, 8);
+        }
+    }
+
+    if version >= BINARY_VERSION {
+        bits.write(grid.variants().len() as u32, 16);
+        for variant in grid.variants() {
+            bits.write(variant.kind() as u32, 8);
+            let (cells, sum) = variant.parts();
+            match variant.kind() {
+                VariantKind::REGIONS => {
+                    for region in cells {
+                        bits.write(region as u32, bits_per_num(geometry));
+                    }
+                }
+                VariantKind::THERMO | VariantKind::ARROW | VariantKind::LITTLE_KILLER => {
+                    bits.write(cells.len() as u32, 8);
+                    for idx in cells {
+                        bits.write(idx as u32, 8);
+                    }
+                    if variant.kind() == VariantKind::LITTLE_KILLER {
+                        bits.write(sum as u32, 16);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    out.extend_from_slice(&bits.into_bytes());
+    out
+}
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(BINARY_MAGIC)
+}
+
+// Only the stored state is decoded, candidate highlights stay 0
+pub fn decode_binary(bytes: &[u8]) -> Result<Grid, SudokuError> {
+    if bytes.len() < BINARY_HEADER_LEN || !is_binary(bytes) {
+        return Err(SudokuError::UnknownFormat); // not a binary save
+    }
+
+    let version = bytes[4];
+    if version > BINARY_VERSION {
+        return Err(SudokuError::UnsupportedVersion); // written by a newer app version
+    }
+
+    let geometry = Geometry::from_size(bytes[5] as usize)
+        .filter(|_| bytes[5] == bytes[6])
+        .ok_or(SudokuError::DimensionMismatch)?;
+
+    let mut reader = BitReader::new(&bytes[BINARY_HEADER_LEN..]);
+    let mut grid = Grid::with_geometry(geometry);
+
+    for cell in grid.cells_mut() {
+        let decoded = (|| {
+            let mut num_state = [0; constSelectedNumStateListSize as usize];
+            let mut candidates = [0; MAX_GRID_SIZE];
+
+            reader.read_flags(&mut num_state)?;
+            let num = reader.read(bits_per_num(geometry))? as u8;
+            reader.read_flags(&mut candidates[..geometry.size()])?;
+            reader.read_flags(&mut cell.patterns)?;
+            reader.read_flags(&mut cell.element_highlight)?;
+
+            cell.given = num_state[0] != 0;
+            cell.future_use = num_state[1] != 0;
+            cell.candidates = CandidateSet::from_flags(&candidates);
+            cell.value = geometry.digit(num);
+            cell.candidate_highlight = [0; MAX_GRID_SIZE];
+
+            // 0 is an empty cell, everything above the grid size is corrupt
+            (num == 0 || cell.value.is_some()).then_some(())
+        })();
+
+        if decoded.is_none() {
+            return Err(SudokuError::CorruptData); // truncated or corrupt data
+        }
+    }
+
+    if version >= BINARY_VERSION_CAGES {
+        read_cages(&mut reader, &mut grid)?;
+    }
+    if version >= BINARY_VERSION_VARIANTS_8 {
+        let count_bits = if version == BINARY_VERSION_VARIANTS_8 { 8 } else { 16 };
+        read_variants(&mut reader, &mut grid, count_bits)?;
+    }
+
+    Ok(grid)
+}
+
+fn read_cages(reader: &mut BitReader, grid: &mut Grid) -> Result<(), SudokuError> {
+    let geometry = grid.geometry();
+    let mut read = |bits| reader.read(bits).ok_or(SudokuError::CorruptData);
+
+    for _ in 0..read(16)? {
+        let mut cells = Vec::new();
+        for _ in 0..read(8)? {
+            cells.push(geometry.cell(read(8)? as usize).ok_or(SudokuError::CorruptData)?);
+        }
+        let sum = read(8)? as u16;
+
+        grid.add_cage(Cage::new(geometry, cells, sum)?)?;
+    }
+
+    Ok(())
+}
+
+fn read_variants(reader: &mut BitReader, grid: &mut Grid, count_bits: u8) -> Result<(), SudokuError> {
+    let geometry = grid.geometry();
+    let mut read = |bits| reader.read(bits).ok_or(SudokuError::CorruptData);
+
+    for _ in 0..read(count_bits)? {
+        let kind = read(8)? as u8;
+        let mut cells = Vec::new();
+        let mut sum = 0;
+        match kind {
+            VariantKind::REGIONS => {
+                for _ in 0..geometry.cells() {
+                    cells.push(read(bits_per_num(geometry))? as u8);
+                }
+            }
+            VariantKind::THERMO | VariantKind::ARROW | VariantKind::LITTLE_KILLER => {
+                for _ in 0..read(8)? {
+                    cells.push(read(8)? as u8);
+                }
+                if kind == VariantKind::LITTLE_KILLER {
+                    sum = read(16)? as u16;
+                }
+            }
+            _ => {}
+        }
+
+        grid.add_variant(Variant::from_parts(geometry, kind, &cells, sum)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::grid::{Cell, CellIndex};
+    use crate::puzzle_string::parse_puzzle_string;
+    use crate::constraint::Regions;
+    use crate::store_data::{load_data, save_data_format, AppData, SaveFormat};
+    use crate::test_util::temp_path;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    // Givens, a placed number, candidates and user pattern flags
+    fn sample_grid() -> Grid {
+        let mut grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+        grid.set_all_candidates();
+
+        grid.cell_mut(idx(2)).value = Digit::new(4);
+        grid.cell_mut(idx(3)).candidates = CandidateSet::from_flags(&[0, 1, 0, 0, 0, 1, 0, 0, 0]);
+        grid.cell_mut(idx(5)).candidates = CandidateSet::from_flags(&[0, 0, 0, 0, 0, 0, 0, 1, 0]);
+        grid.cell_mut(idx(7)).patterns = [1, 0, 1, 0];
+        grid.cell_mut(idx(8)).element_highlight = [0, 1, 0, 0, 1];
+        grid.cell_mut(idx(9)).future_use = true;
+
+        grid
+    }
+
+    // Save and load through the FFI functions used by Dart
+    fn save_and_load(grid: &Grid, format: u8, name: &str) -> Grid {
+        let path = temp_path(name);
+        let elements = grid.to_ffi();
+        let mut loaded = Grid::new().to_ffi();
+
+        unsafe {
+            assert_eq!(save_data_format(elements.as_ptr(), 9, 9, path.as_ptr(), format), 0);
+            assert_eq!(load_data(loaded.as_mut_ptr(), 9, 9, path.as_ptr()), 0);
+        }
+
+        fs::remove_file(path.to_str().unwrap()).unwrap();
+        Grid::from_ffi(&loaded).unwrap()
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_cells() {
+        let mut grid = sample_grid();
+        let decoded = decode_binary(&encode_binary(&grid)).unwrap();
+
+        for cell in grid.cells_mut() {
+            cell.candidate_highlight = [0; MAX_GRID_SIZE];
+        }
+
+        assert_eq!(decoded, grid);
+    }
+
+    #[test]
+    fn binary_and_json_load_the_same_grid() {
+        let grid = sample_grid();
+
+        let from_json = save_and_load(&grid, SaveFormat::JSON, "json");
+        let from_binary = save_and_load(&grid, SaveFormat::BINARY, "binary");
+
+        assert_eq!(from_binary, from_json);
+    }
+
+    #[test]
+    fn binary_is_smaller_than_json() {
+        let elements = sample_grid().to_ffi();
+        let json_path = temp_path("size_json");
+        let binary_path = temp_path("size_binary");
+
+        unsafe {
+            assert_eq!(save_data_format(elements.as_ptr(), 9, 9, json_path.as_ptr(), SaveFormat::JSON), 0);
+            assert_eq!(save_data_format(elements.as_ptr(), 9, 9, binary_path.as_ptr(), SaveFormat::BINARY), 0);
+        }
+
+        let json_len = fs::metadata(json_path.to_str().unwrap()).unwrap().len();
+        let binary_len = fs::metadata(binary_path.to_str().unwrap()).unwrap().len();
+        fs::remove_file(json_path.to_str().unwrap()).unwrap();
+        fs::remove_file(binary_path.to_str().unwrap()).unwrap();
+
+        assert_eq!(binary_len, 250);
+        assert!(binary_len * 20 < json_len);
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_other_grid_sizes() {
+        for geometry in Geometry::SUPPORTED {
+            let last = geometry.cell(geometry.cells() - 1).unwrap();
+            let mut grid = Grid::with_geometry(geometry);
+            grid.set_all_candidates();
+            *grid.cell_mut(last) = Cell::given(geometry.digit(geometry.size() as u8).unwrap());
+
+            let mut bytes = encode_binary(&grid);
+            assert_eq!(bytes[5], geometry.size() as u8);
+            assert_eq!(decode_binary(&bytes).unwrap().numbers(), grid.numbers());
+            assert_eq!(decode_binary(&bytes).unwrap().cell(idx(0)).candidates, geometry.all_candidates());
+
+            bytes[6] = 7;
+            assert_eq!(decode_binary(&bytes).unwrap_err(), SudokuError::DimensionMismatch);
+        }
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_cages() {
+        let mut grid = sample_grid();
+        grid.add_cage(Cage::new(Geometry::CLASSIC, vec![idx(2), idx(3), idx(12)], 12).unwrap()).unwrap();
+        grid.add_cage(Cage::new(Geometry::CLASSIC, vec![idx(80)], 9).unwrap()).unwrap();
+
+        let bytes = encode_binary(&grid);
+        assert_eq!(bytes[4], BINARY_VERSION_CAGES);
+        assert_eq!(decode_binary(&bytes).unwrap().cages(), grid.cages());
+
+        // Without cages older app versions can still read the file
+        assert_eq!(encode_binary(&sample_grid())[4], BINARY_VERSION_PLAIN);
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_more_than_255_cages() {
+        let geometry = Geometry::GIANT;
+        let mut grid = Grid::with_geometry(geometry);
+        for i in geometry.all_cells() {
+            grid.add_cage(Cage::new(geometry, vec![i], 1 + (i.get() % 16) as u16).unwrap()).unwrap();
+        }
+        assert_eq!(grid.cages().len(), 256);
+
+        assert_eq!(decode_binary(&encode_binary(&grid)).unwrap().cages(), grid.cages());
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_variants() {
+        let mut grid = sample_grid();
+        grid.add_cage(Cage::new(Geometry::CLASSIC, vec![idx(2), idx(3)], 10).unwrap()).unwrap();
+        grid.add_variant(Variant::Diagonal).unwrap();
+        grid.add_variant(Variant::NonConsecutive).unwrap();
+        let map: Vec<u8> = Geometry::CLASSIC.all_cells().map(|i| Geometry::CLASSIC.box_index(i) as u8).collect();
+        grid.add_variant(Variant::from_parts(Geometry::CLASSIC, VariantKind::REGIONS, &map, 0).unwrap()).unwrap();
+        grid.add_variant(Variant::from_parts(Geometry::CLASSIC, VariantKind::THERMO, &[0, 1, 10], 0).unwrap()).unwrap();
+        grid.add_variant(Variant::from_parts(Geometry::CLASSIC, VariantKind::ARROW, &[40, 41, 42], 0).unwrap()).unwrap();
+        grid.add_variant(Variant::from_parts(Geometry::CLASSIC, VariantKind::LITTLE_KILLER, &[62, 70, 78], 6).unwrap()).unwrap();
+
+        let bytes = encode_binary(&grid);
+        assert_eq!(bytes[4], BINARY_VERSION);
+        let decoded = decode_binary(&bytes).unwrap();
+        assert_eq!(decoded.variants(), grid.variants());
+        assert_eq!(decoded.cages(), grid.cages());
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_more_than_255_variants() {
+        let geometry = Geometry::GIANT;
+        let mut grid = Grid::with_geometry(geometry);
+        // Two cell thermos, left to right and top to bottom
+        for i in 0..150u8 {
+            let (row, col) = (i / 15, i % 15);
+            for line in [[row * 16 + col, row * 16 + col + 1], [col * 16 + row, (col + 1) * 16 + row]] {
+                grid.add_variant(Variant::from_parts(geometry, VariantKind::THERMO, &line, 0).unwrap()).unwrap();
+            }
+        }
+        assert_eq!(grid.variants().len(), 300);
+
+        assert_eq!(decode_binary(&encode_binary(&grid)).unwrap().variants(), grid.variants());
+    }
+
+    #[test]
+    fn version_3_variants_are_still_read() {
+        // 24 bits per cell of a 9x9 grid, the cells end on a byte boundary
+        let mut bytes = encode_binary(&sample_grid());
+        bytes[4] = BINARY_VERSION_VARIANTS_8;
+        let mut bits = BitWriter::default();
+        bits.write(0, 16); // cages
+        bits.write(1, 8);
+        bits.write(VariantKind::DIAGONAL as u32, 8);
+        bytes.extend_from_slice(&bits.into_bytes());
+
+        assert_eq!(decode_binary(&bytes).unwrap().variants(), &[Variant::Diagonal]);
+    }
+
+    #[test]
+    fn json_with_broken_regions_is_rejected() {
+        let geometry = Geometry::CLASSIC;
+        let rows: Vec<u8> = (0..81).map(|i| (i / 9) as u8).collect();
+
+        let mut grid = sample_grid();
+        grid.set_regions(Regions::new(geometry, rows).unwrap()).unwrap();
+        let json = serde_json::to_string(&AppData::from_grid(&grid)).unwrap();
+        let broken: AppData = serde_json::from_str(&json.replace("\"regions\":[0,", "\"regions\":[8,")).unwrap();
+        assert_eq!(broken.to_grid().unwrap_err(), SudokuError::InvalidRegions);
+    }
+
+    #[test]
+    fn decode_rejects_bad_data() {
+        let mut bytes = encode_binary(&sample_grid());
+
+        assert_eq!(decode_binary(&bytes[..100]).unwrap_err(), SudokuError::CorruptData);
+        assert_eq!(decode_binary(b"{\"rows\":9}").unwrap_err(), SudokuError::UnknownFormat);
+
+        bytes[4] = BINARY_VERSION + 1;
+        assert_eq!(decode_binary(&bytes).unwrap_err(), SudokuError::UnsupportedVersion);
+    }
+}
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
@@ -0,0 +1,1171 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Constraint framework
+Every rule of a puzzle is a Constraint. Rows, columns and boxes are
+constraints like the variants, nothing in the solver or in process_data
+knows about them directly.
+
+A constraint describes itself in two ways :
+units      → groups of cells whose numbers must all differ
+             (row, column, box, diagonal, jigsaw region, killer cage)
+neighbours → single cells that restrict each other, excluded_by tells which
+             numbers a value takes away (anti-knight, anti-king : the same
+             number, non-consecutive : the number one below and above)
+
+Variants are stored in the Grid and can be mixed per puzzle :
+Diagonal        → both main diagonals hold every number once (X-Sudoku)
+AntiKnight      → cells a chess knight move apart differ
+AntiKing        → cells touching, also diagonally, differ
+NonConsecutive  → orthogonal neighbours never differ by 1
+Regions         → irregular regions replace the boxes (jigsaw)
+
+A region map holds the region number 0..size of every cell. It must be a
+partition of the grid : size regions of size orthogonally connected cells,
+anything else is SudokuError::InvalidRegions.
+
+Line rules describe themselves by their cells instead :
+Thermo          → numbers rise strictly from the bulb (first cell)
+Arrow           → the circle holds the sum of the arrow cells
+LittleKiller    → a whole diagonal adds up to the clue outside the grid
+is_feasible checks the placed numbers against the bounds, prune narrows the
+candidates so hints.rs can explain the step. Lines may be added many times,
+the other variants once per grid.
+
+Rules is the compiled form of all constraints of a grid (units, neighbours
+and line rules per cell), built once per solve or analysis pass. Analysis of
+the whole grid keeps one UnitMask of placed numbers per unit, a cell is then
+excluded by the union of the masks of its units.
+*/
+
+use crate::error::SudokuError;
+
+use crate::grid::{CandidateSet, CellIndex, Digit, Geometry, Grid, UnitMask};
+
+use crate::killer::is_connected;
+
+pub trait Constraint {
+    // Groups of cells whose numbers must all differ
+    fn units(&self, _geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        Vec::new()
+    }
+
+    // Cells restricting idx outside of the units
+    fn neighbours(&self, _geometry: Geometry, _idx: CellIndex) -> Vec<CellIndex> {
+        Vec::new()
+    }
+
+    // Numbers a neighbour holding value takes away
+    fn excluded_by(&self, value: Digit, _size: usize) -> CandidateSet {
+        CandidateSet::single(value)
+    }
+
+    // Cells of a line rule (thermo, arrow, little killer), checked with is_feasible
+    fn cells(&self) -> Vec<CellIndex> {
+        Vec::new()
+    }
+
+    // The placed numbers of cells() (0 = empty) can still be completed
+    fn is_feasible(&self, _geometry: Geometry, _value: &dyn Fn(CellIndex) -> u8) -> bool {
+        true
+    }
+
+    // Placed numbers break the rule
+    fn is_violated(&self, grid: &Grid) -> bool {
+        let g = grid.geometry();
+
+        let repeat = self.units(g).iter().any(|unit| {
+            let mut seen = CandidateSet::EMPTY;
+            unit.iter().filter_map(|&idx| grid.value(idx)).any(|digit| {
+                let repeated = seen.contains(digit);
+                seen.insert(digit);
+                repeated
+            })
+        });
+
+        let broken_neighbour = g.all_cells().any(|idx| {
+            grid.value(idx).is_some_and(|value| {
+                self.neighbours(g, idx)
+                    .iter()
+                    .filter_map(|&n| grid.value(n))
+                    .any(|other| self.excluded_by(other, g.size()).contains(value))
+            })
+        });
+
+        repeat || broken_neighbour || !self.is_feasible(g, &|idx| grid.value(idx).map_or(0, Digit::get))
+    }
+
+    // Numbers the rule takes away, options = numbers every cell can still take
+    // (one per placed cell). Units and neighbours drop the numbers of cells
+    // with a single option, line rules narrow their ranges.
+    fn prune(&self, geometry: Geometry, options: &[CandidateSet]) -> Vec<(CellIndex, CandidateSet)> {
+        let single = |idx: CellIndex| Some(options[idx.get()]).filter(|o| o.len() == 1);
+        let mut removed = Vec::new();
+
+        for unit in self.units(geometry) {
+            for &idx in &unit {
+                if let Some(fixed) = single(idx) {
+                    removed.extend(unit.iter().filter(|&&other| other != idx).map(|&other| (other, fixed)));
+                }
+            }
+        }
+
+        for idx in geometry.all_cells() {
+            if let Some(digit) = single(idx).and_then(CandidateSet::first) {
+                let excluded = self.excluded_by(digit, geometry.size());
+                removed.extend(self.neighbours(geometry, idx).into_iter().map(|n| (n, excluded)));
+            }
+        }
+
+        removed
+    }
+}
+
+// Numbers low..=high, empty if low > high
+fn between(low: usize, high: usize) -> CandidateSet {
+    (low.max(1)..=high.min(16)).filter_map(|n| Digit::new(n as u8)).collect()
+}
+
+fn lowest(options: CandidateSet) -> usize {
+    options.first().map_or(0, |digit| digit.get() as usize)
+}
+
+fn highest(options: CandidateSet) -> usize {
+    options.iter().last().map_or(0, |digit| digit.get() as usize)
+}
+
+// Options outside low..=high, for every cell of a line
+fn outside(cells: &[CellIndex], options: &[CandidateSet], range: impl Fn(usize) -> (usize, usize)) -> Vec<(CellIndex, CandidateSet)> {
+    cells
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| {
+            let (low, high) = range(i);
+            (idx, options[idx.get()].difference(between(low, high)))
+        })
+        .filter(|(_, removed)| !removed.is_empty())
+        .collect()
+}
+
+pub struct Rows;
+pub struct Columns;
+pub struct Boxes;
+
+impl Constraint for Rows {
+    fn units(&self, geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        geometry.units()[..geometry.size()].to_vec()
+    }
+}
+
+impl Constraint for Columns {
+    fn units(&self, geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        geometry.units()[geometry.size()..2 * geometry.size()].to_vec()
+    }
+}
+
+impl Constraint for Boxes {
+    fn units(&self, geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        geometry.units()[2 * geometry.size()..].to_vec()
+    }
+}
+
+pub struct VariantKind;
+
+impl VariantKind {
+    pub const DIAGONAL: u8 = 1;
+    pub const ANTI_KNIGHT: u8 = 2;
+    pub const ANTI_KING: u8 = 3;
+    pub const NON_CONSECUTIVE: u8 = 4;
+    pub const REGIONS: u8 = 5;
+    pub const THERMO: u8 = 6;
+    pub const ARROW: u8 = 7;
+    pub const LITTLE_KILLER: u8 = 8;
+}
+
+pub struct Diagonal;
+pub struct AntiKnight;
+pub struct AntiKing;
+pub struct NonConsecutive;
+
+// Cells at the given row / column offsets that are inside the grid
+fn offset_cells(geometry: Geometry, idx: CellIndex, offsets: &[(isize, isize)]) -> Vec<CellIndex> {
+    let (row, col) = (geometry.row(idx) as isize, geometry.col(idx) as isize);
+
+    offsets
+        .iter()
+        .filter_map(|&(dr, dc)| {
+            let (r, c) = (row + dr, col + dc);
+            (r >= 0 && c >= 0).then(|| geometry.cell_at(r as usize, c as usize)).flatten()
+        })
+        .collect()
+}
+
+const KNIGHT_MOVES: [(isize, isize); 8] = [(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)];
+const KING_MOVES: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+impl Constraint for Diagonal {
+    fn units(&self, geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        let size = geometry.size();
+        let main = (0..size).filter_map(|i| geometry.cell_at(i, i)).collect();
+        let anti = (0..size).filter_map(|i| geometry.cell_at(i, size - 1 - i)).collect();
+        vec![main, anti]
+    }
+}
+
+impl Constraint for AntiKnight {
+    fn neighbours(&self, geometry: Geometry, idx: CellIndex) -> Vec<CellIndex> {
+        offset_cells(geometry, idx, &KNIGHT_MOVES)
+    }
+}
+
+impl Constraint for AntiKing {
+    fn neighbours(&self, geometry: Geometry, idx: CellIndex) -> Vec<CellIndex> {
+        offset_cells(geometry, idx, &KING_MOVES)
+    }
+}
+
+impl Constraint for NonConsecutive {
+    fn neighbours(&self, geometry: Geometry, idx: CellIndex) -> Vec<CellIndex> {
+        offset_cells(geometry, idx, &ORTHOGONAL)
+    }
+
+    fn excluded_by(&self, value: Digit, size: usize) -> CandidateSet {
+        let v = value.get();
+        [v.checked_sub(1), v.checked_add(1)]
+            .into_iter()
+            .flatten()
+            .filter(|&n| (1..=size as u8).contains(&n))
+            .filter_map(Digit::new)
+            .collect()
+    }
+}
+
+// Cells inside the grid, no cell twice
+fn check_cells(geometry: Geometry, cells: &[CellIndex]) -> Result<(), SudokuError> {
+    if cells.iter().any(|idx| geometry.cell(idx.get()).is_none()) {
+        return Err(SudokuError::IndexOutOfRange);
+    }
+    if cells.iter().enumerate().any(|(i, idx)| cells[..i].contains(idx)) {
+        return Err(SudokuError::InvalidVariant);
+    }
+    Ok(())
+}
+
+// Every cell touches the next one, also diagonally
+fn is_line(geometry: Geometry, cells: &[CellIndex]) -> bool {
+    cells.windows(2).all(|pair| {
+        geometry.row(pair[0]).abs_diff(geometry.row(pair[1])) <= 1
+            && geometry.col(pair[0]).abs_diff(geometry.col(pair[1])) <= 1
+    })
+}
+
+// Numbers rise strictly from the bulb (first cell) to the tip
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Thermo {
+    cells: Vec<CellIndex>,
+}
+
+impl Thermo {
+    // 2..=size touching cells, bulb first
+    pub fn new(geometry: Geometry, cells: Vec<CellIndex>) -> Result<Thermo, SudokuError> {
+        check_cells(geometry, &cells)?;
+        if cells.len() < 2 || cells.len() > geometry.size() || !is_line(geometry, &cells) {
+            return Err(SudokuError::InvalidVariant);
+        }
+        Ok(Thermo { cells })
+    }
+}
+
+impl Constraint for Thermo {
+    fn units(&self, _geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        vec![self.cells.clone()]
+    }
+
+    fn cells(&self) -> Vec<CellIndex> {
+        self.cells.clone()
+    }
+
+    // Every placed number leaves room for the cells before and after it
+    fn is_feasible(&self, geometry: Geometry, value: &dyn Fn(CellIndex) -> u8) -> bool {
+        let mut low = 0;
+        for &idx in &self.cells {
+            low += 1;
+            match value(idx) as usize {
+                0 => {}
+                v if v < low => return false,
+                v => low = v,
+            }
+        }
+
+        let mut high = geometry.size() + 1;
+        for &idx in self.cells.iter().rev() {
+            high -= 1;
+            match value(idx) as usize {
+                0 => {}
+                v if v > high => return false,
+                v => high = v,
+            }
+        }
+
+        true
+    }
+
+    // Above the lowest option of the cell before, below the highest of the cell after
+    fn prune(&self, _geometry: Geometry, options: &[CandidateSet]) -> Vec<(CellIndex, CandidateSet)> {
+        let n = self.cells.len();
+        let mut low = vec![0; n];
+        let mut high = vec![0; n];
+
+        for i in 0..n {
+            let previous = if i == 0 { 0 } else { low[i - 1] };
+            low[i] = lowest(options[self.cells[i].get()]).max(previous + 1);
+        }
+        for i in (0..n).rev() {
+            let next = if i + 1 == n { usize::MAX } else { high[i + 1] };
+            high[i] = highest(options[self.cells[i].get()]).min(next.saturating_sub(1));
+        }
+
+        outside(&self.cells, options, |i| (low[i], high[i]))
+    }
+}
+
+// The circle holds the sum of the numbers along the arrow
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Arrow {
+    circle: CellIndex,
+    path: Vec<CellIndex>,
+}
+
+impl Arrow {
+    // path starts next to the circle, numbers may repeat along the path
+    pub fn new(geometry: Geometry, circle: CellIndex, path: Vec<CellIndex>) -> Result<Arrow, SudokuError> {
+        let line: Vec<CellIndex> = std::iter::once(circle).chain(path.iter().copied()).collect();
+        check_cells(geometry, &line)?;
+        if path.is_empty() || path.len() > geometry.size() || !is_line(geometry, &line) {
+            return Err(SudokuError::InvalidVariant);
+        }
+        Ok(Arrow { circle, path })
+    }
+
+    pub fn circle(&self) -> CellIndex {
+        self.circle
+    }
+
+    pub fn path(&self) -> &[CellIndex] {
+        &self.path
+    }
+}
+
+// Sum of the placed numbers and the number of empty cells
+fn placed_sum(cells: &[CellIndex], value: &dyn Fn(CellIndex) -> u8) -> (usize, usize) {
+    cells.iter().fold((0, 0), |(sum, empty), &idx| match value(idx) {
+        0 => (sum, empty + 1),
+        v => (sum + v as usize, empty),
+    })
+}
+
+// Range of every cell so that the line can still add up to low..=high
+fn sum_ranges(cells: &[CellIndex], options: &[CandidateSet], low: usize, high: usize) -> Vec<(CellIndex, CandidateSet)> {
+    let min: usize = cells.iter().map(|idx| lowest(options[idx.get()])).sum();
+    let max: usize = cells.iter().map(|idx| highest(options[idx.get()])).sum();
+
+    outside(cells, options, |i| {
+        let options = options[cells[i].get()];
+        let others_min = min - lowest(options);
+        let others_max = max - highest(options);
+        (low.saturating_sub(others_max), high.saturating_sub(others_min))
+    })
+}
+
+impl Constraint for Arrow {
+    fn cells(&self) -> Vec<CellIndex> {
+        std::iter::once(self.circle).chain(self.path.iter().copied()).collect()
+    }
+
+    fn is_feasible(&self, geometry: Geometry, value: &dyn Fn(CellIndex) -> u8) -> bool {
+        let (sum, empty) = placed_sum(&self.path, value);
+        let (low, high) = (sum + empty, sum + empty * geometry.size());
+
+        match value(self.circle) as usize {
+            0 => low <= geometry.size(),
+            circle => (low..=high).contains(&circle),
+        }
+    }
+
+    // Circle between the smallest and largest path sum, path cells within what the circle allows
+    fn prune(&self, _geometry: Geometry, options: &[CandidateSet]) -> Vec<(CellIndex, CandidateSet)> {
+        let min: usize = self.path.iter().map(|idx| lowest(options[idx.get()])).sum();
+        let max: usize = self.path.iter().map(|idx| highest(options[idx.get()])).sum();
+
+        let circle = options[self.circle.get()];
+        let narrowed = circle.intersection(between(min, max));
+        let mut removed = sum_ranges(&self.path, options, lowest(narrowed), highest(narrowed));
+
+        if narrowed != circle {
+            removed.push((self.circle, circle.difference(narrowed)));
+        }
+        removed
+    }
+}
+
+// Numbers along a diagonal add up to the clue outside the grid, they may repeat
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LittleKiller {
+    cells: Vec<CellIndex>,
+    sum: u16,
+}
+
+impl LittleKiller {
+    // The whole diagonal from one edge of the grid to the other, clue next to the first cell
+    pub fn new(geometry: Geometry, cells: Vec<CellIndex>, sum: u16) -> Result<LittleKiller, SudokuError> {
+        check_cells(geometry, &cells)?;
+
+        let (Some(&first), Some(&last)) = (cells.first(), cells.last()) else {
+            return Err(SudokuError::InvalidVariant);
+        };
+        let step = |from: CellIndex, to: CellIndex| {
+            (geometry.row(to) as isize - geometry.row(from) as isize, geometry.col(to) as isize - geometry.col(from) as isize)
+        };
+        // A single corner cell can run either way, (1, 1) is as good as any
+        let (dr, dc) = if cells.len() > 1 { step(first, cells[1]) } else { (1, 1) };
+        let beyond = |idx: CellIndex, dr: isize, dc: isize| {
+            let (r, c) = (geometry.row(idx) as isize + dr, geometry.col(idx) as isize + dc);
+            r >= 0 && c >= 0 && geometry.cell_at(r as usize, c as usize).is_some()
+        };
+
+        let diagonal = dr.abs() == 1 && dc.abs() == 1 && cells.windows(2).all(|pair| step(pair[0], pair[1]) == (dr, dc));
+        let edge_to_edge = !beyond(first, -dr, -dc) && !beyond(last, dr, dc);
+        let reachable = (cells.len()..=cells.len() * geometry.size()).contains(&(sum as usize));
+
+        if !diagonal || !edge_to_edge || !reachable {
+            return Err(SudokuError::InvalidVariant);
+        }
+        Ok(LittleKiller { cells, sum })
+    }
+
+    pub fn sum(&self) -> u16 {
+        self.sum
+    }
+}
+
+impl Constraint for LittleKiller {
+    fn cells(&self) -> Vec<CellIndex> {
+        self.cells.clone()
+    }
+
+    fn is_feasible(&self, geometry: Geometry, value: &dyn Fn(CellIndex) -> u8) -> bool {
+        let (sum, empty) = placed_sum(&self.cells, value);
+        (sum + empty..=sum + empty * geometry.size()).contains(&(self.sum as usize))
+    }
+
+    fn prune(&self, _geometry: Geometry, options: &[CandidateSet]) -> Vec<(CellIndex, CandidateSet)> {
+        sum_ranges(&self.cells, options, self.sum as usize, self.sum as usize)
+    }
+}
+
+// Region number 0..size of every cell, row major
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Regions {
+    map: Vec<u8>,
+}
+
+impl Regions {
+    // One region number per cell, the regions must partition the grid
+    pub fn new(geometry: Geometry, map: Vec<u8>) -> Result<Regions, SudokuError> {
+        validate_regions(geometry, &map)?;
+        Ok(Regions { map })
+    }
+
+    // The boxes of the geometry, starting point for editing a jigsaw layout
+    pub fn from_boxes(geometry: Geometry) -> Regions {
+        Regions { map: geometry.all_cells().map(|idx| geometry.box_index(idx) as u8).collect() }
+    }
+
+    pub fn map(&self) -> &[u8] {
+        &self.map
+    }
+
+    pub fn region(&self, idx: CellIndex) -> usize {
+        self.map[idx.get()] as usize
+    }
+}
+
+impl Constraint for Regions {
+    fn units(&self, geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        (0..geometry.size())
+            .map(|region| geometry.all_cells().filter(|&idx| self.region(idx) == region).collect())
+            .collect()
+    }
+}
+
+// size regions numbered 0..size, each with size connected cells
+pub fn validate_regions(geometry: Geometry, map: &[u8]) -> Result<(), SudokuError> {
+    if map.len() != geometry.cells() {
+        return Err(SudokuError::InvalidLength);
+    }
+
+    for region in 0..geometry.size() {
+        let cells: Vec<CellIndex> = geometry.all_cells().filter(|idx| map[idx.get()] as usize == region).collect();
+        if cells.len() != geometry.size() || !is_connected(geometry, &cells) {
+            return Err(SudokuError::InvalidRegions);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Variant {
+    Diagonal,
+    AntiKnight,
+    AntiKing,
+    NonConsecutive,
+    Regions(Regions),
+    Thermo(Thermo),
+    Arrow(Arrow),
+    LittleKiller(LittleKiller),
+}
+
+impl Variant {
+    // VariantKind, parameterless variants only, the others need their cells
+    pub fn from_kind(kind: u8) -> Result<Variant, SudokuError> {
+        match kind {
+            VariantKind::DIAGONAL => Ok(Variant::Diagonal),
+            VariantKind::ANTI_KNIGHT => Ok(Variant::AntiKnight),
+            VariantKind::ANTI_KING => Ok(Variant::AntiKing),
+            VariantKind::NON_CONSECUTIVE => Ok(Variant::NonConsecutive),
+            _ => Err(SudokuError::InvalidVariant),
+        }
+    }
+
+    // Stored form : region map for regions, cell indices for lines (arrow
+    // circle first), sum for little killers. Parameterless kinds ignore both.
+    pub fn from_parts(geometry: Geometry, kind: u8, cells: &[u8], sum: u16) -> Result<Variant, SudokuError> {
+        let indices = || {
+            cells
+                .iter()
+                .map(|&i| geometry.cell(i as usize).ok_or(SudokuError::IndexOutOfRange))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        match kind {
+            VariantKind::REGIONS => Ok(Variant::Regions(Regions::new(geometry, cells.to_vec())?)),
+            VariantKind::THERMO => Ok(Variant::Thermo(Thermo::new(geometry, indices()?)?)),
+            VariantKind::ARROW => {
+                let mut line = indices()?;
+                if line.is_empty() {
+                    return Err(SudokuError::InvalidVariant);
+                }
+                let circle = line.remove(0);
+                Ok(Variant::Arrow(Arrow::new(geometry, circle, line)?))
+            }
+            VariantKind::LITTLE_KILLER => Ok(Variant::LittleKiller(LittleKiller::new(geometry, indices()?, sum)?)),
+            _ => Variant::from_kind(kind),
+        }
+    }
+
+    // Inverse of from_parts
+    pub fn parts(&self) -> (Vec<u8>, u16) {
+        let sum = match self {
+            Variant::LittleKiller(little_killer) => little_killer.sum(),
+            _ => 0,
+        };
+        let cells = match self {
+            Variant::Regions(regions) => regions.map().to_vec(),
+            _ => self.constraint().cells().iter().map(|idx| idx.get() as u8).collect(),
+        };
+        (cells, sum)
+    }
+
+    // One per grid, lines can be added many times
+    pub fn is_global(&self) -> bool {
+        self.kind() <= VariantKind::REGIONS
+    }
+
+    pub fn kind(&self) -> u8 {
+        match self {
+            Variant::Diagonal => VariantKind::DIAGONAL,
+            Variant::AntiKnight => VariantKind::ANTI_KNIGHT,
+            Variant::AntiKing => VariantKind::ANTI_KING,
+            Variant::NonConsecutive => VariantKind::NON_CONSECUTIVE,
+            Variant::Regions(_) => VariantKind::REGIONS,
+            Variant::Thermo(_) => VariantKind::THERMO,
+            Variant::Arrow(_) => VariantKind::ARROW,
+            Variant::LittleKiller(_) => VariantKind::LITTLE_KILLER,
+        }
+    }
+
+    pub fn constraint(&self) -> &dyn Constraint {
+        match self {
+            Variant::Diagonal => &Diagonal,
+            Variant::AntiKnight => &AntiKnight,
+            Variant::AntiKing => &AntiKing,
+            Variant::NonConsecutive => &NonConsecutive,
+            Variant::Regions(regions) => regions,
+            Variant::Thermo(thermo) => thermo,
+            Variant::Arrow(arrow) => arrow,
+            Variant::LittleKiller(little_killer) => little_killer,
+        }
+    }
+}
+
+// Entries per cell in one flat list, start[idx]..start[idx + 1] belong to idx
+struct PerCell<T> {
+    start: Vec<usize>,
+    items: Vec<T>,
+}
+
+impl<T: Copy> PerCell<T> {
+    // Counting sort, entries of one cell keep their order
+    fn new(cells: usize, entries: impl Iterator<Item = (CellIndex, T)>) -> PerCell<T> {
+        let entries: Vec<(CellIndex, T)> = entries.collect();
+
+        let mut start = vec![0; cells + 1];
+        for (idx, _) in &entries {
+            start[idx.get() + 1] += 1;
+        }
+        for i in 0..cells {
+            start[i + 1] += start[i];
+        }
+
+        // Every slot is overwritten once, in cell order
+        let mut items: Vec<T> = entries.iter().map(|&(_, item)| item).collect();
+        let mut next = start.clone();
+        for (idx, item) in entries {
+            items[next[idx.get()]] = item;
+            next[idx.get()] += 1;
+        }
+
+        PerCell { start, items }
+    }
+
+    fn get(&self, idx: CellIndex) -> &[T] {
+        &self.items[self.start[idx.get()]..self.start[idx.get() + 1]]
+    }
+}
+
+// All constraints of a grid, compiled for lookups per cell
+pub struct Rules<'a> {
+    geometry: Geometry,
+    constraints: Vec<&'a dyn Constraint>,
+    units: Vec<Vec<CellIndex>>,
+    cell_units: PerCell<usize>,
+    // neighbour cell and index into constraints
+    neighbours: PerCell<(CellIndex, usize)>,
+    // line rules through the cell, index into constraints
+    lines: PerCell<usize>,
+}
+
+impl<'a> Rules<'a> {
+    pub fn new(grid: &'a Grid) -> Rules<'a> {
+        let geometry = grid.geometry();
+        let constraints = grid.constraints();
+
+        let units: Vec<Vec<CellIndex>> = constraints.iter().flat_map(|c| c.units(geometry)).collect();
+
+        let cells = geometry.cells();
+        let cell_units = PerCell::new(
+            cells,
+            units.iter().enumerate().flat_map(|(u, unit)| unit.iter().map(move |&idx| (idx, u))),
+        );
+
+        let neighbours = PerCell::new(
+            cells,
+            geometry.all_cells().flat_map(|idx| {
+                constraints.iter().enumerate().flat_map(move |(c, constraint)| {
+                    constraint.neighbours(geometry, idx).into_iter().map(move |n| (idx, (n, c)))
+                })
+            }),
+        );
+
+        let lines = PerCell::new(
+            cells,
+            constraints.iter().enumerate().flat_map(|(c, constraint)| constraint.cells().into_iter().map(move |idx| (idx, c))),
+        );
+
+        Rules { geometry, constraints, units, cell_units, neighbours, lines }
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn constraints(&self) -> &[&'a dyn Constraint] {
+        &self.constraints
+    }
+
+    pub fn units(&self) -> &[Vec<CellIndex>] {
+        &self.units
+    }
+
+    // Indices into units
+    pub fn cell_units(&self, idx: CellIndex) -> &[usize] {
+        self.cell_units.get(idx)
+    }
+
+    pub fn neighbours(&self, idx: CellIndex) -> impl Iterator<Item = (CellIndex, &'a dyn Constraint)> + '_ {
+        self.neighbours.get(idx).iter().map(|&(n, c)| (n, self.constraints[c]))
+    }
+
+    pub fn has_lines(&self, idx: CellIndex) -> bool {
+        !self.lines.get(idx).is_empty()
+    }
+
+    // Every line rule through idx can still be completed with digit in idx,
+    // value gives the numbers of the other cells (0 = empty)
+    pub fn fits_lines(&self, idx: CellIndex, digit: Digit, value: &dyn Fn(CellIndex) -> u8) -> bool {
+        let with_digit = |other: CellIndex| if other == idx { digit.get() } else { value(other) };
+        self.lines.get(idx).iter().all(|&c| self.constraints[c].is_feasible(self.geometry, &with_digit))
+    }
+
+    // Cells sharing a unit with idx
+    pub fn unit_peers(&self, idx: CellIndex) -> Vec<CellIndex> {
+        let mut peers: Vec<CellIndex> = self
+            .cell_units(idx)
+            .iter()
+            .flat_map(|&u| self.units[u].iter().copied())
+            .filter(|&other| other != idx)
+            .collect();
+        peers.sort();
+        peers.dedup();
+        peers
+    }
+
+    // Placed numbers of unit u
+    pub fn unit_mask(&self, grid: &Grid, u: usize) -> UnitMask {
+        let mut mask = UnitMask::default();
+        for value in self.units[u].iter().filter_map(|&idx| grid.value(idx)) {
+            mask.add(CandidateSet::single(value));
+        }
+        mask
+    }
+
+    pub fn unit_masks(&self, grid: &Grid) -> Vec<UnitMask> {
+        (0..self.units.len()).map(|u| self.unit_mask(grid, u)).collect()
+    }
+
+    // Numbers of the other cells of the units of idx, its own number only if
+    // it is placed twice
+    fn unit_excluded(&self, grid: &Grid, idx: CellIndex, mask: impl Fn(usize) -> UnitMask) -> CandidateSet {
+        let own = grid.value(idx);
+        self.cell_units(idx)
+            .iter()
+            .map(|&u| {
+                let mask = mask(u);
+                match own {
+                    Some(value) if !mask.repeated.contains(value) => mask.seen.difference(CandidateSet::single(value)),
+                    _ => mask.seen,
+                }
+            })
+            .fold(CandidateSet::EMPTY, CandidateSet::union)
+    }
+
+    fn neighbour_excluded(&self, grid: &Grid, idx: CellIndex) -> CandidateSet {
+        self.neighbours(idx)
+            .filter_map(|(n, c)| grid.value(n).map(|value| c.excluded_by(value, self.geometry.size())))
+            .fold(CandidateSet::EMPTY, CandidateSet::union)
+    }
+
+    fn line_excluded(&self, grid: &Grid, idx: CellIndex) -> CandidateSet {
+        if !self.has_lines(idx) {
+            return CandidateSet::EMPTY;
+        }
+        let value = |other: CellIndex| grid.value(other).map_or(0, Digit::get);
+        self.geometry.digits().filter(|&digit| !self.fits_lines(idx, digit, &value)).collect()
+    }
+
+    // Numbers placed in a unit of idx or taken away by a neighbour
+    pub fn peer_excluded(&self, grid: &Grid, idx: CellIndex) -> CandidateSet {
+        self.unit_excluded(grid, idx, |u| self.unit_mask(grid, u))
+            .union(self.neighbour_excluded(grid, idx))
+    }
+
+    // peer_excluded of every cell, the unit masks are built once
+    pub fn peer_excluded_all(&self, grid: &Grid) -> Vec<CandidateSet> {
+        let masks = self.unit_masks(grid);
+        self.geometry
+            .all_cells()
+            .map(|idx| self.unit_excluded(grid, idx, |u| masks[u]).union(self.neighbour_excluded(grid, idx)))
+            .collect()
+    }
+
+    // Numbers idx can not take because of the numbers placed elsewhere
+    pub fn excluded(&self, grid: &Grid, idx: CellIndex) -> CandidateSet {
+        self.peer_excluded(grid, idx).union(self.line_excluded(grid, idx))
+    }
+
+    // Numbers other cells lose once idx holds digit
+    pub fn eliminations(&self, idx: CellIndex, digit: Digit) -> Vec<(CellIndex, CandidateSet)> {
+        let single = CandidateSet::single(digit);
+
+        self.unit_peers(idx)
+            .into_iter()
+            .map(|other| (other, single))
+            .chain(self.neighbours(idx).map(|(n, c)| (n, c.excluded_by(digit, self.geometry.size()))))
+            .collect()
+    }
+
+    // Cells whose number breaks a unit or neighbour rule
+    pub fn conflicts(&self, grid: &Grid) -> Vec<CellIndex> {
+        let excluded = self.peer_excluded_all(grid);
+        self.geometry
+            .all_cells()
+            .filter(|&idx| {
+                grid.value(idx)
+                    .is_some_and(|value| excluded[idx.get()].union(self.line_excluded(grid, idx)).contains(value))
+            })
+            .collect()
+    }
+
+    pub fn is_violated(&self, grid: &Grid) -> bool {
+        self.constraints.iter().any(|c| c.is_violated(grid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_variant_kind_is_an_invalid_variant() {
+        assert_eq!(Variant::from_kind(VariantKind::ANTI_KING).unwrap().kind(), VariantKind::ANTI_KING);
+        assert_eq!(Variant::from_kind(0).unwrap_err(), SudokuError::InvalidVariant);
+        assert_eq!(Variant::from_kind(200).unwrap_err(), SudokuError::InvalidVariant);
+        assert_eq!(Variant::from_parts(Geometry::CLASSIC, 200, &[], 0).unwrap_err(), SudokuError::InvalidVariant);
+    }
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    #[test]
+    fn region_map_must_partition_the_grid() {
+        let geometry = Geometry::CLASSIC;
+        let rows: Vec<u8> = (0..81).map(|i| (i / 9) as u8).collect();
+        assert_eq!(validate_regions(geometry, &rows), Ok(()));
+        assert_eq!(validate_regions(geometry, &rows[1..]), Err(SudokuError::InvalidLength));
+
+        // Same region sizes, but regions 0 and 8 fall apart
+        let mut split = rows.clone();
+        split.swap(0, 80);
+        assert_eq!(validate_regions(geometry, &split), Err(SudokuError::InvalidRegions));
+
+        // Connected, but region 0 has ten cells and region 1 eight
+        let mut uneven = rows.clone();
+        uneven[9] = 0;
+        assert_eq!(Regions::new(geometry, uneven).unwrap_err(), SudokuError::InvalidRegions);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn jigsaw_regions_replace_the_boxes() {
+        // Boxes 0 and 1 trade (0,2) and (2,3), both stay connected
+        let mut map = Regions::from_boxes(Geometry::CLASSIC).map().to_vec();
+        map.swap(2, 21);
+        let regions = Regions::new(Geometry::CLASSIC, map).unwrap();
+        let mut grid = Grid::new();
+        grid.set_regions(regions.clone()).unwrap();
+        assert_eq!(grid.region_of(idx(2)), 1);
+        assert_eq!(grid.region_of(idx(21)), 0);
+
+        // (0,2) left box 0 for region 1
+        grid.set_value(idx(2), Some(digit(5)));
+        let rules = Rules::new(&grid);
+        assert!(!rules.excluded(&grid, idx(10)).contains(digit(5)));
+        assert!(!rules.excluded(&grid, idx(21)).contains(digit(5)));
+        assert!(rules.excluded(&grid, idx(12)).contains(digit(5)));
+
+        for backend in crate::solver::BACKENDS {
+            let solved = backend.solve(&grid).unwrap();
+            assert!(crate::solver::is_solved(&solved), "{}", backend.name());
+            for unit in regions.units(Geometry::CLASSIC) {
+                let numbers: CandidateSet = unit.iter().filter_map(|&idx| solved.value(idx)).collect();
+                assert_eq!(numbers, Geometry::CLASSIC.all_candidates(), "{}", backend.name());
+            }
+        }
+    }
+
+    fn digit(n: u8) -> Digit {
+        Digit::new(n).unwrap()
+    }
+
+    // Classic grid with one variant and a 5 at cell at
+    fn grid_with(variant: Variant, at: usize) -> Grid {
+        let mut grid = Grid::new();
+        grid.add_variant(variant).unwrap();
+        grid.set_value(idx(at), Some(digit(5)));
+        grid
+    }
+
+    #[test]
+    fn variants_exclude_numbers_outside_the_units() {
+        // (0,0) and (8,8) only share the main diagonal
+        let grid = grid_with(Variant::Diagonal, 0);
+        assert!(Rules::new(&grid).excluded(&grid, idx(80)).contains(digit(5)));
+        let plain = Grid::from_givens(&grid.numbers()).unwrap();
+        assert!(!Rules::new(&plain).excluded(&plain, idx(80)).contains(digit(5)));
+
+        // (0,0) → (1,2) is a knight move into the next box
+        let grid = grid_with(Variant::AntiKnight, 0);
+        assert!(Rules::new(&grid).excluded(&grid, idx(11)).contains(digit(5)));
+        assert!(!Rules::new(&grid).excluded(&grid, idx(30)).contains(digit(5)));
+
+        // (2,2) and (3,3) touch diagonally across a box corner
+        let grid = grid_with(Variant::AntiKing, 20);
+        assert!(Rules::new(&grid).excluded(&grid, idx(30)).contains(digit(5)));
+
+        // Below (0,0): 5 by the column, 4 and 6 by the variant
+        let grid = grid_with(Variant::NonConsecutive, 0);
+        let excluded = Rules::new(&grid).excluded(&grid, idx(9));
+        assert_eq!(excluded, [4, 5, 6].into_iter().map(digit).collect());
+        assert_eq!(Rules::new(&grid).excluded(&grid, idx(10)), CandidateSet::single(digit(5)));
+    }
+
+    #[test]
+    fn variants_prune_the_options_of_other_cells() {
+        let geometry = Geometry::CLASSIC;
+        let mut options = vec![geometry.all_candidates(); geometry.cells()];
+        options[0] = CandidateSet::single(digit(5));
+
+        let removed = Diagonal.prune(geometry, &options);
+        assert!(removed.contains(&(idx(80), CandidateSet::single(digit(5)))));
+        assert!(!removed.iter().any(|&(cell, _)| cell == idx(8)));
+
+        let removed = NonConsecutive.prune(geometry, &options);
+        let pair: CandidateSet = [4, 6].into_iter().map(digit).collect();
+        assert_eq!(removed, vec![(idx(1), pair), (idx(9), pair)]);
+
+        let grid = grid_with(Variant::AntiKnight, 0);
+        let eliminations = Rules::new(&grid).eliminations(idx(0), digit(5));
+        assert!(eliminations.contains(&(idx(11), CandidateSet::single(digit(5)))));
+        assert!(eliminations.contains(&(idx(19), CandidateSet::single(digit(5)))));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn variants_are_solved_and_checked() {
+        // Top rows of a shifted pattern without equal numbers a knight move
+        // apart, anti knight from an empty grid takes the backtracker minutes
+        let mut knight = Grid::new();
+        for idx in (0..27).map(idx) {
+            let (r, c) = (idx.get() / 9, idx.get() % 9);
+            knight.set_value(idx, Digit::new(((3 * r + r / 3 + c) % 9 + 1) as u8));
+        }
+
+        for variant in [Variant::Diagonal, Variant::AntiKnight, Variant::AntiKing, Variant::NonConsecutive] {
+            let mut grid = if variant == Variant::AntiKnight { knight.clone() } else { Grid::new() };
+            grid.add_variant(variant.clone()).unwrap();
+
+            let solved = crate::solver::solve(&grid).unwrap();
+            assert!(crate::solver::is_solved(&solved), "{variant:?}");
+            assert!(!variant.constraint().is_violated(&solved), "{variant:?}");
+        }
+
+        // Both 5s on the anti diagonal
+        let mut grid = grid_with(Variant::Diagonal, 8);
+        grid.set_value(idx(72), Some(digit(5)));
+        assert_eq!(Rules::new(&grid).conflicts(&grid), vec![idx(8), idx(72)]);
+        assert!(!grid.is_consistent());
+    }
+
+    fn cells(indices: &[usize]) -> Vec<CellIndex> {
+        indices.iter().map(|&i| idx(i)).collect()
+    }
+
+    // Placed numbers of a line, 0 = empty
+    fn values(line: &[CellIndex], numbers: &[u8]) -> impl Fn(CellIndex) -> u8 {
+        let placed: Vec<(CellIndex, u8)> = line.iter().copied().zip(numbers.iter().copied()).collect();
+        move |idx| placed.iter().find(|(cell, _)| *cell == idx).map_or(0, |&(_, n)| n)
+    }
+
+    fn all_options() -> Vec<CandidateSet> {
+        vec![Geometry::CLASSIC.all_candidates(); Geometry::CLASSIC.cells()]
+    }
+
+    fn range(low: u8, high: u8) -> CandidateSet {
+        (low..=high).map(digit).collect()
+    }
+
+    #[test]
+    fn thermo_numbers_rise_from_the_bulb() {
+        let g = Geometry::CLASSIC;
+        assert_eq!(Thermo::new(g, cells(&[0])).unwrap_err(), SudokuError::InvalidVariant);
+        assert_eq!(Thermo::new(g, cells(&[0, 2])).unwrap_err(), SudokuError::InvalidVariant);
+
+        let line = cells(&[0, 10, 20]);
+        let thermo = Thermo::new(g, line.clone()).unwrap();
+        assert!(thermo.is_feasible(g, &values(&line, &[1, 5, 9])));
+        assert!(thermo.is_feasible(g, &values(&line, &[0, 2, 0])));
+        assert!(!thermo.is_feasible(g, &values(&line, &[5, 4, 0])));
+        // Nothing left for the bulb below 1, nothing above 9 for the tip
+        assert!(!thermo.is_feasible(g, &values(&line, &[0, 1, 0])));
+        assert!(!thermo.is_feasible(g, &values(&line, &[0, 9, 0])));
+
+        assert_eq!(
+            thermo.prune(g, &all_options()),
+            vec![(idx(0), range(8, 9)), (idx(10), CandidateSet::from_iter([digit(1), digit(9)])), (idx(20), range(1, 2))]
+        );
+
+        let mut options = all_options();
+        options[10] = range(4, 5);
+        assert_eq!(thermo.prune(g, &options), vec![(idx(0), range(5, 9)), (idx(20), range(1, 4))]);
+    }
+
+    #[test]
+    fn arrow_circle_holds_the_sum_of_the_path() {
+        let g = Geometry::CLASSIC;
+        assert_eq!(Arrow::new(g, idx(0), Vec::new()).unwrap_err(), SudokuError::InvalidVariant);
+        assert_eq!(Arrow::new(g, idx(0), cells(&[2])).unwrap_err(), SudokuError::InvalidVariant);
+
+        let arrow = Arrow::new(g, idx(0), cells(&[10, 20])).unwrap();
+        let line = arrow.cells();
+        assert_eq!(line, cells(&[0, 10, 20]));
+        assert!(arrow.is_feasible(g, &values(&line, &[5, 2, 3])));
+        assert!(!arrow.is_feasible(g, &values(&line, &[5, 2, 4])));
+        // Path numbers may repeat
+        assert!(arrow.is_feasible(g, &values(&line, &[8, 4, 4])));
+        assert!(arrow.is_feasible(g, &values(&line, &[6, 0, 4])));
+        assert!(!arrow.is_feasible(g, &values(&line, &[4, 0, 4])));
+        assert!(!arrow.is_feasible(g, &values(&line, &[0, 5, 5])));
+
+        // The circle is at least 2, so no path cell reaches 9
+        assert_eq!(
+            arrow.prune(g, &all_options()),
+            vec![(idx(10), CandidateSet::single(digit(9))), (idx(20), CandidateSet::single(digit(9))), (idx(0), CandidateSet::single(digit(1)))]
+        );
+
+        let mut options = all_options();
+        options[0] = CandidateSet::single(digit(4));
+        options[10] = CandidateSet::single(digit(1));
+        let three = CandidateSet::single(digit(3));
+        assert_eq!(arrow.prune(g, &options), vec![(idx(20), g.all_candidates().difference(three))]);
+    }
+
+    #[test]
+    fn little_killer_diagonal_adds_up_to_the_clue() {
+        let g = Geometry::CLASSIC;
+        // Ends inside the grid, not a diagonal, sum out of reach
+        assert_eq!(LittleKiller::new(g, cells(&[6, 16]), 3).unwrap_err(), SudokuError::InvalidVariant);
+        assert_eq!(LittleKiller::new(g, cells(&[7, 8]), 3).unwrap_err(), SudokuError::InvalidVariant);
+        assert_eq!(LittleKiller::new(g, cells(&[7, 17]), 19).unwrap_err(), SudokuError::InvalidVariant);
+
+        let main: Vec<CellIndex> = (0..9).map(|i| idx(i * 10)).collect();
+        let little_killer = LittleKiller::new(g, main.clone(), 45).unwrap();
+        assert!(little_killer.is_feasible(g, &values(&main, &[1, 2, 3, 4, 5, 6, 7, 8, 9])));
+        assert!(!little_killer.is_feasible(g, &values(&main, &[1, 2, 3, 4, 5, 6, 7, 8, 8])));
+        // Numbers may repeat on the diagonal
+        assert!(little_killer.is_feasible(g, &values(&main, &[5; 9])));
+
+        let corner = LittleKiller::new(g, cells(&[7, 17]), 3).unwrap();
+        assert!(corner.is_feasible(g, &values(&corner.cells(), &[1, 0])));
+        assert!(!corner.is_feasible(g, &values(&corner.cells(), &[3, 0])));
+        assert_eq!(corner.prune(g, &all_options()), vec![(idx(7), range(3, 9)), (idx(17), range(3, 9))]);
+    }
+
+    // One rule of each line kind, used by the solver and hint tests
+    #[cfg(any(feature = "solver", feature = "patterns"))]
+    fn line_rules() -> Vec<Variant> {
+        let g = Geometry::CLASSIC;
+        vec![
+            Variant::Thermo(Thermo::new(g, cells(&[0, 10, 20])).unwrap()),
+            Variant::Arrow(Arrow::new(g, idx(0), cells(&[10, 20])).unwrap()),
+            Variant::LittleKiller(LittleKiller::new(g, cells(&[7, 17]), 3).unwrap()),
+        ]
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn line_rules_are_solved() {
+        for variant in line_rules() {
+            let mut grid = Grid::new();
+            grid.add_variant(variant.clone()).unwrap();
+
+            let solved = crate::solver::solve(&grid).unwrap();
+            assert!(crate::solver::is_solved(&solved), "{variant:?}");
+            assert!(!variant.constraint().is_violated(&solved), "{variant:?}");
+        }
+
+        // A thermo across a whole row leaves one way to fill it
+        let mut grid = Grid::new();
+        grid.add_variant(Variant::Thermo(Thermo::new(Geometry::CLASSIC, cells(&[0, 1, 2, 3, 4, 5, 6, 7, 8])).unwrap())).unwrap();
+        let solved = crate::solver::solve(&grid).unwrap();
+        assert_eq!(solved.numbers()[..9], [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        // Two arrows into the same cell with different circles
+        let mut grid = Grid::new();
+        grid.set_value(idx(0), Some(digit(3)));
+        grid.set_value(idx(2), Some(digit(4)));
+        grid.add_variant(Variant::Arrow(Arrow::new(Geometry::CLASSIC, idx(0), cells(&[10])).unwrap())).unwrap();
+        grid.add_variant(Variant::Arrow(Arrow::new(Geometry::CLASSIC, idx(2), cells(&[10])).unwrap())).unwrap();
+        assert_eq!(crate::solver::solve(&grid), None);
+    }
+
+    #[cfg(feature = "patterns")]
+    #[test]
+    fn line_rules_explain_their_hints() {
+        use crate::hints::variant_hints;
+
+        let expected = [
+            vec![(0, range(8, 9)), (10, CandidateSet::from_iter([digit(1), digit(9)])), (20, range(1, 2))],
+            vec![(0, CandidateSet::single(digit(1))), (10, CandidateSet::single(digit(9))), (20, CandidateSet::single(digit(9)))],
+            vec![(7, range(3, 9)), (17, range(3, 9))],
+        ];
+
+        for (variant, expected) in line_rules().into_iter().zip(expected) {
+            let mut grid = Grid::new();
+            grid.add_variant(variant.clone()).unwrap();
+
+            let hints = variant_hints(&grid);
+            let found: Vec<(usize, CandidateSet)> = hints.iter().map(|hint| (hint.cell.get(), hint.removed)).collect();
+            assert_eq!(found, expected, "{variant:?}");
+            for hint in &hints {
+                assert_eq!(hint.kind, variant.kind());
+                assert_eq!(hint.cause, variant.constraint().cells());
+            }
+        }
+
+        let mut grid = Grid::new();
+        grid.add_variant(line_rules().remove(0)).unwrap();
+        assert_eq!(variant_hints(&grid)[0].message(9), "r1c1 can not be 8, 9 : numbers rise along the thermometer");
+
+        // Placed numbers get no hint, the thermo still narrows the cells after
+        // them. 7 is gone from the box already, only new eliminations count.
+        grid.set_value(idx(0), Some(digit(7)));
+        let hints = variant_hints(&grid);
+        assert_eq!(hints[0].cell, idx(10));
+        assert_eq!(hints[0].removed, range(1, 6).union(CandidateSet::single(digit(9))));
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
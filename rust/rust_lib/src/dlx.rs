@@ -0,0 +1,372 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+
+/* 
+Dancing Links solver
+Algorithm X on an exact cover matrix, the links are index vectors instead
+of pointers (Knuth's DLX). A row is one cell holding one number, the
+columns are what a solution has to cover :
+cell          → every cell holds exactly one number (primary)
+unit, number  → a full size unit holds every number once (primary), smaller
+                units (killer cages) hold it at most once (secondary)
+neighbour pair → two rows a neighbour rule forbids together (anti-knight,
+                anti-king, non-consecutive), at most one of them (secondary)
+Only primary columns are linked into the header list, the search covers the
+one with the fewest rows left.
+
+Killer sums and line rules (thermo, arrow, little killer) are no columns :
+a row is skipped when CageState::fits or Rules::fits_lines reject its number
+for the numbers placed so far, the column choice then counts the rows that
+still fit. Givens and placed numbers are no rows : they remove the numbers
+they exclude from the empty cells, units only get columns for the numbers
+still missing.
+*/
+
+use crate::constraint::Rules;
+
+use crate::grid::{CandidateSet, CellIndex, Digit, Grid};
+
+use crate::solver::{filled, CageState, SolverBackend};
+
+// Exact cover backend, see SolverBackend
+pub struct DancingLinks;
+
+impl SolverBackend for DancingLinks {
+    fn name(&self) -> &'static str {
+        "dancing-links"
+    }
+
+    fn solve(&self, grid: &Grid) -> Option<Grid> {
+        let mut matrix = Matrix::new(grid)?;
+        let mut found = 0;
+        let mut first = None;
+
+        matrix.search(1, &mut found, &mut first);
+        first.map(|numbers| filled(grid, &numbers))
+    }
+
+    fn count_solutions(&self, grid: &Grid, limit: usize) -> usize {
+        let Some(mut matrix) = Matrix::new(grid) else {
+            return 0;
+        };
+        let mut found = 0;
+        let mut first = None;
+
+        matrix.search(limit, &mut found, &mut first);
+        found
+    }
+}
+
+// Header of the primary column list
+const ROOT: usize = 0;
+
+struct Matrix<'a> {
+    rules: Rules<'a>,
+    // nodes 1..=columns are the column headers
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>, // rows left per column header
+    filtered: bool,   // killer cages or line rules, rows are checked with fits
+    rows: Vec<(CellIndex, Digit)>,
+    numbers: Vec<u8>,
+    all: CandidateSet,
+    cages: Vec<CageState>,
+    cage_of: Vec<Option<usize>>,
+}
+
+impl<'a> Matrix<'a> {
+    // None if the placed numbers already conflict
+    fn new(grid: &'a Grid) -> Option<Matrix<'a>> {
+        let g = grid.geometry();
+        let size = g.size();
+        let rules = Rules::new(grid);
+        let (mut cages, cage_of) = CageState::of_grid(grid);
+
+        // Placed numbers are no rows, they only take numbers away from the
+        // empty cells and cover their columns up front
+        let excluded = rules.peer_excluded_all(grid);
+        let numbers = grid.numbers();
+        let value = |idx: CellIndex| numbers[idx.get()];
+        for idx in g.all_cells() {
+            if let Some(digit) = grid.value(idx) {
+                let cage = cage_of[idx.get()];
+                if excluded[idx.get()].contains(digit)
+                    || !cage.is_none_or(|c| cages[c].fits(digit, g.all_candidates()))
+                    || !rules.fits_lines(idx, digit, &value)
+                {
+                    return None;
+                }
+                if let Some(c) = cage {
+                    cages[c].place(digit);
+                }
+            }
+        }
+
+        let mut rows = Vec::new();
+        let mut row_of = vec![None; g.cells() * size];
+        let empty: Vec<CellIndex> = g.all_cells().filter(|&idx| grid.value(idx).is_none()).collect();
+        for &idx in &empty {
+            for digit in g.all_candidates().difference(excluded[idx.get()]).iter() {
+                row_of[idx.get() * size + digit.index()] = Some(rows.len());
+                rows.push((idx, digit));
+            }
+        }
+        let row_at = |idx: CellIndex, digit: Digit| row_of[idx.get() * size + digit.index()];
+
+        // (row, column) of every node, columns counted from 1
+        let mut primary = Vec::new();
+        let mut entries = Vec::new();
+
+        for &idx in &empty {
+            primary.push(true);
+            let c = primary.len();
+            entries.extend(g.digits().filter_map(|digit| row_at(idx, digit)).map(|r| (r, c)));
+        }
+        for (unit, mask) in rules.units().iter().zip(rules.unit_masks(grid)) {
+            for digit in g.all_candidates().difference(mask.seen).iter() {
+                primary.push(unit.len() == size);
+                let c = primary.len();
+                entries.extend(unit.iter().filter_map(|&idx| row_at(idx, digit)).map(|r| (r, c)));
+            }
+        }
+        for &a in &empty {
+            for (b, constraint) in rules.neighbours(a).filter(|&(b, _)| a < b) {
+                for digit in g.digits() {
+                    for other in constraint.excluded_by(digit, size).iter() {
+                        if let (Some(ra), Some(rb)) = (row_at(a, digit), row_at(b, other)) {
+                            primary.push(false);
+                            entries.extend([(ra, primary.len()), (rb, primary.len())]);
+                        }
+                    }
+                }
+            }
+        }
+
+        let columns = primary.len();
+        let nodes = columns + 1 + entries.len();
+        let mut matrix = Matrix {
+            left: Vec::with_capacity(nodes),
+            right: Vec::with_capacity(nodes),
+            up: Vec::with_capacity(nodes),
+            down: Vec::with_capacity(nodes),
+            column: Vec::with_capacity(nodes),
+            row: vec![usize::MAX; columns + 1],
+            size: vec![0; columns + 1],
+            filtered: !cages.is_empty() || g.all_cells().any(|idx| rules.has_lines(idx)),
+            rows,
+            numbers,
+            all: g.all_candidates(),
+            cages,
+            cage_of,
+            rules,
+        };
+        for links in [&mut matrix.left, &mut matrix.right, &mut matrix.up, &mut matrix.down, &mut matrix.column] {
+            links.extend(0..=columns);
+        }
+
+        let mut last = ROOT;
+        for c in (1..=columns).filter(|&c| primary[c - 1]) {
+            matrix.right[last] = c;
+            matrix.left[c] = last;
+            last = c;
+        }
+        matrix.right[last] = ROOT;
+        matrix.left[ROOT] = last;
+
+        // Nodes of one row next to each other, counting sort by row
+        let mut start = vec![0; matrix.rows.len() + 1];
+        for &(r, _) in &entries {
+            start[r + 1] += 1;
+        }
+        for r in 0..matrix.rows.len() {
+            start[r + 1] += start[r];
+        }
+        let mut sorted = vec![0; entries.len()];
+        let mut next = start.clone();
+        for &(r, c) in &entries {
+            sorted[next[r]] = c;
+            next[r] += 1;
+        }
+
+        let base = columns + 1;
+        for r in 0..matrix.rows.len() {
+            let (first, end) = (base + start[r], base + start[r + 1]);
+            for node in first..end {
+                let c = sorted[node - base];
+                let above = matrix.up[c];
+                matrix.left.push(if node == first { end - 1 } else { node - 1 });
+                matrix.right.push(if node + 1 == end { first } else { node + 1 });
+                matrix.up.push(above);
+                matrix.down.push(c);
+                matrix.column.push(c);
+                matrix.row.push(r);
+                matrix.down[above] = node;
+                matrix.up[c] = node;
+                matrix.size[c] += 1;
+            }
+        }
+
+        Some(matrix)
+    }
+
+    fn cover(&mut self, c: usize) {
+        self.right[self.left[c]] = self.right[c];
+        self.left[self.right[c]] = self.left[c];
+
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                self.up[self.down[j]] = self.up[j];
+                self.down[self.up[j]] = self.down[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.up[self.down[j]] = j;
+                self.down[self.up[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[c]] = c;
+        self.left[self.right[c]] = c;
+    }
+
+    // Row of node joins the solution, its own column is already covered
+    fn select(&mut self, node: usize) {
+        let mut j = self.right[node];
+        while j != node {
+            self.cover(self.column[j]);
+            j = self.right[j];
+        }
+
+        let (idx, digit) = self.rows[self.row[node]];
+        self.numbers[idx.get()] = digit.get();
+        if let Some(c) = self.cage_of[idx.get()] {
+            self.cages[c].place(digit);
+        }
+    }
+
+    fn deselect(&mut self, node: usize) {
+        let (idx, digit) = self.rows[self.row[node]];
+        self.numbers[idx.get()] = 0;
+        if let Some(c) = self.cage_of[idx.get()] {
+            self.cages[c].unplace(digit);
+        }
+
+        let mut j = self.left[node];
+        while j != node {
+            self.uncover(self.column[j]);
+            j = self.left[j];
+        }
+    }
+
+    // Killer sum and line rules still reachable with the row's number
+    fn fits(&self, node: usize) -> bool {
+        let (idx, digit) = self.rows[self.row[node]];
+        let value = |other: CellIndex| self.numbers[other.get()];
+
+        self.cage_of[idx.get()].is_none_or(|c| self.cages[c].fits(digit, self.all))
+            && (!self.rules.has_lines(idx) || self.rules.fits_lines(idx, digit, &value))
+    }
+
+    // Rows of column c the search can still take
+    fn options(&self, c: usize) -> usize {
+        if !self.filtered {
+            return self.size[c];
+        }
+
+        let mut count = 0;
+        let mut node = self.down[c];
+        while node != c {
+            count += self.fits(node) as usize;
+            node = self.down[node];
+        }
+        count
+    }
+
+    // Counts solutions up to limit, the first one found is kept in `first`
+    fn search(&mut self, limit: usize, found: &mut usize, first: &mut Option<Vec<u8>>) {
+        let mut c = self.right[ROOT];
+        if c == ROOT {
+            *found += 1;
+            if first.is_none() {
+                *first = Some(self.numbers.clone());
+            }
+            return;
+        }
+
+        // Column with the fewest rows, with killer sums and line rules the
+        // fewest rows that still fit
+        let (mut best, mut fewest) = (c, usize::MAX);
+        while c != ROOT && fewest > 1 {
+            let options = self.options(c);
+            if options < fewest {
+                (best, fewest) = (c, options);
+            }
+            c = self.right[c];
+        }
+        if fewest == 0 {
+            return;
+        }
+
+        self.cover(best);
+        let mut node = self.down[best];
+        while node != best && *found < limit {
+            if self.fits(node) {
+                self.select(node);
+                self.search(limit, found, first);
+                self.deselect(node);
+            }
+            node = self.down[node];
+        }
+        self.uncover(best);
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
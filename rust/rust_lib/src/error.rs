@@ -0,0 +1,247 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Error handling shared by all modules
+Every failure has a stable negative code, Dart only sees the number.
+The numbers below never change, new errors get new numbers.
+
+Break with the codes from before SudokuError (ABI_VERSION 4) :
+save_data returned -3 when the grid could not be serialized and load_data
+-2 when the file could not be parsed. -2 and -3 now always mean
+WriteFailed and ReadFailed, the two cases are SerializeFailed (-16) and
+ParseFailed (-15).
+
+Every extern "C" function runs its body through ffi_boundary :
+- Err(..) → code returned to Dart, text kept for last_error_message
+- panic   → caught, never unwinds into Dart, reported as SudokuError::Panic
+*/
+
+use std::cell::RefCell;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::ffi::string_to_c_char;
+
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SudokuError {
+    NullPointer = -1,
+    WriteFailed = -2,
+    ReadFailed = -3,
+    DimensionMismatch = -4,
+    CorruptData = -5,
+    InvalidUtf8 = -6,
+    InvalidLength = -7,
+    InvalidCharacter = -8,
+    UnknownFormat = -9,
+    IndexOutOfRange = -10,
+    NotFound = -11,
+    DuplicateId = -12,
+    UnsupportedVersion = -13,
+    ChecksumMismatch = -14,
+    ParseFailed = -15,
+    SerializeFailed = -16,
+    AllocationFailed = -17,
+    Panic = -18,
+    InvalidHandle = -19,
+    ReadOnlyCell = -20,
+    InvalidCage = -21,
+    InvalidRegions = -22,
+    InvalidVariant = -23,
+    InvalidLayout = -24,
+    NotRepresentable = -25,
+}
+
+impl SudokuError {
+    /// cbindgen:ignore
+    pub const ALL: [SudokuError; 25] = [
+        SudokuError::NullPointer,
+        SudokuError::WriteFailed,
+        SudokuError::ReadFailed,
+        SudokuError::DimensionMismatch,
+        SudokuError::CorruptData,
+        SudokuError::InvalidUtf8,
+        SudokuError::InvalidLength,
+        SudokuError::InvalidCharacter,
+        SudokuError::UnknownFormat,
+        SudokuError::IndexOutOfRange,
+        SudokuError::NotFound,
+        SudokuError::DuplicateId,
+        SudokuError::UnsupportedVersion,
+        SudokuError::ChecksumMismatch,
+        SudokuError::ParseFailed,
+        SudokuError::SerializeFailed,
+        SudokuError::AllocationFailed,
+        SudokuError::Panic,
+        SudokuError::InvalidHandle,
+        SudokuError::ReadOnlyCell,
+        SudokuError::InvalidCage,
+        SudokuError::InvalidRegions,
+        SudokuError::InvalidVariant,
+        SudokuError::InvalidLayout,
+        SudokuError::NotRepresentable,
+    ];
+
+    pub fn code(self) -> c_int {
+        self as c_int
+    }
+
+    pub fn from_code(code: c_int) -> Option<SudokuError> {
+        SudokuError::ALL.into_iter().find(|e| e.code() == code)
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            SudokuError::NullPointer => "null pointer",
+            SudokuError::WriteFailed => "file could not be written",
+            SudokuError::ReadFailed => "file could not be read",
+            SudokuError::DimensionMismatch => "grid size does not match",
+            SudokuError::CorruptData => "truncated or corrupt data",
+            SudokuError::InvalidUtf8 => "string is not valid UTF-8",
+            SudokuError::InvalidLength => "invalid length",
+            SudokuError::InvalidCharacter => "invalid character",
+            SudokuError::UnknownFormat => "unknown format",
+            SudokuError::IndexOutOfRange => "index out of range",
+            SudokuError::NotFound => "no matching entry",
+            SudokuError::DuplicateId => "id already exists",
+            SudokuError::UnsupportedVersion => "written by a newer app version",
+            SudokuError::ChecksumMismatch => "checksum mismatch",
+            SudokuError::ParseFailed => "content could not be parsed",
+            SudokuError::SerializeFailed => "content could not be serialized",
+            SudokuError::AllocationFailed => "memory allocation failed",
+            SudokuError::Panic => "internal error",
+            SudokuError::InvalidHandle => "unknown or released session handle",
+            SudokuError::ReadOnlyCell => "cell can not be changed",
+            SudokuError::InvalidCage => "cage cells or sum not possible",
+            SudokuError::InvalidRegions => "regions are not a partition of the grid",
+            SudokuError::InvalidVariant => "variant cells or sum not possible",
+            SudokuError::InvalidLayout => "grids of the layout are not joined by shared cells",
+            SudokuError::NotRepresentable => "grid can not be written in this format",
+        }
+    }
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message(), self.code())
+    }
+}
+
+impl std::error::Error for SudokuError {}
+
+impl From<SudokuError> for c_int {
+    fn from(err: SudokuError) -> c_int {
+        err.code()
+    }
+}
+
+thread_local! {
+    // Per thread, Dart calls from the isolate thread that made the call
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown panic"
+    }
+}
+
+// Wrap the body of an extern "C" function
+pub(crate) fn ffi_boundary<T>(body: impl FnOnce() -> Result<T, SudokuError>) -> Result<T, SudokuError> {
+    let result = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => result,
+        Err(payload) => {
+            set_last_error(format!("{}: {}", SudokuError::Panic, panic_message(payload.as_ref())));
+            return Err(SudokuError::Panic);
+        }
+    };
+
+    if let Err(err) = result {
+        set_last_error(err.to_string());
+    }
+
+    result
+}
+
+// 0 on success, otherwise the error code
+pub(crate) fn ffi_status(body: impl FnOnce() -> Result<(), SudokuError>) -> c_int {
+    ffi_boundary(body).map_or_else(SudokuError::code, |_| 0)
+}
+
+// Count or index on success, otherwise the (negative) error code
+#[cfg(any(feature = "solver", feature = "storage", feature = "session", feature = "patterns"))]
+pub(crate) fn ffi_value(body: impl FnOnce() -> Result<c_int, SudokuError>) -> c_int {
+    ffi_boundary(body).unwrap_or_else(SudokuError::code)
+}
+
+// String for Dart on success (release with free_rust_string), otherwise null
+#[cfg(feature = "storage")]
+pub(crate) fn ffi_string(body: impl FnOnce() -> Result<String, SudokuError>) -> *mut c_char {
+    ffi_boundary(body).map_or(std::ptr::null_mut(), string_to_c_char)
+}
+
+// Text of the last error on this thread, null if there was none.
+// Must be released with free_rust_string.
+#[no_mangle]
+pub extern "C" fn last_error_message() -> *mut c_char {
+    match LAST_ERROR.with(|last| last.borrow().clone()) {
+        Some(message) => string_to_c_char(message),
+        None => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+// Message for a code returned by any function, for logging on the Dart side.
+// Must be released with free_rust_string.
+#[no_mangle]
+pub extern "C" fn error_code_message(code: c_int) -> *mut c_char {
+    match SudokuError::from_code(code) {
+        Some(err) => string_to_c_char(err.message().to_string()),
+        None => std::ptr::null_mut(),
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -55,9 +55,23 @@ Allocations, updates, and deallocation all compile cleanly.
 
 // for general tasks like FFI interface
 use std::alloc::{alloc, dealloc, Layout};
+use std::ffi::CString;
+#[cfg(feature = "storage")]
+use std::ffi::CStr;
+use std::mem::{align_of, offset_of, size_of};
+use std::os::raw::{c_char, c_int};
 
-use crate::process_data::check_one_element;
-use crate::process_data::check_all_elements;
+use static_assertions::const_assert_eq;
+
+#[cfg(feature = "patterns")]
+use crate::process_data::{check_grid_for_patterns, update_cell_at};
+
+#[cfg(feature = "patterns")]
+use crate::error::ffi_value;
+
+use crate::error::{ffi_boundary, ffi_status, SudokuError};
+
+use crate::grid::{Geometry, Grid, GRID_CELLS, MAX_GRID_SIZE};
 
 pub const MAX_UINT: u8 = 255;
 pub const CONST_MATRIX_SIZE: u8 = 9;
@@ -108,7 +122,7 @@ pub const constSelectedNumberListAllSelected: [u8; constSelectedNumberListSize a
     [1; constSelectedNumberListSize as usize];
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct DartToRustElementFFI {
     pub row: u8,
     pub col: u8,
@@ -120,200 +134,493 @@ pub struct DartToRustElementFFI {
     pub requestedCandHighLightType: [u8; constRequestedCandHighLightTypeSize as usize],
 }
 
+// Cell of the session buffers (session_read / session_write), any grid size.
+// The lists hold one entry per digit up to 16, entries above the grid size
+// are 0. A buffer holds exactly size * size elements, row major.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GridElementFFI {
+    pub row: u8,
+    pub col: u8,
+    pub selectedNum: u8,
+    pub selectedNumStateList: [u8; constSelectedNumStateListSize as usize],
+    pub selectedCandList: [u8; MAX_GRID_SIZE],
+    pub selectedPatternList: [u8; constSelectedPatternListSize as usize],
+    pub requestedElementHighLightType: [u8; constRequestedElementHighLightTypeSize as usize],
+    pub requestedCandHighLightType: [u8; MAX_GRID_SIZE],
+}
+
+impl Default for GridElementFFI {
+    fn default() -> Self {
+        GridElementFFI {
+            row: 0,
+            col: 0,
+            selectedNum: 0,
+            selectedNumStateList: constSelectedNumStateList,
+            selectedCandList: [0; MAX_GRID_SIZE],
+            selectedPatternList: constSelectedPatternList,
+            requestedElementHighLightType: constRequestedElementHighLightType,
+            requestedCandHighLightType: [constPatternListOff; MAX_GRID_SIZE],
+        }
+    }
+}
+
+// Bump on every change of an exported signature or of DartToRustElementFFI /
+// GridElementFFI, rust_matrix.dart refuses to run against a different version.
+pub const ABI_VERSION: u32 = 6;
+
+// sizeOf<DartToRustElementFFI>() on the Dart side
+pub const ELEMENT_FFI_SIZE: usize = 32;
+
+// Layout of DartToRustElementFFI is written by hand in rust_matrix.dart,
+// every field is u8 so there is no padding.
+const_assert_eq!(size_of::<DartToRustElementFFI>(), ELEMENT_FFI_SIZE);
+const_assert_eq!(align_of::<DartToRustElementFFI>(), 1);
+const_assert_eq!(offset_of!(DartToRustElementFFI, row), 0);
+const_assert_eq!(offset_of!(DartToRustElementFFI, col), 1);
+const_assert_eq!(offset_of!(DartToRustElementFFI, selectedNum), 2);
+const_assert_eq!(offset_of!(DartToRustElementFFI, selectedNumStateList), 3);
+const_assert_eq!(offset_of!(DartToRustElementFFI, selectedCandList), 5);
+const_assert_eq!(offset_of!(DartToRustElementFFI, selectedPatternList), 14);
+const_assert_eq!(offset_of!(DartToRustElementFFI, requestedElementHighLightType), 18);
+const_assert_eq!(offset_of!(DartToRustElementFFI, requestedCandHighLightType), 23);
+
+// sizeOf<GridElementFFI>() on the Dart side
+pub const GRID_ELEMENT_FFI_SIZE: usize = 46;
+
+const_assert_eq!(size_of::<GridElementFFI>(), GRID_ELEMENT_FFI_SIZE);
+const_assert_eq!(align_of::<GridElementFFI>(), 1);
+const_assert_eq!(offset_of!(GridElementFFI, selectedNumStateList), 3);
+const_assert_eq!(offset_of!(GridElementFFI, selectedCandList), 5);
+const_assert_eq!(offset_of!(GridElementFFI, selectedPatternList), 21);
+const_assert_eq!(offset_of!(GridElementFFI, requestedElementHighLightType), 25);
+const_assert_eq!(offset_of!(GridElementFFI, requestedCandHighLightType), 30);
+
+#[no_mangle]
+pub extern "C" fn abi_version() -> u32 {
+    ABI_VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn element_ffi_size() -> u32 {
+    ELEMENT_FFI_SIZE as u32
+}
+
+#[no_mangle]
+pub extern "C" fn grid_element_ffi_size() -> u32 {
+    GRID_ELEMENT_FFI_SIZE as u32
+}
+
+// Deprecated : pointer + rows / cols functions on a Rust allocated
+// DartToRustElementFFI matrix. rust_matrix.dart runs on the handle based
+// session_* functions (session.rs) since ABI_VERSION 6, nothing in the app
+// calls these any more. Removal plan :
+//   ABI_VERSION 6 : kept unchanged for other callers of the library
+//   ABI_VERSION 7 : create_matrix, update_matrix, update_cell, erase_matrix,
+//                   set_all_candidates and free_matrix are removed, the
+//                   storage / puzzle string functions taking a
+//                   DartToRustElementFFI buffer get session_* counterparts
+//   ABI_VERSION 8 : DartToRustElementFFI and element_ffi_size are removed
 #[no_mangle]
 pub unsafe extern "C" fn create_matrix(rows: u8, cols: u8) -> *mut DartToRustElementFFI {
-    let rows_usize = rows as usize;
-    let cols_usize = cols as usize;
+    ffi_boundary(|| {
+        check_dimensions(rows, cols)?;
 
-    assert!(rows == constSelectedNumberListSize);
-    assert!(cols == constSelectedNumberListSize);
+        let layout = matrix_layout(rows, cols)?;
+        let ptr = alloc(layout) as *mut DartToRustElementFFI;
 
-    let count = rows_usize * cols_usize;
-    let layout = Layout::array::<DartToRustElementFFI>(count).unwrap();
-    let ptr = alloc(layout) as *mut DartToRustElementFFI;
+        if ptr.is_null() {
+            return Err(SudokuError::AllocationFailed);
+        }
 
-    if ptr.is_null() {
-        return std::ptr::null_mut();
-    }
+        // Initial state comes from the safe grid layer
+        for (idx, cell) in Grid::new().to_ffi().into_iter().enumerate() {
+            ptr.add(idx).write(cell);
+        }
 
-    for r in 0..rows_usize {
-        for c in 0..cols_usize {
-            let idx = r * cols_usize + c;
-            let cell = ptr.add(idx);
-
-            // Unsafe block to write raw pointer data
-            unsafe {
-                (*cell).row = r as u8;
-                (*cell).col = c as u8;
-                (*cell).selectedNum = 0;
-                (*cell).selectedNumStateList = constSelectedNumStateList;
-                (*cell).selectedCandList = constSelectedNumberList;
-                (*cell).selectedPatternList = constSelectedPatternList;
-                (*cell).requestedElementHighLightType = constRequestedElementHighLightType;
-                (*cell).requestedCandHighLightType = constRequestedCandHighLightType;
-            }
+        Ok(ptr)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+// Dart wrote cell idx : highlights are updated around it, with
+// remove_candidates > 0 its number also leaves the peers' candidates
+// (process_data::update_cell_at). dirty receives the indices of the cells to
+// redraw, dirty_len must fit them (GRID_CELLS always does).
+// Returns their count or a SudokuError code.
+#[cfg(feature = "patterns")]
+#[no_mangle]
+pub unsafe extern "C" fn update_cell(
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    idx: u8,
+    remove_candidates: u8,
+    dirty: *mut u8,
+    dirty_len: u32,
+) -> c_int {
+    ffi_value(|| {
+        let mut grid = read_grid(ptr, rows, cols)?;
+        let idx = grid.geometry().cell(idx as usize).ok_or(SudokuError::IndexOutOfRange)?;
+        if dirty.is_null() {
+            return Err(SudokuError::NullPointer);
         }
-    }
 
-    ptr
+        let cells = update_cell_at(&mut grid, idx, remove_candidates > 0);
+        if cells.len() > dirty_len as usize {
+            return Err(SudokuError::InvalidLength);
+        }
+        write_grid(&grid, ptr)?;
+
+        let out = std::slice::from_raw_parts_mut(dirty, cells.len());
+        for (slot, cell) in out.iter_mut().zip(&cells) {
+            *slot = cell.get() as u8;
+        }
+        Ok(cells.len() as c_int)
+    })
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn update_cell(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8, idx: u8) {
+pub unsafe extern "C" fn erase_matrix(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8, erase_givens: u8) -> c_int {
+    ffi_status(|| {
+        let mut grid = read_grid(ptr, rows, cols)?;
+        grid.erase(erase_givens > 0);
+        write_grid(&grid, ptr)?;
+        Ok(())
+    })
+}
+
+
+#[no_mangle]
+pub unsafe extern "C" fn set_all_candidates(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8) -> c_int {
+    ffi_status(|| {
+        let mut grid = read_grid(ptr, rows, cols)?;
+        // only cells without number
+        grid.set_all_candidates();
+        write_grid(&grid, ptr)?;
+        Ok(())
+    })
+}
+
+#[cfg(feature = "patterns")]
+#[no_mangle]
+pub unsafe extern "C" fn update_matrix(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8) -> c_int {
+    ffi_status(|| {
+        // Full pass with hidden singles, update_cell continues from it
+        let mut grid = read_grid(ptr, rows, cols)?;
+        check_grid_for_patterns(&mut grid);
+        write_grid(&grid, ptr)
+    })
+}
+
+
+
+// Add update cell function
+
+#[no_mangle]
+pub unsafe extern "C" fn free_matrix(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8) {
     if ptr.is_null() {
         return;
     }
 
-    let rows_usize = rows as usize;
-    let cols_usize = cols as usize;
-    let count = rows_usize * cols_usize;
-
-    // Check matrix size
-    assert!(count <= CONST_MATRIX_ELEMENTS as usize);
+    // Never free with a layout that differs from create_matrix
+    let _ = ffi_boundary(|| {
+        check_dimensions(rows, cols)?;
+        dealloc(ptr as *mut u8, matrix_layout(rows, cols)?);
+        Ok(())
+    });
+}
 
-    // check max. index
-    assert!((idx as usize) < count);
+// Only 9 x 9 matrices are allocated by create_matrix
+pub(crate) fn check_dimensions(rows: u8, cols: u8) -> Result<(), SudokuError> {
+    if rows != CONST_MATRIX_SIZE || cols != CONST_MATRIX_SIZE {
+        return Err(SudokuError::DimensionMismatch);
+    }
 
-    // Check if the element has only 2 candidates
-    check_one_element(ptr, idx as usize)
+    Ok(())
+}
 
+fn matrix_layout(rows: u8, cols: u8) -> Result<Layout, SudokuError> {
+    Layout::array::<DartToRustElementFFI>((rows as usize) * (cols as usize))
+        .map_err(|_| SudokuError::AllocationFailed)
 }
 
-#[no_mangle]
-pub unsafe extern "C" fn erase_matrix(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8, erase_givens: u8) {
+// Copy the Dart buffer into a safe Grid
+pub(crate) unsafe fn read_grid(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+) -> Result<Grid, SudokuError> {
     if ptr.is_null() {
-        return;
+        return Err(SudokuError::NullPointer);
     }
 
-    let rows_usize = rows as usize;
-    let cols_usize = cols as usize;
-    let count = rows_usize * cols_usize;
-
-    // Check matrix size
-    assert!(count <= CONST_MATRIX_ELEMENTS as usize);
-
-    for r in 0..rows_usize {
-        for c in 0..cols_usize {
-            let idx = r * cols_usize + c;
-            
-            // check max. index
-            assert!(idx < count);
-
-            let cell = &mut *ptr.add(idx);
-            
-            // Unsafe block to write raw pointer data
-            unsafe {
-                if erase_givens > 0  {
-                    // erase givens
-                    (*cell).selectedNum = 0;
-                    (*cell).selectedNumStateList = constSelectedNumStateList;
-                    (*cell).selectedCandList = constSelectedNumberList;
-                    (*cell).selectedPatternList = constSelectedPatternList;
-                    (*cell).requestedElementHighLightType = constRequestedElementHighLightType;
-                    (*cell).requestedCandHighLightType = constRequestedCandHighLightType;
-                }
-                else if erase_givens == 0{
-                    // do not erase givens
-                    if (*cell).selectedNumStateList[NumStateListIndex::GIVENS as usize] == 0 {
-                        // Not a given, erase
-                        (*cell).selectedNum = 0;
-                        (*cell).selectedNumStateList = constSelectedNumStateList;
-                        
-                    }
-
-                    // Always erase candidates and highlights
-                    (*cell).selectedCandList = constSelectedNumberList;
-                    (*cell).selectedPatternList = constSelectedPatternList;
-                    (*cell).requestedElementHighLightType = constRequestedElementHighLightType;
-                    (*cell).requestedCandHighLightType = constRequestedCandHighLightType;
+    check_dimensions(rows, cols)?;
 
-                }
-                else {
-                    // do nothing
-                }
-            }
-        }
+    let slice = std::slice::from_raw_parts(ptr, GRID_CELLS);
+    Grid::from_ffi(slice).ok_or(SudokuError::CorruptData)
+}
+
+// Copy the Grid back into the Dart buffer, ptr must come from read_grid checks.
+// The buffer only holds the classic 9 x 9 grid.
+pub(crate) unsafe fn write_grid(grid: &Grid, ptr: *mut DartToRustElementFFI) -> Result<(), SudokuError> {
+    if grid.geometry() != Geometry::CLASSIC {
+        return Err(SudokuError::DimensionMismatch);
     }
 
+    let slice = std::slice::from_raw_parts_mut(ptr, GRID_CELLS);
+    grid.write_ffi(slice);
+    Ok(())
 }
 
+// Convert a C string coming from Dart (toNativeUtf8) into a Rust &str
+#[cfg(feature = "storage")]
+pub(crate) unsafe fn c_char_to_str<'a>(s: *const c_char) -> Result<&'a str, SudokuError> {
+    if s.is_null() {
+        return Err(SudokuError::NullPointer);
+    }
+
+    CStr::from_ptr(s).to_str().map_err(|_| SudokuError::InvalidUtf8)
+}
+
+// Hand a Rust string over to Dart. Must be released with free_rust_string.
+pub(crate) fn string_to_c_char(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
 
 #[no_mangle]
-pub unsafe extern "C" fn set_all_candidates(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8) {
-    if ptr.is_null() {
+pub unsafe extern "C" fn free_rust_string(s: *mut c_char) {
+    if s.is_null() {
         return;
     }
 
-    let rows_usize = rows as usize;
-    let cols_usize = cols as usize;
-    let count = rows_usize * cols_usize;
+    let _ = ffi_boundary(|| {
+        drop(CString::from_raw(s));
+        Ok(())
+    });
+}
 
-    // Check matrix size
-    assert!(count <= CONST_MATRIX_ELEMENTS as usize);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for r in 0..rows_usize {
-        for c in 0..cols_usize {
-            let idx = r * cols_usize + c;
-            
-            // check max. index
-            assert!(idx < count);
+    use crate::grid::{CandidateSet, Cell, CellIndex, Digit};
 
-            let cell = &mut *ptr.add(idx);
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
 
+    // Two givens, a placed number, candidates and user patterns
+    fn sample_grid() -> Grid {
+        let mut grid = Grid::new();
+        *grid.cell_mut(idx(0)) = Cell::given(Digit::new(5).unwrap());
+        *grid.cell_mut(idx(40)) = Cell::given(Digit::new(9).unwrap());
+        grid.cell_mut(idx(1)).value = Digit::new(3);
+        grid.cell_mut(idx(2)).candidates = CandidateSet::from_flags(&[0, 1, 1, 0, 0, 0, 0, 0, 0]);
+        grid.cell_mut(idx(2)).patterns = [1, 0, 1, 0];
+        grid.cell_mut(idx(3)).candidates = CandidateSet::from_flags(&[0, 0, 0, 0, 1, 0, 0, 0, 0]);
+        grid
+    }
 
-            
-            // Unsafe block to write raw pointer data
-            unsafe {
-                // only if no number is selected
-                if (*cell).selectedNum == 0 {
+    // Matrix allocated like rust_matrix.dart does, filled with grid
+    unsafe fn matrix(grid: &Grid) -> *mut DartToRustElementFFI {
+        let ptr = create_matrix(9, 9);
+        assert!(!ptr.is_null());
+        write_grid(grid, ptr).unwrap();
+        ptr
+    }
 
-                    // Set all candidates
-                    (*cell).selectedCandList = constSelectedNumberListAllSelected;
-                    
-                }
-                else {
-                    // do nothing
-                }
-            }
+    unsafe fn grid_at(ptr: *mut DartToRustElementFFI) -> Grid {
+        read_grid(ptr, 9, 9).unwrap()
+    }
+
+    // What is left of grid after a trip through the buffer (9 candidate highlights)
+    fn through_ffi(grid: &Grid) -> Grid {
+        Grid::from_ffi(&grid.to_ffi()).unwrap()
+    }
+
+    #[test]
+    fn create_matrix_starts_with_an_empty_grid() {
+        unsafe {
+            let ptr = create_matrix(9, 9);
+            assert!(!ptr.is_null());
+            assert_eq!(std::slice::from_raw_parts(ptr, GRID_CELLS), Grid::new().to_ffi().as_slice());
+            free_matrix(ptr, 9, 9);
+
+            // The pointer API only knows the classic grid
+            assert!(create_matrix(9, 8).is_null());
+            assert!(create_matrix(16, 16).is_null());
         }
     }
 
-}
+    #[test]
+    fn erase_matrix_keeps_givens() {
+        unsafe {
+            let ptr = matrix(&sample_grid());
+            assert_eq!(erase_matrix(ptr, 9, 9, 0), 0);
 
-#[no_mangle]
-pub unsafe extern "C" fn update_matrix(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8) {
-    if ptr.is_null() {
-        return;
+            let grid = grid_at(ptr);
+            free_matrix(ptr, 9, 9);
+
+            assert_eq!(grid.value(idx(0)), Digit::new(5));
+            assert_eq!(grid.value(idx(40)), Digit::new(9));
+            assert_eq!(grid.value(idx(1)), None);
+            assert_eq!(grid.cell(idx(2)), through_ffi(&Grid::new()).cell(idx(2)));
+            assert_eq!(grid.givens(), sample_grid().givens());
+        }
     }
 
-    let rows_usize = rows as usize;
-    let cols_usize = cols as usize;
-    let count = rows_usize * cols_usize;
+    #[test]
+    fn erase_matrix_with_givens_empties_the_grid() {
+        unsafe {
+            let ptr = matrix(&sample_grid());
+            assert_eq!(erase_matrix(ptr, 9, 9, 1), 0);
 
-    // Check matrix size
-    assert!(count <= CONST_MATRIX_ELEMENTS as usize);
+            let grid = grid_at(ptr);
+            free_matrix(ptr, 9, 9);
 
-    // ✅ Just do all elements at once
-    check_all_elements(ptr, count);
+            assert_eq!(grid, through_ffi(&Grid::new()));
+        }
+    }
 
-}
+    #[test]
+    fn set_all_candidates_fills_empty_cells_only() {
+        let before = through_ffi(&sample_grid());
 
+        unsafe {
+            let ptr = matrix(&before);
+            assert_eq!(set_all_candidates(ptr, 9, 9), 0);
 
+            let grid = grid_at(ptr);
+            free_matrix(ptr, 9, 9);
 
-// Add update cell function
+            for i in Geometry::CLASSIC.all_cells() {
+                if before.cell(i).is_empty() {
+                    assert_eq!(grid.cell(i).candidates, Geometry::CLASSIC.all_candidates());
+                } else {
+                    assert_eq!(grid.cell(i), before.cell(i));
+                }
+            }
+        }
+    }
 
-#[no_mangle]
-pub unsafe extern "C" fn free_matrix(ptr: *mut DartToRustElementFFI, rows: u8, cols: u8) {
-    if ptr.is_null() {
-        return;
+    #[cfg(feature = "patterns")]
+    #[test]
+    fn update_cell_reports_dirty_cells() {
+        use crate::process_data::{check_grid_for_patterns, remove_from_peers};
+
+        let mut dirty = [0u8; GRID_CELLS];
+        unsafe {
+            let ptr = matrix(&sample_grid());
+            assert_eq!(update_matrix(ptr, 9, 9), 0);
+            assert_eq!(grid_at(ptr).cell(idx(3)).candidate_highlight[4], PatternList::SINGLES);
+
+            // Dart writes a 2 into box 0, cell 2 keeps only its 3
+            let mut before = grid_at(ptr);
+            before.set_value(idx(10), Digit::new(2));
+            write_grid(&before, ptr).unwrap();
+
+            let count = update_cell(ptr, 9, 9, 10, 1, dirty.as_mut_ptr(), GRID_CELLS as u32);
+            assert!(count > 0);
+            let dirty = &dirty[..count as usize];
+            assert!(dirty.contains(&2) && dirty.contains(&10));
+            assert!(dirty.windows(2).all(|pair| pair[0] < pair[1]));
+
+            // Same as the full pass, only the dirty cells differ from before
+            let mut expected = before.clone();
+            remove_from_peers(&mut expected, idx(10));
+            check_grid_for_patterns(&mut expected);
+            let grid = grid_at(ptr);
+            assert_eq!(grid, through_ffi(&expected));
+            assert_eq!(grid.cell(idx(2)).candidates, CandidateSet::single(Digit::new(3).unwrap()));
+            for i in Geometry::CLASSIC.all_cells().filter(|i| !dirty.contains(&(i.get() as u8))) {
+                assert_eq!(grid.cell(i), before.cell(i));
+            }
+            free_matrix(ptr, 9, 9);
+        }
+    }
+
+    #[cfg(feature = "patterns")]
+    #[test]
+    fn update_cell_keeps_pencil_marks_by_default() {
+        use crate::process_data::check_grid_for_patterns;
+
+        let mut dirty = [0u8; GRID_CELLS];
+        unsafe {
+            let ptr = matrix(&sample_grid());
+            assert_eq!(update_matrix(ptr, 9, 9), 0);
+
+            let mut before = grid_at(ptr);
+            before.set_value(idx(10), Digit::new(2));
+            write_grid(&before, ptr).unwrap();
+
+            let count = update_cell(ptr, 9, 9, 10, 0, dirty.as_mut_ptr(), GRID_CELLS as u32);
+            assert!(count > 0);
+            let dirty = &dirty[..count as usize];
+            assert!(dirty.contains(&10));
+
+            // Highlights as after a full pass, no candidate taken away
+            let mut expected = before.clone();
+            check_grid_for_patterns(&mut expected);
+            let grid = grid_at(ptr);
+            assert_eq!(grid, through_ffi(&expected));
+            for i in Geometry::CLASSIC.all_cells() {
+                assert_eq!(grid.cell(i).candidates, before.cell(i).candidates);
+                if !dirty.contains(&(i.get() as u8)) {
+                    assert_eq!(grid.cell(i), before.cell(i));
+                }
+            }
+            free_matrix(ptr, 9, 9);
+        }
     }
 
-    let rows_usize = rows as usize;
-    let cols_usize = cols as usize;
-    let count = rows_usize * cols_usize;
-    let layout = Layout::array::<DartToRustElementFFI>(count).unwrap();
+    #[cfg(feature = "patterns")]
+    #[test]
+    fn update_cell_rejects_bad_arguments() {
+        use crate::process_data::check_one_element;
+
+        let mut dirty = [0u8; GRID_CELLS];
+        let out = dirty.as_mut_ptr();
+        unsafe {
+            let ptr = matrix(&sample_grid());
+            assert_eq!(update_cell(ptr, 9, 9, 81, 0, out, GRID_CELLS as u32), SudokuError::IndexOutOfRange.code());
+            assert_eq!(update_cell(ptr, 8, 9, 2, 0, out, GRID_CELLS as u32), SudokuError::DimensionMismatch.code());
+            assert_eq!(update_cell(std::ptr::null_mut(), 9, 9, 2, 0, out, GRID_CELLS as u32), SudokuError::NullPointer.code());
+            assert_eq!(update_cell(ptr, 9, 9, 2, 0, std::ptr::null_mut(), GRID_CELLS as u32), SudokuError::NullPointer.code());
+            // No room for the dirty cells, the matrix stays as it was
+            assert_eq!(update_cell(ptr, 9, 9, 2, 0, out, 0), SudokuError::InvalidLength.code());
+            assert_eq!(grid_at(ptr), through_ffi(&sample_grid()));
+
+            assert_eq!(check_one_element(ptr, 81), SudokuError::IndexOutOfRange.code());
+            // No other cell of row 0 has candidates, the pair are hidden singles too
+            assert_eq!(check_one_element(ptr, 2), 0);
+            assert_eq!(grid_at(ptr).cell(idx(2)).candidate_highlight[1..3], [PatternList::SINGLES; 2]);
+            assert_eq!(update_matrix(std::ptr::null_mut(), 9, 9), SudokuError::NullPointer.code());
+            free_matrix(ptr, 9, 9);
+        }
+    }
+
+    #[test]
+    fn matrix_functions_reject_bad_arguments() {
+        let null = std::ptr::null_mut();
+
+        unsafe {
+            assert_eq!(erase_matrix(null, 9, 9, 0), SudokuError::NullPointer.code());
+            assert_eq!(set_all_candidates(null, 9, 9), SudokuError::NullPointer.code());
 
-    unsafe { dealloc(ptr as *mut u8, layout) };
+            let ptr = matrix(&sample_grid());
+            assert_eq!(erase_matrix(ptr, 9, 4, 1), SudokuError::DimensionMismatch.code());
+            assert_eq!(set_all_candidates(ptr, 4, 4), SudokuError::DimensionMismatch.code());
+            // Rejected calls leave the matrix alone
+            assert_eq!(grid_at(ptr), through_ffi(&sample_grid()));
+            free_matrix(ptr, 9, 9);
+
+            free_matrix(null, 9, 9);
+        }
+    }
 }
 
 
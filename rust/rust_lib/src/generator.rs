@@ -0,0 +1,218 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Puzzle generator
+Same seed → same puzzle, on every platform (no system random source).
+
+1. Solution : the boxes on the diagonal (1, 5 and 9 of the classic grid)
+   share no row or column, they get shuffled digits, the solver completes
+   the rest. Shuffles without solution are redrawn.
+2. Puzzle   : cells are emptied in shuffled order, a removal is kept only
+   if the puzzle still has exactly one solution (counted by the backend
+   solver::backend_for picks, dancing links for plain grids). Stops at
+   min_givens.
+
+generate / generate_solution build the classic 9 x 9 grid, the _sized
+variants take any supported Geometry.
+*/
+
+use crate::grid::{Geometry, Grid};
+
+use crate::solver::{backend_for, solve};
+
+// Fewest givens a uniquely solvable 9 x 9 puzzle can have
+pub const MIN_GIVENS: usize = 17;
+
+// xorshift64, small and reproducible, not for anything security related
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct XorShift64(u64);
+
+impl XorShift64 {
+    pub fn new(seed: u64) -> XorShift64 {
+        // 0 would only ever produce 0
+        XorShift64(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    // 0..n, n must not be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+// Random complete 9 x 9 grid, all cells are givens
+pub fn generate_solution(rng: &mut XorShift64) -> Grid {
+    generate_solution_sized(Geometry::CLASSIC, rng)
+}
+
+pub fn generate_solution_sized(geometry: Geometry, rng: &mut XorShift64) -> Grid {
+    let (box_rows, box_cols, size) = (geometry.box_rows(), geometry.box_cols(), geometry.size());
+
+    loop {
+        let mut numbers = vec![0; geometry.cells()];
+
+        // Box b of band b and stack b, there are box_cols bands and box_rows stacks
+        for b in 0..box_rows.min(box_cols) {
+            let mut digits: Vec<u8> = geometry.digits().map(|d| d.get()).collect();
+            rng.shuffle(&mut digits);
+
+            for (i, digit) in digits.into_iter().enumerate() {
+                let row = b * box_rows + i / box_cols;
+                let col = b * box_cols + i % box_cols;
+                numbers[row * size + col] = digit;
+            }
+        }
+
+        // Always solvable for 3 x 3 boxes, small boxes can block each other's
+        // columns (4 x 4 grid), then the next shuffle is tried
+        if let Some(solved) = solve(&Grid::with_givens(geometry, &numbers)) {
+            return Grid::with_givens(geometry, &solved.numbers());
+        }
+    }
+}
+
+// Uniquely solvable 9 x 9 puzzle with at least min_givens givens
+pub fn generate(seed: u64, min_givens: usize) -> Grid {
+    generate_sized(Geometry::CLASSIC, seed, min_givens)
+}
+
+// MIN_GIVENS only applies to the classic grid
+pub fn generate_sized(geometry: Geometry, seed: u64, min_givens: usize) -> Grid {
+    let mut rng = XorShift64::new(seed);
+    let mut numbers = generate_solution_sized(geometry, &mut rng).numbers();
+    let min_givens = if geometry == Geometry::CLASSIC { min_givens.max(MIN_GIVENS) } else { min_givens };
+
+    let mut order: Vec<_> = geometry.all_cells().collect();
+    rng.shuffle(&mut order);
+
+    let mut givens = geometry.cells();
+
+    for idx in order {
+        if givens <= min_givens {
+            break;
+        }
+
+        let kept = std::mem::take(&mut numbers[idx.get()]);
+        let puzzle = Grid::with_givens(geometry, &numbers);
+
+        if backend_for(&puzzle).has_unique_solution(&puzzle) {
+            givens -= 1;
+        } else {
+            numbers[idx.get()] = kept;
+        }
+    }
+
+    Grid::with_givens(geometry, &numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    use crate::solver::{is_solved, BACKENDS};
+
+    // Every given is needed, without it the puzzle has more than one solution
+    fn is_minimal(puzzle: &Grid) -> bool {
+        let numbers = puzzle.givens();
+        (0..numbers.len()).filter(|&i| numbers[i] > 0).all(|i| {
+            let mut fewer = numbers.clone();
+            fewer[i] = 0;
+            let fewer = Grid::with_givens(puzzle.geometry(), &fewer);
+            backend_for(&fewer).count_solutions(&fewer, 2) == 2
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn generated_puzzles_have_one_solution(seed in any::<u64>()) {
+            let puzzle = generate(seed, 0);
+            prop_assert_eq!(puzzle.givens(), puzzle.numbers());
+            prop_assert!(is_minimal(&puzzle));
+
+            for backend in BACKENDS {
+                prop_assert!(backend.has_unique_solution(&puzzle), "{}", backend.name());
+            }
+            let solved = solve(&puzzle).unwrap();
+            prop_assert!(is_solved(&solved));
+        }
+
+        #[test]
+        fn generated_small_puzzles_have_one_solution(
+            seed in any::<u64>(),
+            geometry in prop::sample::select(vec![Geometry::MINI, Geometry::SIX]),
+        ) {
+            let puzzle = generate_sized(geometry, seed, 0);
+
+            prop_assert_eq!(puzzle.geometry(), geometry);
+            prop_assert!(backend_for(&puzzle).has_unique_solution(&puzzle));
+            prop_assert!(is_minimal(&puzzle));
+        }
+    }
+
+    #[test]
+    fn generate_sized_covers_every_geometry() {
+        for geometry in Geometry::SUPPORTED {
+            let puzzle = generate_sized(geometry, 7, 0);
+
+            assert_eq!(puzzle.geometry(), geometry);
+            assert_eq!(puzzle.givens(), puzzle.numbers());
+            assert!(puzzle.givens().contains(&0), "{geometry:?}");
+            assert!(backend_for(&puzzle).has_unique_solution(&puzzle), "{geometry:?}");
+
+            let solution = generate_solution_sized(geometry, &mut XorShift64::new(7));
+            assert!(is_solved(&solution), "{geometry:?}");
+            assert_eq!(solution.numbers().iter().filter(|&&n| n == geometry.size() as u8).count(), geometry.size());
+        }
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,452 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+
+/* 
+Difficulty grader
+Grades a puzzle on the DifficultyGrade scale of the puzzle library by the
+hardest step a player needs. The grader solves like a player with full
+candidates : every number the rules of the grid allow (units, neighbour
+rules), narrowed by Constraint::prune of every rule and the sum
+combinations of the killer cages, then step by step
+
+EASY    → naked and hidden singles
+MEDIUM  → locked candidates : a number of one unit confined to the cells it
+          shares with another unit is removed from the rest of the other
+HARD    → naked and hidden pairs and triples of a full size unit
+EXPERT  → one trial : a number that runs into a contradiction with the
+          steps above is removed
+EXTREME → none of the above makes progress, only search finishes it
+
+The easiest step that makes progress is always taken. Puzzles without
+exactly one solution stay UNGRADED. trace lists the steps (technique,
+placed numbers, removed candidates) for hint traces of the command line
+tool.
+*/
+
+use crate::constraint::Rules;
+
+use crate::grid::{CandidateSet, CellIndex, Digit, Geometry, Grid};
+
+use crate::killer::Cage;
+
+use crate::puzzle_library::DifficultyGrade;
+
+use crate::solver::backend_for;
+
+// One step of the grader
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GradeStep {
+    // DifficultyGrade of the technique
+    pub grade: u8,
+    pub technique: &'static str,
+    pub placed: Vec<(CellIndex, Digit)>,
+    pub removed: Vec<(CellIndex, CandidateSet)>,
+}
+
+impl GradeStep {
+    // single : r1c3 = 4
+    // locked candidates : r2c7 -5, r2c8 -5
+    pub fn message(&self, size: usize) -> String {
+        let cell = |idx: CellIndex| format!("r{}c{}", idx.get() / size + 1, idx.get() % size + 1);
+        let placed = self.placed.iter().map(|&(idx, digit)| format!("{} = {}", cell(idx), digit));
+        let removed = self.removed.iter().map(|&(idx, numbers)| {
+            let numbers: Vec<String> = numbers.iter().map(|digit| digit.to_string()).collect();
+            format!("{} -{}", cell(idx), numbers.join(","))
+        });
+        format!("{} : {}", self.technique, placed.chain(removed).collect::<Vec<_>>().join(", "))
+    }
+}
+
+// Candidates of every cell, placed cells keep their number
+#[derive(Clone)]
+struct Candidates {
+    options: Vec<CandidateSet>,
+    placed: Vec<bool>,
+}
+
+struct Grader<'a> {
+    geometry: Geometry,
+    rules: Rules<'a>,
+    cages: &'a [Cage],
+    // Units sharing at least two cells, for locked candidates
+    crossing: Vec<(usize, usize)>,
+}
+
+impl<'a> Grader<'a> {
+    fn new(grid: &'a Grid) -> Grader<'a> {
+        let rules = Rules::new(grid);
+        let units = rules.units();
+
+        let mut crossing = Vec::new();
+        for a in 0..units.len() {
+            for b in (0..units.len()).filter(|&b| b != a) {
+                if units[a].iter().filter(|idx| units[b].contains(idx)).count() >= 2 {
+                    crossing.push((a, b));
+                }
+            }
+        }
+
+        Grader { geometry: grid.geometry(), rules, cages: grid.cages(), crossing }
+    }
+
+    fn start(&self, grid: &Grid) -> Candidates {
+        let g = self.geometry;
+        let mut cands = Candidates { options: vec![g.all_candidates(); g.cells()], placed: vec![false; g.cells()] };
+        for idx in g.all_cells() {
+            if let Some(digit) = grid.value(idx) {
+                self.place(&mut cands, idx, digit);
+            }
+        }
+        cands
+    }
+
+    fn place(&self, cands: &mut Candidates, idx: CellIndex, digit: Digit) {
+        cands.options[idx.get()] = CandidateSet::single(digit);
+        cands.placed[idx.get()] = true;
+        for (other, removed) in self.rules.eliminations(idx, digit) {
+            if !cands.placed[other.get()] {
+                cands.options[other.get()] = cands.options[other.get()].difference(removed);
+            }
+        }
+    }
+
+    // Removes numbers from empty cells, true if one was removed
+    fn remove(&self, cands: &mut Candidates, removals: impl IntoIterator<Item = (CellIndex, CandidateSet)>) -> bool {
+        let mut progress = false;
+        for (idx, removed) in removals {
+            let options = &mut cands.options[idx.get()];
+            if !cands.placed[idx.get()] && !options.intersection(removed).is_empty() {
+                *options = options.difference(removed);
+                progress = true;
+            }
+        }
+        progress
+    }
+
+    // Empty cells of a full size unit
+    fn full_units<'b>(&'b self, cands: &'b Candidates) -> impl Iterator<Item = Vec<CellIndex>> + 'b {
+        self.rules
+            .units()
+            .iter()
+            .filter(|unit| unit.len() == self.geometry.size())
+            .map(|unit| unit.iter().copied().filter(|idx| !cands.placed[idx.get()]).collect())
+    }
+
+    fn is_solved(&self, cands: &Candidates) -> bool {
+        cands.placed.iter().all(|&placed| placed)
+    }
+
+    // A cell without numbers, or a full size unit missing a number everywhere
+    fn is_broken(&self, cands: &Candidates) -> bool {
+        cands.options.iter().any(|options| options.is_empty())
+            || self.rules.units().iter().filter(|unit| unit.len() == self.geometry.size()).any(|unit| {
+                let seen = unit.iter().fold(CandidateSet::EMPTY, |acc, idx| acc.union(cands.options[idx.get()]));
+                seen != self.geometry.all_candidates()
+            })
+    }
+
+    fn singles(&self, cands: &mut Candidates) -> bool {
+        let naked = self.geometry.all_cells().find_map(|idx| {
+            let options = cands.options[idx.get()];
+            options.first().filter(|_| !cands.placed[idx.get()] && options.len() == 1).map(|digit| (idx, digit))
+        });
+        if let Some((idx, digit)) = naked {
+            self.place(cands, idx, digit);
+            return true;
+        }
+
+        for cells in self.full_units(cands).collect::<Vec<_>>() {
+            for digit in self.geometry.digits() {
+                let mut places = cells.iter().filter(|idx| cands.options[idx.get()].contains(digit));
+                if let (Some(&idx), None) = (places.next(), places.next()) {
+                    self.place(cands, idx, digit);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // Constraint::prune of every rule and the killer sum combinations
+    fn prune(&self, cands: &mut Candidates) -> bool {
+        let size = self.geometry.size();
+        let mut removals: Vec<(CellIndex, CandidateSet)> =
+            self.rules.constraints().iter().flat_map(|c| c.prune(self.geometry, &cands.options)).collect();
+
+        for cage in self.cages {
+            let placed = cage
+                .cells()
+                .iter()
+                .filter(|idx| cands.placed[idx.get()])
+                .fold(CandidateSet::EMPTY, |acc, idx| acc.union(cands.options[idx.get()]));
+            let allowed = cage
+                .combinations(size)
+                .into_iter()
+                .filter(|combination| combination.intersection(placed) == placed)
+                .fold(CandidateSet::EMPTY, CandidateSet::union);
+            let excluded = self.geometry.all_candidates().difference(allowed);
+            removals.extend(cage.cells().iter().map(|&idx| (idx, excluded)));
+        }
+
+        self.remove(cands, removals)
+    }
+
+    fn locked(&self, cands: &mut Candidates) -> bool {
+        let units = self.rules.units();
+        for &(a, b) in &self.crossing {
+            for digit in self.geometry.digits() {
+                let single = CandidateSet::single(digit);
+                let mut places = units[a].iter().filter(|idx| !cands.placed[idx.get()] && cands.options[idx.get()].contains(digit));
+                let confined = places.clone().next().is_some() && places.all(|idx| units[b].contains(idx));
+                if confined {
+                    let rest = units[b].iter().filter(|idx| !units[a].contains(idx)).map(|&idx| (idx, single));
+                    if self.remove(cands, rest.collect::<Vec<_>>()) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // Naked and hidden pairs and triples
+    fn subsets(&self, cands: &mut Candidates) -> bool {
+        for cells in self.full_units(cands).collect::<Vec<_>>() {
+            for k in 2..=3 {
+                for group in combinations(cells.len(), k) {
+                    let members: Vec<CellIndex> = group.iter().map(|&i| cells[i]).collect();
+
+                    // k cells holding only k numbers
+                    let numbers = members.iter().fold(CandidateSet::EMPTY, |acc, idx| acc.union(cands.options[idx.get()]));
+                    if numbers.len() == k {
+                        let others = cells.iter().filter(|idx| !members.contains(idx)).map(|&idx| (idx, numbers));
+                        if self.remove(cands, others.collect::<Vec<_>>()) {
+                            return true;
+                        }
+                    }
+                }
+
+                let missing = cells.iter().fold(CandidateSet::EMPTY, |acc, idx| acc.union(cands.options[idx.get()]));
+                let digits: Vec<Digit> = missing.iter().collect();
+                for group in combinations(digits.len(), k) {
+                    // k numbers with only k places
+                    let numbers: CandidateSet = group.iter().map(|&i| digits[i]).collect();
+                    let places: Vec<CellIndex> =
+                        cells.iter().copied().filter(|idx| !cands.options[idx.get()].intersection(numbers).is_empty()).collect();
+                    if places.len() == k {
+                        let others = self.geometry.all_candidates().difference(numbers);
+                        if self.remove(cands, places.into_iter().map(|idx| (idx, others))) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // A number of a cell with two or three options that breaks the grid
+    fn trial(&self, cands: &mut Candidates) -> bool {
+        for idx in self.geometry.all_cells().filter(|idx| !cands.placed[idx.get()] && cands.options[idx.get()].len() <= 3) {
+            for digit in cands.options[idx.get()].iter() {
+                let mut attempt = cands.clone();
+                self.place(&mut attempt, idx, digit);
+                while !self.is_broken(&attempt) && self.step(&mut attempt, DifficultyGrade::HARD).is_some() {}
+
+                if self.is_broken(&attempt) {
+                    return self.remove(cands, [(idx, CandidateSet::single(digit))]);
+                }
+            }
+        }
+        false
+    }
+
+    // Easiest step up to max that makes progress (index into STEPS), None if stuck
+    fn step(&self, cands: &mut Candidates, max: u8) -> Option<usize> {
+        STEPS.iter().position(|&(grade, _, step)| grade <= max && step(self, cands))
+    }
+
+    // Steps up to EXPERT until solved or stuck, true if solved
+    fn run(&self, cands: &mut Candidates) -> (Vec<GradeStep>, bool) {
+        let mut steps = Vec::new();
+
+        while !self.is_solved(cands) {
+            let before = cands.clone();
+            let Some(s) = self.step(cands, DifficultyGrade::EXPERT) else {
+                return (steps, false);
+            };
+
+            let (grade, technique, _) = STEPS[s];
+            let mut step = GradeStep { grade, technique, placed: Vec::new(), removed: Vec::new() };
+            for idx in self.geometry.all_cells() {
+                let (old, new) = (before.options[idx.get()], cands.options[idx.get()]);
+                if cands.placed[idx.get()] && !before.placed[idx.get()] {
+                    step.placed.extend(new.first().map(|digit| (idx, digit)));
+                } else if old != new {
+                    step.removed.push((idx, old.difference(new)));
+                }
+            }
+            steps.push(step);
+        }
+
+        (steps, true)
+    }
+}
+
+// A step narrows the candidates, true if it made progress
+type Step = fn(&Grader, &mut Candidates) -> bool;
+
+// Easiest first, grade and name of the step
+const STEPS: [(u8, &str, Step); 5] = [
+    (DifficultyGrade::EASY, "single", |grader, cands| grader.singles(cands)),
+    (DifficultyGrade::EASY, "rules", |grader, cands| grader.prune(cands)),
+    (DifficultyGrade::MEDIUM, "locked candidates", |grader, cands| grader.locked(cands)),
+    (DifficultyGrade::HARD, "pair / triple", |grader, cands| grader.subsets(cands)),
+    (DifficultyGrade::EXPERT, "trial", |grader, cands| grader.trial(cands)),
+];
+
+// Index sets of k out of n, in lexicographic order
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut all = Vec::new();
+    let mut group: Vec<usize> = (0..k).collect();
+    if k > n {
+        return all;
+    }
+
+    loop {
+        all.push(group.clone());
+        let Some(i) = (0..k).rev().find(|&i| group[i] < n - k + i) else {
+            return all;
+        };
+        group[i] += 1;
+        for j in i + 1..k {
+            group[j] = group[j - 1] + 1;
+        }
+    }
+}
+
+// DifficultyGrade of the hardest step needed, UNGRADED without a unique solution
+pub fn grade(grid: &Grid) -> u8 {
+    if !backend_for(grid).has_unique_solution(grid) {
+        return DifficultyGrade::UNGRADED;
+    }
+
+    let grader = Grader::new(grid);
+    match grader.run(&mut grader.start(grid)) {
+        (steps, true) => steps.iter().map(|step| step.grade).max().unwrap_or(DifficultyGrade::EASY),
+        (_, false) => DifficultyGrade::EXTREME,
+    }
+}
+
+// Steps of grade until the grid is solved or no step makes progress
+pub fn trace(grid: &Grid) -> Vec<GradeStep> {
+    let grader = Grader::new(grid);
+    grader.run(&mut grader.start(grid)).0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::puzzle_string::parse_puzzle_string;
+
+    fn grid(puzzle: &str) -> Grid {
+        Grid::from_givens(&parse_puzzle_string(puzzle).unwrap()).unwrap()
+    }
+
+    // Only steps up to max, true if they solve the grid
+    fn solves_with(grid: &Grid, max: u8) -> bool {
+        let grader = Grader::new(grid);
+        let mut cands = grader.start(grid);
+        while !grader.is_solved(&cands) {
+            if grader.step(&mut cands, max).is_none() {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Project Euler 96 grid 01 is easy, AI Escargot extreme. The middle
+    // grades are pinned by the steps of the grade below getting stuck.
+    const GRADED: [(&str, u8); 5] = [
+        ("003020600900305001001806400008102900700000008006708200002609500800203009005010300", DifficultyGrade::EASY),
+        ("100920000524010000000000070050008102000000000402700090060000000000030945000071006", DifficultyGrade::MEDIUM),
+        ("300200000000107000706030500070009080900020004010800050009040301000702000000008006", DifficultyGrade::HARD),
+        ("1.....7.9.4...72..8.........7..1..6.3.......5.6..4..2.........8..53...7.7.2....46", DifficultyGrade::EXPERT),
+        ("..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97..", DifficultyGrade::EXTREME),
+    ];
+
+    #[test]
+    fn puzzles_get_their_known_grade() {
+        for (puzzle, expected) in GRADED {
+            let grid = grid(puzzle);
+            assert_eq!(grade(&grid), expected, "{puzzle}");
+
+            // The grade is the first level whose steps finish the puzzle
+            assert!(!solves_with(&grid, expected - 1), "{puzzle}");
+            assert_eq!(solves_with(&grid, expected), expected < DifficultyGrade::EXTREME, "{puzzle}");
+        }
+
+        let inkala = grid("8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..");
+        assert_eq!(grade(&inkala), DifficultyGrade::EXTREME);
+    }
+
+    #[test]
+    fn puzzles_without_one_solution_stay_ungraded() {
+        assert_eq!(grade(&Grid::new()), DifficultyGrade::UNGRADED);
+
+        // Two 5s in the first row of an otherwise easy puzzle
+        let mut numbers = parse_puzzle_string(GRADED[0].0).unwrap();
+        numbers[0] = 5;
+        numbers[1] = 5;
+        assert_eq!(grade(&Grid::from_givens(&numbers).unwrap()), DifficultyGrade::UNGRADED);
+
+        let solved = crate::solver::solve(&grid(GRADED[0].0)).unwrap();
+        assert_eq!(grade(&solved), DifficultyGrade::EASY);
+        assert!(trace(&solved).is_empty());
+    }
+
+    #[test]
+    fn trace_places_every_empty_cell() {
+        let grid = grid(GRADED[1].0);
+        let steps = trace(&grid);
+
+        let placed: usize = steps.iter().map(|step| step.placed.len()).sum();
+        assert_eq!(placed, grid.numbers().iter().filter(|&&n| n == 0).count());
+        assert_eq!(steps.iter().map(|step| step.grade).max(), Some(DifficultyGrade::MEDIUM));
+        assert!(steps.iter().any(|step| step.technique == "locked candidates" && !step.removed.is_empty()));
+        assert!(steps[0].message(9).starts_with(steps[0].technique));
+    }
+}
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
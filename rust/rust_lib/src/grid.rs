@@ -0,0 +1,974 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Safe grid layer
+All game logic works on these types, the extern "C" functions only copy the
+Dart buffer (DartToRustElementFFI) into a Grid and back.
+
+Digit        → number 1..=16
+CandidateSet → u16 bitmask, bit 0 = digit 1, len is a popcount
+UnitMask     → digits seen once / more than once in one unit, built with
+               a few bit operations per cell (hidden singles, repeats)
+CellIndex    → 0..cells, row major
+Geometry     → box layout 2x2, 2x3, 3x3, 3x4 or 4x4, with units and peers
+Cell         → one element, same content as DartToRustElementFFI
+Grid         → size x size cells plus row / column / box helpers, killer cages
+               and variant constraints (constraint.rs)
+
+DartToRustElementFFI (pointer API) is always the classic 9 x 9 grid.
+GridElementFFI (session buffers) carries 16 digits per cell and covers every
+size, entries above the grid size stay 0.
+*/
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::error::SudokuError;
+
+use crate::ffi::{DartToRustElementFFI, GridElementFFI, NumStateListIndex};
+
+use crate::killer::Cage;
+
+use crate::constraint::{Boxes, Columns, Constraint, Regions, Rows, Rules, Variant, VariantKind};
+
+use crate::ffi::{CONST_MATRIX_SIZE, CONST_MATRIX_ELEMENTS};
+
+use crate::ffi::{constSelectedNumberListSize,
+constSelectedPatternListSize,
+constRequestedElementHighLightTypeSize,
+constRequestedCandHighLightTypeSize,
+constPatternListOff,
+constSelectedNumStateList,
+constSelectedNumberList,
+constSelectedPatternList,
+constRequestedElementHighLightType,
+constRequestedCandHighLightType};
+
+// Classic 9 x 9 grid, the layout of DartToRustElementFFI
+pub const GRID_SIZE: usize = CONST_MATRIX_SIZE as usize;
+pub const GRID_CELLS: usize = CONST_MATRIX_ELEMENTS as usize;
+pub const BOX_SIZE: usize = 3;
+
+// Largest supported grid, 16 x 16 with 4 x 4 boxes
+pub const MAX_GRID_SIZE: usize = 16;
+pub const MAX_GRID_CELLS: usize = MAX_GRID_SIZE * MAX_GRID_SIZE;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digit(u8);
+
+impl Digit {
+    // 1..=16, use Geometry::digit to check against the grid size
+    pub fn new(value: u8) -> Option<Digit> {
+        if (1..=MAX_GRID_SIZE as u8).contains(&value) {
+            Some(Digit(value))
+        } else {
+            None
+        }
+    }
+
+    // 0 based index for candidate and highlight arrays
+    pub fn from_index(index: usize) -> Option<Digit> {
+        Digit::new(index as u8 + 1)
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    pub fn index(self) -> usize {
+        (self.0 - 1) as usize
+    }
+
+    // 1..=9 of the classic grid
+    pub fn all() -> impl Iterator<Item = Digit> {
+        (1..=constSelectedNumberListSize).map(Digit)
+    }
+}
+
+impl fmt::Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CandidateSet(u16);
+
+impl CandidateSet {
+    pub const EMPTY: CandidateSet = CandidateSet(0);
+    // 1..=9 of the classic grid, Geometry::all_candidates for other sizes
+    pub const ALL: CandidateSet = CandidateSet((1 << constSelectedNumberListSize) - 1);
+
+    // Digits 1..=size
+    pub fn all(size: usize) -> CandidateSet {
+        CandidateSet(((1u32 << size.min(MAX_GRID_SIZE)) - 1) as u16)
+    }
+
+    pub fn from_bits(bits: u16) -> CandidateSet {
+        CandidateSet(bits)
+    }
+
+    pub fn single(digit: Digit) -> CandidateSet {
+        CandidateSet(1 << digit.index())
+    }
+
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(self, digit: Digit) -> bool {
+        self.0 & (1 << digit.index()) != 0
+    }
+
+    pub fn insert(&mut self, digit: Digit) {
+        self.0 |= 1 << digit.index();
+    }
+
+    pub fn remove(&mut self, digit: Digit) {
+        self.0 &= !(1 << digit.index());
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: CandidateSet) -> CandidateSet {
+        CandidateSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: CandidateSet) -> CandidateSet {
+        CandidateSet(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: CandidateSet) -> CandidateSet {
+        CandidateSet(self.0 & !other.0)
+    }
+
+    // Lowest digit of the set
+    pub fn first(self) -> Option<Digit> {
+        if self.0 == 0 {
+            None
+        } else {
+            Digit::from_index(self.0.trailing_zeros() as usize)
+        }
+    }
+
+    // Lowest bit first, one step per digit of the set
+    pub fn iter(self) -> impl Iterator<Item = Digit> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let index = bits.trailing_zeros() as u8;
+            bits &= bits - 1;
+            Some(Digit(index + 1))
+        })
+    }
+
+    // selectedCandList layout, one u8 flag per digit
+    pub fn from_flags(flags: &[u8]) -> CandidateSet {
+        let mut set = CandidateSet::EMPTY;
+        for (i, &flag) in flags.iter().enumerate() {
+            if flag != 0 {
+                if let Some(digit) = Digit::from_index(i) {
+                    set.insert(digit);
+                }
+            }
+        }
+        set
+    }
+
+    // Classic layout, digits above 9 are dropped
+    pub fn to_flags(self) -> [u8; constSelectedNumberListSize as usize] {
+        let mut flags = [0; constSelectedNumberListSize as usize];
+        for digit in self.iter() {
+            if let Some(flag) = flags.get_mut(digit.index()) {
+                *flag = 1;
+            }
+        }
+        flags
+    }
+
+    // One flag per digit 1..=size
+    pub fn to_flag_vec(self, size: usize) -> Vec<u8> {
+        (0..size).map(|i| (self.0 >> i & 1) as u8).collect()
+    }
+}
+
+impl FromIterator<Digit> for CandidateSet {
+    fn from_iter<I: IntoIterator<Item = Digit>>(iter: I) -> Self {
+        let mut set = CandidateSet::EMPTY;
+        for digit in iter {
+            set.insert(digit);
+        }
+        set
+    }
+}
+
+// Digits of one unit : seen in at least one cell, seen in more than one
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UnitMask {
+    pub seen: CandidateSet,
+    pub repeated: CandidateSet,
+}
+
+impl UnitMask {
+    // Numbers or candidates of one more cell
+    pub fn add(&mut self, set: CandidateSet) {
+        self.repeated = self.repeated.union(self.seen.intersection(set));
+        self.seen = self.seen.union(set);
+    }
+
+    // Seen in exactly one cell
+    pub fn once(self) -> CandidateSet {
+        self.seen.difference(self.repeated)
+    }
+}
+
+// Row major position, 0..cells of the grid's Geometry
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellIndex(u8);
+
+// Helpers without geometry are for the classic 9 x 9 grid
+impl CellIndex {
+    pub fn new(index: usize) -> Option<CellIndex> {
+        Geometry::CLASSIC.cell(index)
+    }
+
+    pub fn from_row_col(row: usize, col: usize) -> Option<CellIndex> {
+        Geometry::CLASSIC.cell_at(row, col)
+    }
+
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn row(self) -> usize {
+        Geometry::CLASSIC.row(self)
+    }
+
+    pub fn col(self) -> usize {
+        Geometry::CLASSIC.col(self)
+    }
+
+    pub fn box_index(self) -> usize {
+        Geometry::CLASSIC.box_index(self)
+    }
+
+    pub fn all() -> impl Iterator<Item = CellIndex> {
+        Geometry::CLASSIC.all_cells()
+    }
+
+    // All other cells sharing a row, column or box
+    pub fn peers(self) -> &'static [CellIndex] {
+        Geometry::CLASSIC.peers(self)
+    }
+}
+
+// 27 units of the classic grid : 9 rows, 9 columns, 9 boxes
+pub fn units() -> &'static [Vec<CellIndex>] {
+    Geometry::CLASSIC.units()
+}
+
+/* 
+Box geometry, the grid size is box_rows * box_cols
+2 x 2 →  4 x 4  (kids mode)
+2 x 3 →  6 x 6  (boxes 2 rows high, 3 columns wide)
+3 x 3 →  9 x 9  (classic)
+3 x 4 → 12 x 12
+4 x 4 → 16 x 16 (giant)
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Geometry {
+    box_rows: u8,
+    box_cols: u8,
+}
+
+struct GeometryTables {
+    units: Vec<Vec<CellIndex>>,
+    peers: Vec<Vec<CellIndex>>,
+}
+
+/// cbindgen:ignore
+impl Geometry {
+    pub const MINI: Geometry = Geometry { box_rows: 2, box_cols: 2 };
+    pub const SIX: Geometry = Geometry { box_rows: 2, box_cols: 3 };
+    pub const CLASSIC: Geometry = Geometry { box_rows: 3, box_cols: 3 };
+    pub const TWELVE: Geometry = Geometry { box_rows: 3, box_cols: 4 };
+    pub const GIANT: Geometry = Geometry { box_rows: 4, box_cols: 4 };
+
+    pub const SUPPORTED: [Geometry; 5] =
+        [Geometry::MINI, Geometry::SIX, Geometry::CLASSIC, Geometry::TWELVE, Geometry::GIANT];
+
+    pub fn new(box_rows: usize, box_cols: usize) -> Option<Geometry> {
+        Geometry::SUPPORTED
+            .into_iter()
+            .find(|g| g.box_rows() == box_rows && g.box_cols() == box_cols)
+    }
+
+    // 4, 6, 9, 12 or 16
+    pub fn from_size(size: usize) -> Option<Geometry> {
+        Geometry::SUPPORTED.into_iter().find(|g| g.size() == size)
+    }
+
+    // 16, 36, 81, 144 or 256
+    pub fn from_cells(cells: usize) -> Option<Geometry> {
+        Geometry::SUPPORTED.into_iter().find(|g| g.cells() == cells)
+    }
+
+    pub fn box_rows(self) -> usize {
+        self.box_rows as usize
+    }
+
+    pub fn box_cols(self) -> usize {
+        self.box_cols as usize
+    }
+
+    pub fn size(self) -> usize {
+        self.box_rows() * self.box_cols()
+    }
+
+    pub fn cells(self) -> usize {
+        self.size() * self.size()
+    }
+
+    pub fn cell(self, index: usize) -> Option<CellIndex> {
+        // 256 cells of the giant grid still fit in u8
+        (index < self.cells()).then_some(CellIndex(index as u8))
+    }
+
+    pub fn cell_at(self, row: usize, col: usize) -> Option<CellIndex> {
+        (row < self.size() && col < self.size()).then_some(CellIndex((row * self.size() + col) as u8))
+    }
+
+    pub fn row(self, idx: CellIndex) -> usize {
+        idx.get() / self.size()
+    }
+
+    pub fn col(self, idx: CellIndex) -> usize {
+        idx.get() % self.size()
+    }
+
+    // Boxes are numbered row major, size / box_cols boxes per band
+    pub fn box_index(self, idx: CellIndex) -> usize {
+        (self.row(idx) / self.box_rows()) * (self.size() / self.box_cols()) + self.col(idx) / self.box_cols()
+    }
+
+    pub fn all_cells(self) -> impl Iterator<Item = CellIndex> {
+        (0..self.cells()).map(|i| CellIndex(i as u8))
+    }
+
+    pub fn digit(self, value: u8) -> Option<Digit> {
+        Digit::new(value).filter(|d| d.index() < self.size())
+    }
+
+    pub fn digits(self) -> impl Iterator<Item = Digit> {
+        (1..=self.size() as u8).map(Digit)
+    }
+
+    pub fn all_candidates(self) -> CandidateSet {
+        CandidateSet::all(self.size())
+    }
+
+    // size rows, then size columns, then size boxes
+    pub fn units(self) -> &'static [Vec<CellIndex>] {
+        &self.tables().units
+    }
+
+    // All other cells sharing a row, column or box
+    pub fn peers(self, idx: CellIndex) -> &'static [CellIndex] {
+        &self.tables().peers[idx.get()]
+    }
+
+    fn tables(self) -> &'static GeometryTables {
+        static TABLES: [OnceLock<GeometryTables>; 5] = [const { OnceLock::new() }; 5];
+
+        // Geometry values only exist for SUPPORTED entries
+        let slot = Geometry::SUPPORTED.iter().position(|&g| g == self).unwrap_or(2);
+        TABLES[slot].get_or_init(|| GeometryTables::new(self))
+    }
+}
+
+impl GeometryTables {
+    fn new(g: Geometry) -> GeometryTables {
+        let size = g.size();
+        let mut units: Vec<Vec<CellIndex>> = Vec::with_capacity(3 * size);
+
+        for r in 0..size {
+            units.push(g.all_cells().filter(|&idx| g.row(idx) == r).collect());
+        }
+        for c in 0..size {
+            units.push(g.all_cells().filter(|&idx| g.col(idx) == c).collect());
+        }
+        for b in 0..size {
+            units.push(g.all_cells().filter(|&idx| g.box_index(idx) == b).collect());
+        }
+
+        let peers = g
+            .all_cells()
+            .map(|idx| {
+                g.all_cells()
+                    .filter(|&other| {
+                        other != idx
+                            && (g.row(other) == g.row(idx)
+                                || g.col(other) == g.col(idx)
+                                || g.box_index(other) == g.box_index(idx))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        GeometryTables { units, peers }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub value: Option<Digit>,
+    pub given: bool,
+    pub future_use: bool,
+    pub candidates: CandidateSet,
+    // UI state, kept as in DartToRustElementFFI
+    pub patterns: [u8; constSelectedPatternListSize as usize],
+    pub element_highlight: [u8; constRequestedElementHighLightTypeSize as usize],
+    // One entry per digit, the FFI layout carries the first 9
+    pub candidate_highlight: [u8; MAX_GRID_SIZE],
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            value: None,
+            given: false,
+            future_use: false,
+            candidates: CandidateSet::EMPTY,
+            patterns: constSelectedPatternList,
+            element_highlight: constRequestedElementHighLightType,
+            candidate_highlight: [constPatternListOff; MAX_GRID_SIZE],
+        }
+    }
+}
+
+impl Cell {
+    pub fn given(digit: Digit) -> Cell {
+        Cell { value: Some(digit), given: true, ..Cell::default() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none()
+    }
+
+    // Reset candidates, user patterns and highlights
+    pub fn clear_marks(&mut self) {
+        self.candidates = CandidateSet::EMPTY;
+        self.patterns = constSelectedPatternList;
+        self.element_highlight = constRequestedElementHighLightType;
+        self.candidate_highlight = [constPatternListOff; MAX_GRID_SIZE];
+    }
+
+    // Copy into the FFI layout, row and col of the target are kept
+    pub fn write_ffi(&self, e: &mut DartToRustElementFFI) {
+        e.selectedNum = self.value.map_or(0, Digit::get);
+        e.selectedNumStateList[NumStateListIndex::GIVENS as usize] = self.given as u8;
+        e.selectedNumStateList[NumStateListIndex::FUTUREUSE as usize] = self.future_use as u8;
+        e.selectedCandList = self.candidates.to_flags();
+        e.selectedPatternList = self.patterns;
+        e.requestedElementHighLightType = self.element_highlight;
+        e.requestedCandHighLightType
+            .copy_from_slice(&self.candidate_highlight[..constRequestedCandHighLightTypeSize as usize]);
+    }
+
+    // Counterpart of write_ffi, highlights of digits above 9 are kept
+    pub fn read_ffi(&mut self, e: &DartToRustElementFFI) {
+        let highlight = self.candidate_highlight;
+        *self = Cell::from(e);
+        self.candidate_highlight[constRequestedCandHighLightTypeSize as usize..]
+            .copy_from_slice(&highlight[constRequestedCandHighLightTypeSize as usize..]);
+    }
+}
+
+impl Cell {
+    // Copy into the session layout, digits above the size are 0
+    pub fn write_element(&self, e: &mut GridElementFFI, size: usize) {
+        e.selectedNum = self.value.map_or(0, Digit::get);
+        e.selectedNumStateList[NumStateListIndex::GIVENS as usize] = self.given as u8;
+        e.selectedNumStateList[NumStateListIndex::FUTUREUSE as usize] = self.future_use as u8;
+        e.selectedCandList = [0; MAX_GRID_SIZE];
+        e.selectedCandList[..size].copy_from_slice(&self.candidates.to_flag_vec(size));
+        e.selectedPatternList = self.patterns;
+        e.requestedElementHighLightType = self.element_highlight;
+        e.requestedCandHighLightType = [0; MAX_GRID_SIZE];
+        e.requestedCandHighLightType[..size].copy_from_slice(&self.candidate_highlight[..size]);
+    }
+
+    // Counterpart of write_element, numbers and candidates above the size
+    // are dropped like in From<&DartToRustElementFFI>
+    pub fn read_element(&mut self, e: &GridElementFFI, geometry: Geometry) {
+        let size = geometry.size();
+        let mut candidate_highlight = [0; MAX_GRID_SIZE];
+        candidate_highlight[..size].copy_from_slice(&e.requestedCandHighLightType[..size]);
+        *self = Cell {
+            value: geometry.digit(e.selectedNum),
+            given: e.selectedNumStateList[NumStateListIndex::GIVENS as usize] != 0,
+            future_use: e.selectedNumStateList[NumStateListIndex::FUTUREUSE as usize] != 0,
+            candidates: CandidateSet::from_flags(&e.selectedCandList).intersection(geometry.all_candidates()),
+            patterns: e.selectedPatternList,
+            element_highlight: e.requestedElementHighLightType,
+            candidate_highlight,
+        };
+    }
+}
+
+impl From<&DartToRustElementFFI> for Cell {
+    // Numbers outside 1..=9 are read as empty cell
+    fn from(e: &DartToRustElementFFI) -> Self {
+        // Digits above 9 do not exist in the FFI grid, left at 0 like after check_cell_for_patterns
+        let mut candidate_highlight = [0; MAX_GRID_SIZE];
+        candidate_highlight[..constRequestedCandHighLightTypeSize as usize]
+            .copy_from_slice(&e.requestedCandHighLightType);
+        Cell {
+            value: Geometry::CLASSIC.digit(e.selectedNum),
+            given: e.selectedNumStateList[NumStateListIndex::GIVENS as usize] != 0,
+            future_use: e.selectedNumStateList[NumStateListIndex::FUTUREUSE as usize] != 0,
+            candidates: CandidateSet::from_flags(&e.selectedCandList),
+            patterns: e.selectedPatternList,
+            element_highlight: e.requestedElementHighLightType,
+            candidate_highlight,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    geometry: Geometry,
+    cells: Vec<Cell>,
+    cages: Vec<Cage>,
+    variants: Vec<Variant>,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid::with_geometry(Geometry::CLASSIC)
+    }
+}
+
+impl Grid {
+    // Empty classic 9 x 9 grid
+    pub fn new() -> Grid {
+        Grid::default()
+    }
+
+    pub fn with_geometry(geometry: Geometry) -> Grid {
+        Grid { geometry, cells: vec![Cell::default(); geometry.cells()], cages: Vec::new(), variants: Vec::new() }
+    }
+
+    // 0 = empty, other numbers become givens
+    // The size follows from the length (16, 36, 81, 144, 256), other lengths
+    // are SudokuError::InvalidLength, numbers above the size IndexOutOfRange
+    pub fn from_givens(numbers: &[u8]) -> Result<Grid, SudokuError> {
+        let geometry = Geometry::from_cells(numbers.len()).ok_or(SudokuError::InvalidLength)?;
+        if numbers.iter().any(|&num| num as usize > geometry.size()) {
+            return Err(SudokuError::IndexOutOfRange);
+        }
+        Ok(Grid::with_givens(geometry, numbers))
+    }
+
+    // from_givens for numbers known to fit the geometry (generator)
+    pub(crate) fn with_givens(geometry: Geometry, numbers: &[u8]) -> Grid {
+        let mut grid = Grid::with_geometry(geometry);
+        for (cell, &num) in grid.cells.iter_mut().zip(numbers.iter()) {
+            if let Some(digit) = geometry.digit(num) {
+                *cell = Cell::given(digit);
+            }
+        }
+        grid
+    }
+
+    // None if the slice does not hold exactly 81 elements
+    pub fn from_ffi(slice: &[DartToRustElementFFI]) -> Option<Grid> {
+        if slice.len() != GRID_CELLS {
+            return None;
+        }
+        Some(Grid { cells: slice.iter().map(Cell::from).collect(), ..Grid::new() })
+    }
+
+    // Cell state of a classic grid from 81 elements, cages, variants and
+    // regions are part of the puzzle and kept
+    pub fn read_ffi(&mut self, slice: &[DartToRustElementFFI]) -> Result<(), SudokuError> {
+        if self.geometry != Geometry::CLASSIC {
+            return Err(SudokuError::DimensionMismatch);
+        }
+        if slice.len() != GRID_CELLS {
+            return Err(SudokuError::InvalidLength);
+        }
+
+        for (cell, e) in self.cells.iter_mut().zip(slice) {
+            cell.read_ffi(e);
+        }
+        Ok(())
+    }
+
+    // Only meaningful for the classic 9 x 9 grid
+    pub fn write_ffi(&self, slice: &mut [DartToRustElementFFI]) {
+        for (idx, (cell, e)) in self.cells.iter().zip(slice.iter_mut()).enumerate() {
+            e.row = (idx / GRID_SIZE) as u8;
+            e.col = (idx % GRID_SIZE) as u8;
+            cell.write_ffi(e);
+        }
+    }
+
+    pub fn to_ffi(&self) -> Vec<DartToRustElementFFI> {
+        let mut elements = vec![
+            DartToRustElementFFI {
+                row: 0,
+                col: 0,
+                selectedNum: 0,
+                selectedNumStateList: constSelectedNumStateList,
+                selectedCandList: constSelectedNumberList,
+                selectedPatternList: constSelectedPatternList,
+                requestedElementHighLightType: constRequestedElementHighLightType,
+                requestedCandHighLightType: constRequestedCandHighLightType,
+            };
+            GRID_CELLS
+        ];
+        self.write_ffi(&mut elements);
+        elements
+    }
+
+    // Cell state from size * size session elements, cages, variants and
+    // regions are kept
+    pub fn read_elements(&mut self, slice: &[GridElementFFI]) -> Result<(), SudokuError> {
+        if slice.len() != self.geometry.cells() {
+            return Err(SudokuError::InvalidLength);
+        }
+
+        let geometry = self.geometry;
+        for (cell, e) in self.cells.iter_mut().zip(slice) {
+            cell.read_element(e, geometry);
+        }
+        Ok(())
+    }
+
+    // size * size session elements, row major
+    pub fn write_elements(&self, slice: &mut [GridElementFFI]) -> Result<(), SudokuError> {
+        if slice.len() != self.geometry.cells() {
+            return Err(SudokuError::InvalidLength);
+        }
+
+        let size = self.size();
+        for (idx, (cell, e)) in self.cells.iter().zip(slice.iter_mut()).enumerate() {
+            e.row = (idx / size) as u8;
+            e.col = (idx % size) as u8;
+            cell.write_element(e, size);
+        }
+        Ok(())
+    }
+
+    pub fn to_elements(&self) -> Vec<GridElementFFI> {
+        let mut elements = vec![GridElementFFI::default(); self.geometry.cells()];
+        // Length matches by construction
+        let _ = self.write_elements(&mut elements);
+        elements
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn size(&self) -> usize {
+        self.geometry.size()
+    }
+
+    pub fn cell(&self, idx: CellIndex) -> &Cell {
+        &self.cells[idx.get()]
+    }
+
+    pub fn cell_mut(&mut self, idx: CellIndex) -> &mut Cell {
+        &mut self.cells[idx.get()]
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    pub fn value(&self, idx: CellIndex) -> Option<Digit> {
+        self.cells[idx.get()].value
+    }
+
+    // Placed number, the cell keeps its given flag
+    pub fn set_value(&mut self, idx: CellIndex, value: Option<Digit>) {
+        self.cells[idx.get()].value = value;
+    }
+
+    // One number per cell, 0 = empty
+    pub fn numbers(&self) -> Vec<u8> {
+        self.cells.iter().map(|cell| cell.value.map_or(0, Digit::get)).collect()
+    }
+
+    pub fn givens(&self) -> Vec<u8> {
+        self.cells
+            .iter()
+            .map(|cell| if cell.given { cell.value.map_or(0, Digit::get) } else { 0 })
+            .collect()
+    }
+
+    // erase_givens = false keeps given numbers, candidates and highlights are always reset
+    pub fn erase(&mut self, erase_givens: bool) {
+        for cell in self.cells.iter_mut() {
+            if erase_givens || !cell.given {
+                cell.value = None;
+                cell.given = false;
+                cell.future_use = false;
+            }
+            cell.clear_marks();
+        }
+    }
+
+    // Every candidate in every cell without number
+    pub fn set_all_candidates(&mut self) {
+        for cell in self.cells.iter_mut().filter(|cell| cell.is_empty()) {
+            cell.candidates = self.geometry.all_candidates();
+        }
+    }
+
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    pub fn cage_of(&self, idx: CellIndex) -> Option<&Cage> {
+        self.cages.iter().find(|cage| cage.contains(idx))
+    }
+
+    // Cages must fit the grid and not overlap
+    pub fn add_cage(&mut self, cage: Cage) -> Result<(), SudokuError> {
+        if cage.cells().iter().any(|idx| self.geometry.cell(idx.get()).is_none()) {
+            return Err(SudokuError::IndexOutOfRange);
+        }
+        if cage.cells().iter().any(|&idx| self.cage_of(idx).is_some()) {
+            return Err(SudokuError::InvalidCage);
+        }
+
+        self.cages.push(cage);
+        Ok(())
+    }
+
+    pub fn clear_cages(&mut self) {
+        self.cages.clear();
+    }
+
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    pub fn has_variant(&self, kind: u8) -> bool {
+        self.variants.iter().any(|variant| variant.kind() == kind)
+    }
+
+    // Global kinds at most once, lines any number of times but not twice the same.
+    // Regions and lines must fit the grid.
+    pub fn add_variant(&mut self, variant: Variant) -> Result<(), SudokuError> {
+        if (variant.is_global() && self.has_variant(variant.kind())) || self.variants.contains(&variant) {
+            return Err(SudokuError::DuplicateId);
+        }
+        if let Variant::Regions(regions) = &variant {
+            if regions.map().len() != self.geometry.cells() {
+                return Err(SudokuError::DimensionMismatch);
+            }
+        }
+        if variant.constraint().cells().iter().any(|idx| self.geometry.cell(idx.get()).is_none()) {
+            return Err(SudokuError::IndexOutOfRange);
+        }
+
+        self.variants.push(variant);
+        Ok(())
+    }
+
+    // Every variant of the kind, false if none was set
+    pub fn remove_variant(&mut self, kind: u8) -> bool {
+        let before = self.variants.len();
+        self.variants.retain(|variant| variant.kind() != kind);
+        self.variants.len() != before
+    }
+
+    pub fn clear_variants(&mut self) {
+        self.variants.clear();
+    }
+
+    // Jigsaw regions, None while the boxes apply
+    pub fn regions(&self) -> Option<&Regions> {
+        self.variants.iter().find_map(|variant| match variant {
+            Variant::Regions(regions) => Some(regions),
+            _ => None,
+        })
+    }
+
+    // Replaces the boxes, or the regions set before
+    pub fn set_regions(&mut self, regions: Regions) -> Result<(), SudokuError> {
+        if regions.map().len() != self.geometry.cells() {
+            return Err(SudokuError::DimensionMismatch);
+        }
+        self.remove_variant(VariantKind::REGIONS);
+        self.variants.push(Variant::Regions(regions));
+        Ok(())
+    }
+
+    // Region of idx, the box number without jigsaw regions
+    pub fn region_of(&self, idx: CellIndex) -> usize {
+        self.regions().map_or_else(|| self.geometry.box_index(idx), |regions| regions.region(idx))
+    }
+
+    // Rows, columns, boxes (or irregular regions), variants and cages
+    pub fn constraints(&self) -> Vec<&dyn Constraint> {
+        let mut constraints: Vec<&dyn Constraint> = vec![&Rows, &Columns];
+        if self.regions().is_none() {
+            constraints.push(&Boxes);
+        }
+        constraints.extend(self.variants.iter().map(Variant::constraint));
+        constraints.extend(self.cages.iter().map(|cage| cage as &dyn Constraint));
+        constraints
+    }
+
+    // Only rows, columns and boxes, the static geometry tables apply
+    pub fn is_standard(&self) -> bool {
+        self.variants.is_empty() && self.cages.is_empty()
+    }
+
+    // Numbers idx can not take because of the numbers placed elsewhere
+    // Builds the rules on every call, loops over many cells use Rules directly
+    pub fn peer_values(&self, idx: CellIndex) -> CandidateSet {
+        if self.is_standard() {
+            return self.geometry.peers(idx).iter().filter_map(|&p| self.value(p)).collect();
+        }
+        Rules::new(self).excluded(self, idx)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.cells.iter().all(|cell| cell.value.is_some())
+    }
+
+    // No constraint broken by the placed numbers
+    pub fn is_consistent(&self) -> bool {
+        self.constraints().iter().all(|constraint| !constraint.is_violated(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn from_givens_takes_the_size_from_the_length() {
+        for geometry in Geometry::SUPPORTED {
+            let mut numbers = vec![0; geometry.cells()];
+            numbers[geometry.cells() - 1] = geometry.size() as u8;
+
+            let grid = Grid::from_givens(&numbers).unwrap();
+            assert_eq!(grid.geometry(), geometry);
+            assert_eq!(grid.givens(), numbers);
+
+            numbers[0] = geometry.size() as u8 + 1;
+            assert_eq!(Grid::from_givens(&numbers), Err(SudokuError::IndexOutOfRange));
+        }
+
+        // No silent fallback to the classic grid
+        assert_eq!(Grid::from_givens(&[0; 80]), Err(SudokuError::InvalidLength));
+        assert_eq!(Grid::from_givens(&[]), Err(SudokuError::InvalidLength));
+    }
+
+    // selectedCandList as the engine kept it before the bit masks, one u8 flag per digit
+    fn flags() -> impl Strategy<Value = [u8; 9]> {
+        prop::array::uniform9(0..=1u8)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn candidate_set_matches_the_flag_arrays(a in flags(), b in flags()) {
+            let (set_a, set_b) = (CandidateSet::from_flags(&a), CandidateSet::from_flags(&b));
+            prop_assert_eq!(set_a.to_flags(), a);
+            prop_assert_eq!(set_a.len(), a.iter().filter(|&&flag| flag != 0).count());
+            prop_assert_eq!(set_a.is_empty(), a.iter().all(|&flag| flag == 0));
+
+            // Ascending digits, as the old loops over 0..9 visited them
+            let old: Vec<u8> = (1..=9).filter(|&n| a[n as usize - 1] != 0).collect();
+            prop_assert_eq!(set_a.iter().map(Digit::get).collect::<Vec<_>>(), old.clone());
+            prop_assert_eq!(set_a.first().map(Digit::get), old.first().copied());
+            for digit in Geometry::CLASSIC.digits() {
+                prop_assert_eq!(set_a.contains(digit), a[digit.index()] != 0);
+            }
+
+            let each = |f: fn(u8, u8) -> u8| -> [u8; 9] { std::array::from_fn(|i| f(a[i], b[i])) };
+            prop_assert_eq!(set_a.union(set_b).to_flags(), each(|x, y| x | y));
+            prop_assert_eq!(set_a.intersection(set_b).to_flags(), each(|x, y| x & y));
+            prop_assert_eq!(set_a.difference(set_b).to_flags(), each(|x, y| x & !y));
+
+            let mut edited = set_a;
+            for digit in set_b.iter() {
+                edited.remove(digit);
+            }
+            prop_assert_eq!(edited, set_a.difference(set_b));
+            for digit in set_b.iter() {
+                edited.insert(digit);
+            }
+            prop_assert_eq!(edited, set_a.union(set_b));
+        }
+
+        #[test]
+        fn unit_mask_matches_counting_per_digit(cells in prop::collection::vec(flags(), 1..=9)) {
+            let mut mask = UnitMask::default();
+            for cell in &cells {
+                mask.add(CandidateSet::from_flags(cell));
+            }
+
+            // Cells of the unit holding each digit
+            let count = |i: usize| cells.iter().filter(|cell| cell[i] != 0).count();
+            prop_assert_eq!(mask.seen.to_flags(), std::array::from_fn(|i| (count(i) > 0) as u8));
+            prop_assert_eq!(mask.repeated.to_flags(), std::array::from_fn(|i| (count(i) > 1) as u8));
+            prop_assert_eq!(mask.once().to_flags(), std::array::from_fn(|i| (count(i) == 1) as u8));
+        }
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
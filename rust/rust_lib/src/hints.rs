@@ -0,0 +1,132 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Variant hints
+Candidate eliminations of the variant rules of a grid, each tied to the rule
+that causes it, so the UI can show the step and explain it :
+  r2c5 can not be 8, 9 : numbers rise along the thermometer
+
+Options of a cell are the numbers it can still take : the placed number,
+the candidates set by the player, or every number not placed in a peer
+(units and neighbour rules) when the cell has no candidates. Every Variant
+prunes these options (Constraint::prune), a hint lists what one rule
+removes from one cell.
+Rows, columns, boxes and cages are not reported, the pattern highlights
+of process_data cover them.
+*/
+
+use crate::constraint::{Rules, VariantKind};
+
+use crate::grid::{CandidateSet, CellIndex, Grid};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint {
+    // VariantKind of the rule
+    pub kind: u8,
+    pub cell: CellIndex,
+    pub removed: CandidateSet,
+    // Cells of a line rule, empty for the other variants
+    pub cause: Vec<CellIndex>,
+}
+
+impl Hint {
+    pub fn message(&self, size: usize) -> String {
+        let numbers: Vec<String> = self.removed.iter().map(|digit| digit.to_string()).collect();
+        format!(
+            "r{}c{} can not be {} : {}",
+            self.cell.get() / size + 1,
+            self.cell.get() % size + 1,
+            numbers.join(", "),
+            describe(self.kind)
+        )
+    }
+}
+
+// Rule of a VariantKind in words
+pub fn describe(kind: u8) -> &'static str {
+    match kind {
+        VariantKind::DIAGONAL => "numbers do not repeat on a diagonal",
+        VariantKind::ANTI_KNIGHT => "cells a knight move apart differ",
+        VariantKind::ANTI_KING => "touching cells differ",
+        VariantKind::NON_CONSECUTIVE => "neighbours never differ by 1",
+        VariantKind::REGIONS => "numbers do not repeat in a region",
+        VariantKind::THERMO => "numbers rise along the thermometer",
+        VariantKind::ARROW => "the circle holds the sum of the arrow",
+        VariantKind::LITTLE_KILLER => "the diagonal adds up to the clue",
+        _ => "unknown rule",
+    }
+}
+
+// Numbers every cell can still take
+pub fn cell_options(grid: &Grid) -> Vec<CandidateSet> {
+    let excluded = Rules::new(grid).peer_excluded_all(grid);
+    let all = grid.geometry().all_candidates();
+
+    grid.geometry()
+        .all_cells()
+        .map(|idx| {
+            let cell = grid.cell(idx);
+            match cell.value {
+                Some(digit) => CandidateSet::single(digit),
+                None if !cell.candidates.is_empty() => cell.candidates,
+                None => all.difference(excluded[idx.get()]),
+            }
+        })
+        .collect()
+}
+
+// One hint per variant rule and empty cell that loses options
+pub fn variant_hints(grid: &Grid) -> Vec<Hint> {
+    let geometry = grid.geometry();
+    let options = cell_options(grid);
+    let mut hints = Vec::new();
+
+    for variant in grid.variants() {
+        let constraint = variant.constraint();
+        let mut removed = vec![CandidateSet::EMPTY; geometry.cells()];
+        for (idx, numbers) in constraint.prune(geometry, &options) {
+            removed[idx.get()] = removed[idx.get()].union(numbers);
+        }
+
+        for idx in geometry.all_cells().filter(|&idx| grid.cell(idx).is_empty()) {
+            let removed = removed[idx.get()].intersection(options[idx.get()]);
+            if !removed.is_empty() {
+                hints.push(Hint { kind: variant.kind(), cell: idx, removed, cause: constraint.cells() });
+            }
+        }
+    }
+
+    hints
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,317 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Killer Sudoku cages
+A cage is a group of orthogonally connected cells with a target sum.
+Numbers do not repeat inside a cage and cages never overlap. Cages are
+stored in the Grid next to the cells, so sessions, undo and saves carry
+them. The solver and Grid::is_consistent take them into account.
+
+Sum combinations
+All sets of `cells` different numbers 1..=size that add up to the sum,
+3 cells with sum 7 → {1,2,4} only. An empty cage cell keeps the numbers
+of every combination that still contains all placed numbers of the cage.
+
+The Dart buffer of create_matrix carries no cages, killer games go through
+the session or api functions.
+*/
+
+use crate::error::SudokuError;
+
+use crate::grid::{CandidateSet, CellIndex, Digit, Geometry, Grid};
+
+use crate::constraint::Constraint;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cage {
+    cells: Vec<CellIndex>, // sorted
+    sum: u16,
+}
+
+impl Cage {
+    // Cells must be inside the grid, different and connected, the sum reachable
+    pub fn new(geometry: Geometry, cells: Vec<CellIndex>, sum: u16) -> Result<Cage, SudokuError> {
+        if cells.iter().any(|idx| geometry.cell(idx.get()).is_none()) {
+            return Err(SudokuError::IndexOutOfRange);
+        }
+
+        let mut cells = cells;
+        cells.sort();
+        let count = cells.len();
+        cells.dedup();
+
+        if count != cells.len()
+            || !is_connected(geometry, &cells)
+            || sum_combinations(geometry.size(), cells.len(), sum).is_empty()
+        {
+            return Err(SudokuError::InvalidCage);
+        }
+
+        Ok(Cage { cells, sum })
+    }
+
+    pub fn cells(&self) -> &[CellIndex] {
+        &self.cells
+    }
+
+    pub fn sum(&self) -> u16 {
+        self.sum
+    }
+
+    pub fn contains(&self, idx: CellIndex) -> bool {
+        self.cells.binary_search(&idx).is_ok()
+    }
+
+    pub fn combinations(&self, size: usize) -> Vec<CandidateSet> {
+        sum_combinations(size, self.cells.len(), self.sum)
+    }
+
+    // Numbers placed in the cage, None if one repeats
+    pub fn placed(&self, grid: &Grid) -> Option<CandidateSet> {
+        let mut placed = CandidateSet::EMPTY;
+        for digit in self.cells.iter().filter_map(|&idx| grid.value(idx)) {
+            if placed.contains(digit) {
+                return None;
+            }
+            placed.insert(digit);
+        }
+        Some(placed)
+    }
+
+    // Repeated number, or no sum combination fits the placed numbers
+    pub fn is_violated(&self, grid: &Grid) -> bool {
+        match self.placed(grid) {
+            Some(placed) => !self.combinations(grid.size()).iter().any(|c| c.intersection(placed) == placed),
+            None => true,
+        }
+    }
+
+    // Numbers still possible in the empty cells of the cage
+    pub fn candidates(&self, grid: &Grid) -> CandidateSet {
+        let Some(placed) = self.placed(grid) else {
+            return CandidateSet::EMPTY;
+        };
+
+        self.combinations(grid.size())
+            .into_iter()
+            .filter(|c| c.intersection(placed) == placed)
+            .fold(CandidateSet::EMPTY, |acc, c| acc.union(c.difference(placed)))
+    }
+}
+
+// Cage cells are a unit, the sum is checked by Cage::is_violated
+impl Constraint for Cage {
+    fn units(&self, _geometry: Geometry) -> Vec<Vec<CellIndex>> {
+        vec![self.cells.clone()]
+    }
+
+    fn is_violated(&self, grid: &Grid) -> bool {
+        Cage::is_violated(self, grid)
+    }
+}
+
+// Sets of `cells` different numbers 1..=size adding up to sum
+pub fn sum_combinations(size: usize, cells: usize, sum: u16) -> Vec<CandidateSet> {
+    fn collect(next: u16, size: u16, left: usize, sum: u16, set: CandidateSet, out: &mut Vec<CandidateSet>) {
+        if left == 0 {
+            if sum == 0 {
+                out.push(set);
+            }
+            return;
+        }
+
+        for digit in next..=size {
+            if digit > sum {
+                break;
+            }
+            let mut set = set;
+            set.insert(Digit::new(digit as u8).expect("1..=size"));
+            collect(digit + 1, size, left - 1, sum - digit, set, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    if cells > 0 && cells <= size {
+        collect(1, size as u16, cells, sum, CandidateSet::EMPTY, &mut out);
+    }
+    out
+}
+
+// Orthogonal neighbours only, diagonal contact does not join cells
+pub(crate) fn is_connected(geometry: Geometry, cells: &[CellIndex]) -> bool {
+    let Some(&first) = cells.first() else {
+        return false;
+    };
+
+    let mut reached = vec![first];
+    let mut open = vec![first];
+
+    while let Some(idx) = open.pop() {
+        for &other in cells {
+            let dist = geometry.row(idx).abs_diff(geometry.row(other)) + geometry.col(idx).abs_diff(geometry.col(other));
+            if dist == 1 && !reached.contains(&other) {
+                reached.push(other);
+                open.push(other);
+            }
+        }
+    }
+
+    reached.len() == cells.len()
+}
+
+// Candidates of an empty cell : not placed in a peer and fitting its cage
+pub fn cell_candidates(grid: &Grid, idx: CellIndex) -> CandidateSet {
+    let free = grid.geometry().all_candidates().difference(grid.peer_values(idx));
+
+    match grid.cage_of(idx) {
+        Some(cage) => free.intersection(cage.candidates(grid)),
+        None => free,
+    }
+}
+
+// Drop the candidates no sum combination allows, cells outside cages are kept
+pub fn restrict_to_cages(grid: &mut Grid) {
+    let allowed: Vec<(CellIndex, CandidateSet)> = grid
+        .cages()
+        .iter()
+        .flat_map(|cage| {
+            let candidates = cage.candidates(grid);
+            cage.cells().iter().map(move |&idx| (idx, candidates))
+        })
+        .collect();
+
+    for (idx, candidates) in allowed {
+        let cell = grid.cell_mut(idx);
+        if cell.is_empty() {
+            cell.candidates = cell.candidates.intersection(candidates);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "solver")]
+    use crate::generator::{generate_solution, XorShift64};
+    #[cfg(feature = "solver")]
+    use crate::solver::{count_solutions, is_solved, solve};
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    // Bit n - 1 for every number n
+    fn set(numbers: &[u8]) -> CandidateSet {
+        CandidateSet::from_bits(numbers.iter().fold(0, |bits, &n| bits | (1 << (n - 1))))
+    }
+
+    #[test]
+    fn sum_combinations_lists_every_set() {
+        assert_eq!(sum_combinations(9, 3, 7), vec![set(&[1, 2, 4])]);
+        assert_eq!(sum_combinations(9, 2, 10), vec![set(&[1, 9]), set(&[2, 8]), set(&[3, 7]), set(&[4, 6])]);
+        assert_eq!(sum_combinations(9, 9, 45), vec![set(&[1, 2, 3, 4, 5, 6, 7, 8, 9])]);
+        assert_eq!(sum_combinations(4, 2, 7), vec![set(&[3, 4])]);
+        assert_eq!(sum_combinations(16, 2, 31), vec![set(&[15, 16])]);
+
+        assert!(sum_combinations(9, 3, 5).is_empty());
+        assert!(sum_combinations(9, 2, 18).is_empty());
+        assert!(sum_combinations(9, 10, 55).is_empty());
+        assert!(sum_combinations(9, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn cage_must_be_connected_and_reachable() {
+        let g = Geometry::CLASSIC;
+
+        assert_eq!(Cage::new(g, vec![idx(1), idx(0)], 3).unwrap().cells(), &[idx(0), idx(1)]);
+        // Diagonal contact only
+        assert_eq!(Cage::new(g, vec![idx(0), idx(10)], 3), Err(SudokuError::InvalidCage));
+        assert_eq!(Cage::new(g, vec![idx(0), idx(0)], 3), Err(SudokuError::InvalidCage));
+        assert_eq!(Cage::new(g, vec![idx(0), idx(1)], 2), Err(SudokuError::InvalidCage));
+        assert_eq!(Cage::new(Geometry::MINI, vec![idx(0), idx(20)], 3), Err(SudokuError::IndexOutOfRange));
+    }
+
+    #[test]
+    fn cage_candidates_follow_the_placed_numbers() {
+        let mut grid = Grid::new();
+        grid.add_cage(Cage::new(Geometry::CLASSIC, vec![idx(0), idx(1), idx(2)], 7).unwrap()).unwrap();
+
+        assert_eq!(cell_candidates(&grid, idx(1)), set(&[1, 2, 4]));
+        assert_eq!(cell_candidates(&grid, idx(9)), Geometry::CLASSIC.all_candidates());
+
+        grid.set_value(idx(0), Digit::new(4));
+        assert_eq!(cell_candidates(&grid, idx(1)), set(&[1, 2]));
+
+        grid.set_all_candidates();
+        restrict_to_cages(&mut grid);
+        assert_eq!(grid.cell(idx(2)).candidates, set(&[1, 2]));
+        assert_eq!(grid.cell(idx(9)).candidates.len(), 9);
+
+        // 4 and 2 leave 1, 5 breaks the sum
+        grid.set_value(idx(1), Digit::new(5));
+        assert!(grid.cages()[0].is_violated(&grid));
+        assert!(!grid.is_consistent());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn killer_without_givens_is_solved_by_its_sums() {
+        let solution = generate_solution(&mut XorShift64::new(5));
+        let mut grid = Grid::new();
+
+        // Every row cut into three cages of three cells
+        for start in (0..81).step_by(3) {
+            let cells = vec![idx(start), idx(start + 1), idx(start + 2)];
+            let sum = cells.iter().map(|&i| solution.value(i).unwrap().get() as u16).sum();
+            grid.add_cage(Cage::new(Geometry::CLASSIC, cells, sum).unwrap()).unwrap();
+        }
+
+        let solved = solve(&grid).unwrap();
+        assert!(is_solved(&solved));
+        for cage in grid.cages() {
+            assert_eq!(cage.cells().iter().map(|&i| solved.value(i).unwrap().get() as u16).sum::<u16>(), cage.sum());
+        }
+
+        // Numbers can still swap inside a row of cages
+        assert_eq!(count_solutions(&grid, 2), 2);
+
+        // A wrong sum makes the grid unsolvable
+        let mut broken = Grid::new();
+        broken.add_cage(Cage::new(Geometry::CLASSIC, vec![idx(0), idx(1)], 3).unwrap()).unwrap();
+        broken.add_cage(Cage::new(Geometry::CLASSIC, vec![idx(2), idx(3)], 3).unwrap()).unwrap();
+        assert_eq!(solve(&broken), None);
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
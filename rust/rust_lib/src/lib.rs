@@ -39,14 +39,113 @@
 # -----------------------------------------------------------------------------
 */
 
+// Field and constant names mirror the Dart side (rust_matrix.dart, shared_types.dart)
+#![allow(non_snake_case, non_upper_case_globals)]
+// All extern "C" functions expect the pointer returned by create_matrix
+#![allow(clippy::missing_safety_doc)]
+
 // Import rust files
 mod ffi;
+#[cfg(feature = "patterns")]
 mod process_data;
+#[cfg(feature = "patterns")]
+mod hints;
+#[cfg(feature = "storage")]
 mod store_data;
+#[cfg(feature = "storage")]
+mod puzzle_string;
+#[cfg(feature = "storage")]
+mod puzzle_library;
+#[cfg(feature = "storage")]
+mod binary_format;
+#[cfg(feature = "storage")]
+mod share_code;
+#[cfg(all(test, feature = "storage"))]
+mod test_util;
+
+/* 
+Public Rust surface, also built as rlib for other crates :
+  rust_backend = { path = "../rust_lib", default-features = false, features = ["solver"] }
+grid     → always, safe Grid / Cell types
+killer   → always, cages of Killer Sudoku (stored in the Grid)
+constraint → always, Constraint trait and Sudoku variants (stored in the Grid)
+multi_grid → always, Samurai boards of grids sharing cells
+error    → always, SudokuError with stable codes
+solver   → feature "solver" (solver, dlx, generator)
+patterns → feature "patterns" (highlights, conflicts, variant hints)
+session  → feature "session" (GameSession, handle registry)
+api      → feature "api" (flutter_rust_bridge), not a default feature
+storage  → feature "storage" (JSON, binary, puzzle strings, library)
+grader   → features "solver" and "storage" (difficulty grades)
+batch    → feature "batch" (solve_batch, grade_batch on a rayon pool)
+cli      → feature "cli", the sudoku-cli binary (src/bin/sudoku_cli.rs)
+*/
+
+// Safe Rust API, the FFI functions are thin adapters on top
+pub mod grid;
+
+// Error codes shared by the Rust API and the FFI functions
+pub mod error;
+
+// Killer Sudoku cages and sum combinations
+pub mod killer;
+
+// Constraint trait, standard units and variant rules
+pub mod constraint;
+
+// Samurai and other grids sharing cells
+pub mod multi_grid;
+
+#[cfg(feature = "solver")]
+pub mod solver;
+
+// Dancing Links backend of the solver
+#[cfg(feature = "solver")]
+pub mod dlx;
+
+// Difficulty grades from the steps a player needs
+#[cfg(all(feature = "solver", feature = "storage"))]
+pub mod grader;
+
+// Many puzzle strings solved or graded across threads
+#[cfg(feature = "batch")]
+pub mod batch;
+
+// Opaque handle API, replaces the pointer + rows / cols functions
+#[cfg(feature = "session")]
+pub mod session;
+
+// Puzzle generator, seeded
+#[cfg(feature = "solver")]
+pub mod generator;
+
+// flutter_rust_bridge entry points, Dart bindings are generated from here
+#[cfg(feature = "api")]
+pub mod api;
+
+#[cfg(feature = "patterns")]
+pub mod patterns {
+    pub use crate::process_data::{cell_peers, check_cell_for_patterns, check_grid_for_patterns, find_conflicts, remove_from_peers, update_cell_at, update_patterns_at};
+    pub use crate::hints::{cell_options, describe, variant_hints, Hint};
+}
+
+#[cfg(feature = "storage")]
+pub mod storage {
+    pub use crate::store_data::{save_grid, load_grid, AppData, SaveFormat};
+    pub use crate::store_data::{save_multi_grid, load_multi_grid, MultiGridData};
+    pub use crate::store_data::{parse_sdk, format_sdk, parse_sdm, format_sdm};
+    pub use crate::puzzle_string::{parse_puzzle_string, format_puzzle_string, to_puzzle_string};
+    pub use crate::puzzle_string::{parse_pencilmark_string, to_pencilmark_string, PencilMarkFormat};
+    pub use crate::binary_format::{encode_binary, decode_binary, is_binary, BINARY_VERSION};
+    pub use crate::share_code::{encode_game_state, decode_game_state, SHARE_CODE_VERSION};
+    pub use crate::puzzle_library::{PuzzleLibrary, PuzzleEntry, DifficultyGrade};
+}
 
 // export FFI interface file
 pub use ffi::*;
-
+#[cfg(feature = "patterns")]
+pub use process_data::{check_all_elements, check_one_element};
+pub use error::{last_error_message, clear_last_error, error_code_message};
 
 // Copyright (c) 2025, MIRKO THULKE. All rights reserved.
 // See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,359 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+
+/* 
+Multi-grid Sudoku (Samurai and other overlapping layouts)
+Several grids of the same size placed on one board, grids overlap in shared
+cells. A shared cell is one board position : every grid covering it holds a
+copy of the cell, all changes go through MultiGrid so the copies stay equal.
+The solver places a shared cell once, in every grid at the same time.
+
+Board positions are row * cols + col of the board, the grid at origin
+(row, col) covers the board cells row..row + size, col..col + size.
+Samurai : five 9 x 9 grids on a 21 x 21 board, the middle grid shares one
+corner box with each of the four outer grids.
+
+One character per 3 x 3 box, + = box shared by two grids :
+
+  0 0 0 . 1 1 1
+  0 0 0 . 1 1 1
+  0 0 + 2 + 1 1
+  . . 2 2 2 . .
+  3 3 + 2 + 4 4
+  3 3 3 . 4 4 4
+  3 3 3 . 4 4 4
+
+Cages and variants belong to one grid (add_cage / add_variant with the grid
+number). The create_matrix buffer holds one grid, the FFI function takes the
+numbers of the whole board instead.
+*/
+
+use crate::error::SudokuError;
+
+use crate::grid::{Cell, CellIndex, Digit, Geometry, Grid};
+
+use crate::killer::Cage;
+
+use crate::constraint::Variant;
+
+#[cfg(feature = "solver")]
+use std::os::raw::c_int;
+
+#[cfg(feature = "solver")]
+use crate::error::ffi_value;
+
+#[cfg(feature = "solver")]
+use crate::solver::count_and_solve_multi;
+
+// Rows (= columns) of the Samurai board
+pub const SAMURAI_BOARD_SIZE: u8 = 21;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiGrid {
+    geometry: Geometry,
+    origins: Vec<(usize, usize)>,
+    grids: Vec<Grid>,
+    rows: usize,
+    cols: usize,
+    board: Vec<Vec<(usize, CellIndex)>>, // per board position, (grid, cell) covering it
+}
+
+impl MultiGrid {
+    /// cbindgen:ignore
+    pub const SAMURAI: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+    // origins : top left board cell of every grid. Grids must be different and
+    // joined by shared cells, the board has at most 255 rows and columns.
+    pub fn new(geometry: Geometry, origins: &[(usize, usize)]) -> Result<MultiGrid, SudokuError> {
+        let size = geometry.size();
+        let rows = origins.iter().map(|&(row, _)| row + size).max().ok_or(SudokuError::InvalidLayout)?;
+        let cols = origins.iter().map(|&(_, col)| col + size).max().ok_or(SudokuError::InvalidLayout)?;
+
+        if rows > u8::MAX as usize || cols > u8::MAX as usize {
+            return Err(SudokuError::DimensionMismatch);
+        }
+
+        let mut board = vec![Vec::new(); rows * cols];
+        for (g, &(row, col)) in origins.iter().enumerate() {
+            for idx in geometry.all_cells() {
+                board[(row + geometry.row(idx)) * cols + col + geometry.col(idx)].push((g, idx));
+            }
+        }
+
+        let multi = MultiGrid {
+            geometry,
+            origins: origins.to_vec(),
+            grids: vec![Grid::with_geometry(geometry); origins.len()],
+            rows,
+            cols,
+            board,
+        };
+
+        if !multi.is_joined() {
+            return Err(SudokuError::InvalidLayout);
+        }
+
+        Ok(multi)
+    }
+
+    // Empty Samurai, five classic grids
+    pub fn samurai() -> MultiGrid {
+        MultiGrid::new(Geometry::CLASSIC, &MultiGrid::SAMURAI).expect("samurai layout")
+    }
+
+    // Board numbers row by row (0 = empty, positions outside the grids are
+    // ignored), other numbers become givens
+    pub fn from_numbers(geometry: Geometry, origins: &[(usize, usize)], numbers: &[u8]) -> Result<MultiGrid, SudokuError> {
+        let mut multi = MultiGrid::new(geometry, origins)?;
+        if numbers.len() != multi.rows * multi.cols {
+            return Err(SudokuError::InvalidLength);
+        }
+
+        for pos in multi.positions().collect::<Vec<_>>() {
+            if let Some(digit) = geometry.digit(numbers[pos]) {
+                multi.set_cell(pos, Cell::given(digit));
+            }
+        }
+        Ok(multi)
+    }
+
+    // Every grid reaches every other one through shared cells, no grid twice
+    fn is_joined(&self) -> bool {
+        let mut reached = vec![false; self.grids.len()];
+        let mut open = vec![0];
+        reached[0] = true;
+
+        while let Some(g) = open.pop() {
+            for cells in self.board.iter().filter(|cells| cells.iter().any(|&(other, _)| other == g)) {
+                for &(other, _) in cells {
+                    if !reached[other] {
+                        reached[other] = true;
+                        open.push(other);
+                    }
+                }
+            }
+        }
+
+        let mut origins = self.origins.clone();
+        origins.sort();
+        origins.dedup();
+
+        origins.len() == self.origins.len() && reached.iter().all(|&r| r)
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn origins(&self) -> &[(usize, usize)] {
+        &self.origins
+    }
+
+    pub fn grids(&self) -> &[Grid] {
+        &self.grids
+    }
+
+    pub fn grid(&self, g: usize) -> Option<&Grid> {
+        self.grids.get(g)
+    }
+
+    // Board rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    // Board columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    // Board positions covered by at least one grid
+    pub fn positions(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.board.len()).filter(|&pos| !self.board[pos].is_empty())
+    }
+
+    // (grid, cell) pairs of a board position, empty outside the grids
+    pub fn cells_at(&self, pos: usize) -> &[(usize, CellIndex)] {
+        self.board.get(pos).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn position(&self, g: usize, idx: CellIndex) -> usize {
+        let (row, col) = self.origins[g];
+        (row + self.geometry.row(idx)) * self.cols + col + self.geometry.col(idx)
+    }
+
+    pub fn is_shared(&self, pos: usize) -> bool {
+        self.cells_at(pos).len() > 1
+    }
+
+    pub fn cell(&self, pos: usize) -> Option<&Cell> {
+        self.cells_at(pos).first().map(|&(g, idx)| self.grids[g].cell(idx))
+    }
+
+    pub fn value(&self, pos: usize) -> Option<Digit> {
+        self.cell(pos).and_then(|cell| cell.value)
+    }
+
+    // Written to every grid covering pos, false outside the grids
+    pub fn set_cell(&mut self, pos: usize, cell: Cell) -> bool {
+        let Some(cells) = self.board.get(pos) else {
+            return false;
+        };
+        for &(g, idx) in cells {
+            *self.grids[g].cell_mut(idx) = cell;
+        }
+        !cells.is_empty()
+    }
+
+    pub fn set_value(&mut self, pos: usize, value: Option<Digit>) -> bool {
+        let Some(cells) = self.board.get(pos) else {
+            return false;
+        };
+        for &(g, idx) in cells {
+            self.grids[g].set_value(idx, value);
+        }
+        !cells.is_empty()
+    }
+
+    pub fn add_cage(&mut self, g: usize, cage: Cage) -> Result<(), SudokuError> {
+        self.grids.get_mut(g).ok_or(SudokuError::IndexOutOfRange)?.add_cage(cage)
+    }
+
+    pub fn add_variant(&mut self, g: usize, variant: Variant) -> Result<(), SudokuError> {
+        self.grids.get_mut(g).ok_or(SudokuError::IndexOutOfRange)?.add_variant(variant)
+    }
+
+    // One number per board position, 0 = empty or outside the grids
+    pub fn numbers(&self) -> Vec<u8> {
+        (0..self.board.len()).map(|pos| self.value(pos).map_or(0, Digit::get)).collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.grids.iter().all(Grid::is_complete)
+    }
+
+    // No grid breaks a constraint and the copies of shared cells agree
+    pub fn is_consistent(&self) -> bool {
+        let same = |cells: &[(usize, CellIndex)]| {
+            cells.windows(2).all(|pair| self.grids[pair[0].0].value(pair[0].1) == self.grids[pair[1].0].value(pair[1].1))
+        };
+        self.grids.iter().all(Grid::is_consistent) && self.board.iter().all(|cells| same(cells))
+    }
+}
+
+// numbers : SAMURAI_BOARD_SIZE x SAMURAI_BOARD_SIZE board numbers row by row,
+// 0 = empty, positions outside the five grids are ignored.
+// Solved in place when there is a solution.
+// Returns 0, 1 or 2 (= more than one) solutions, negative on error.
+#[cfg(feature = "solver")]
+#[no_mangle]
+pub unsafe extern "C" fn samurai_solve(numbers: *mut u8, len: u32) -> c_int {
+    ffi_value(|| {
+        if numbers.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+        let board = std::slice::from_raw_parts_mut(numbers, len as usize);
+
+        let multi = MultiGrid::from_numbers(Geometry::CLASSIC, &MultiGrid::SAMURAI, board)?;
+        let (count, solved) = count_and_solve_multi(&multi, 2);
+        if let Some(solved) = solved {
+            board.copy_from_slice(&solved.numbers());
+        }
+
+        Ok(count as c_int)
+    })
+}
+
+// Both tests need a feature, the bare engine has nothing to check here
+#[cfg(all(test, any(feature = "solver", feature = "storage")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "storage")]
+    use crate::store_data::MultiGridData;
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn samurai_json_stores_shared_cells_once() {
+        let mut samurai = MultiGrid::samurai();
+        // Bottom right box of grid 0 is the top left box of grid 2
+        let shared = samurai.position(0, idx(80));
+        assert_eq!(shared, samurai.position(2, idx(20)));
+        samurai.set_cell(shared, Cell::given(Digit::new(7).unwrap()));
+
+        let json = serde_json::to_string(&MultiGridData::from_multi_grid(&samurai)).unwrap();
+        assert_eq!(json.matches("selectedNum\"").count(), 369);
+
+        let loaded = serde_json::from_str::<MultiGridData>(&json).unwrap().to_multi_grid().unwrap();
+        assert_eq!(loaded.numbers(), samurai.numbers());
+        assert_eq!(loaded.grid(2).unwrap().value(idx(20)), Digit::new(7));
+
+        let data: MultiGridData = serde_json::from_str(&json.replace("\"row\":0,\"col\":1,", "\"row\":0,\"col\":0,")).unwrap();
+        assert_eq!(data.to_multi_grid().unwrap_err(), SudokuError::CorruptData);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn solve_multi_treats_shared_cells_as_one() {
+        let mut samurai = MultiGrid::samurai();
+        let seven = Digit::new(7);
+        // 7s in grid 2 leave only its top left cell (2,2) for the 7 of its first box
+        for i in [3, 15, 36, 64] {
+            samurai.set_value(samurai.position(2, idx(i)), seven);
+        }
+
+        let (count, solved) = count_and_solve_multi(&samurai, 2);
+        assert!(count > 0);
+        let solved = solved.unwrap();
+        assert!(solved.is_complete() && solved.is_consistent());
+        assert_eq!(solved.grid(0).unwrap().value(idx(80)), seven);
+        for pos in solved.positions().filter(|&pos| solved.is_shared(pos)) {
+            let cells = solved.cells_at(pos);
+            assert!(cells.iter().all(|&(g, i)| solved.grid(g).unwrap().value(i) == solved.value(pos)));
+        }
+
+        // A 7 in the last row of grid 0 is fine for grid 0 alone, but its
+        // bottom right cell is the same variable as (2,2) of grid 2
+        samurai.set_value(samurai.position(0, idx(72)), seven);
+        for grid in samurai.grids() {
+            assert!(crate::solver::solve(grid).is_some());
+        }
+        assert_eq!(count_and_solve_multi(&samurai, 2), (0, None));
+        assert_eq!(crate::solver::solve_multi(&samurai), None);
+    }
+}
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -28,76 +28,316 @@ Contact: MIRKO THULKE (for permission requests)
 */
 
 // process_data.rs
+use std::os::raw::c_int;
+
+use crate::error::{ffi_status, SudokuError};
+
 use crate::ffi::{DartToRustElementFFI};
 
-use crate::ffi::{CONST_MATRIX_ELEMENTS};
+use crate::ffi::{read_grid, write_grid, CONST_MATRIX_ELEMENTS, CONST_MATRIX_SIZE};
 
 use crate::ffi::{PatternList};
 
+use crate::grid::{CandidateSet, Cell, CellIndex, Digit, Grid, UnitMask, MAX_GRID_SIZE};
+
+use crate::constraint::Rules;
+
 #[no_mangle]
-pub unsafe extern "C" fn check_all_elements(ptr: *mut DartToRustElementFFI, len: usize) {
+pub unsafe extern "C" fn check_all_elements(ptr: *mut DartToRustElementFFI, len: usize) -> c_int {
+    ffi_status(|| {
+        check_elements(ptr, len)?;
+        Ok(())
+    })
+}
+
+
+// Highlights of idx and of the cells sharing a unit with it, incremental
+// like update_patterns_at, the rest of the buffer must be up to date
+#[no_mangle]
+pub unsafe extern "C" fn check_one_element(ptr: *mut DartToRustElementFFI, idx: usize) -> c_int {
+    ffi_status(|| {
+        let mut grid = read_grid(ptr, CONST_MATRIX_SIZE, CONST_MATRIX_SIZE)?;
+        let idx = grid.geometry().cell(idx).ok_or(SudokuError::IndexOutOfRange)?;
+        update_patterns_at(&mut grid, &[idx]);
+        write_grid(&grid, ptr)
+    })
+}
+
+pub(crate) unsafe fn check_elements(ptr: *mut DartToRustElementFFI, len: usize) -> Result<(), SudokuError> {
     if ptr.is_null() {
-        return;
+        return Err(SudokuError::NullPointer);
+    }
+
+    if len > CONST_MATRIX_ELEMENTS as usize {
+        return Err(SudokuError::InvalidLength);
     }
 
     for i in 0..len {
-        let cell = &mut *ptr.add(i);
+        check_element(&mut *ptr.add(i));
+    }
+
+    Ok(())
+}
+
+// FFI adapter, patterns are checked on the safe Cell
+fn check_element(element: &mut DartToRustElementFFI) {
+    let mut cell = Cell::from(&*element);
+    check_cell_for_patterns(&mut cell);
+    cell.write_ffi(element);
+}
+
+// Cell patterns, then hidden singles of every full size unit of the
+// grid constraints (rows, columns, boxes or jigsaw regions, diagonals)
+pub fn check_grid_for_patterns(grid: &mut Grid) {
+    for cell in grid.cells_mut() {
         check_cell_for_patterns(cell);
     }
+
+    let rules = Rules::new(grid);
+    let singles: Vec<(CellIndex, Digit)> = rules
+        .units()
+        .iter()
+        .filter(|unit| unit.len() == grid.size())
+        .flat_map(|unit| hidden_singles(grid, unit))
+        .collect();
+
+    for (idx, digit) in singles {
+        grid.cell_mut(idx).candidate_highlight[digit.index()] = PatternList::SINGLES;
+    }
 }
 
+// Incremental check_grid_for_patterns after the cells in `changed` were
+// edited, the other cells must hold the result of a full pass. Hidden
+// singles only depend on the cells of their unit, so only the cells sharing
+// a unit with a changed cell are checked again. Returns the changed cells
+// and every cell whose highlight changed, sorted, for Dart to redraw.
+pub fn update_patterns_at(grid: &mut Grid, changed: &[CellIndex]) -> Vec<CellIndex> {
+    let geometry = grid.geometry();
+    let mut dirty = vec![false; geometry.cells()];
+    let mut checked = vec![false; geometry.cells()];
 
-#[no_mangle]
-pub unsafe extern "C" fn check_one_element(ptr: *mut DartToRustElementFFI, idx: usize) {
-    if ptr.is_null() {
-        return;
+    let crossing: Vec<Vec<CellIndex>> = {
+        let rules = Rules::new(grid);
+        let full = |u: &usize| rules.units()[*u].len() == geometry.size();
+
+        for &idx in changed {
+            dirty[idx.get()] = true;
+            checked[idx.get()] = true;
+            for &u in rules.cell_units(idx).iter().filter(|u| full(u)) {
+                for p in &rules.units()[u] {
+                    checked[p.get()] = true;
+                }
+            }
+        }
+
+        // Every unit a checked cell can be a hidden single in
+        let mut crossing = vec![false; rules.units().len()];
+        for idx in geometry.all_cells().filter(|idx| checked[idx.get()]) {
+            for &u in rules.cell_units(idx).iter().filter(|u| full(u)) {
+                crossing[u] = true;
+            }
+        }
+        (0..crossing.len()).filter(|&u| crossing[u]).map(|u| rules.units()[u].clone()).collect()
+    };
+
+    let cells: Vec<CellIndex> = geometry.all_cells().filter(|idx| checked[idx.get()]).collect();
+    let before: Vec<[u8; MAX_GRID_SIZE]> = cells.iter().map(|&idx| grid.cell(idx).candidate_highlight).collect();
+
+    for &idx in &cells {
+        check_cell_for_patterns(grid.cell_mut(idx));
+    }
+
+    let singles: Vec<(CellIndex, Digit)> = crossing.iter().flat_map(|unit| hidden_singles(grid, unit)).collect();
+    for (idx, digit) in singles.into_iter().filter(|(idx, _)| checked[idx.get()]) {
+        grid.cell_mut(idx).candidate_highlight[digit.index()] = PatternList::SINGLES;
+    }
+
+    for (&idx, highlight) in cells.iter().zip(before) {
+        if grid.cell(idx).candidate_highlight != highlight {
+            dirty[idx.get()] = true;
+        }
+    }
+
+    geometry.all_cells().filter(|idx| dirty[idx.get()]).collect()
+}
+
+// Number placed in idx : taken out of the candidates of every cell it
+// excludes (units, cages, neighbour rules). Returns the cells that lost a
+// candidate, sorted.
+pub fn remove_from_peers(grid: &mut Grid, idx: CellIndex) -> Vec<CellIndex> {
+    let Some(digit) = grid.value(idx) else {
+        return Vec::new();
+    };
+
+    let mut changed = Vec::new();
+    for (other, excluded) in Rules::new(grid).eliminations(idx, digit) {
+        let candidates = &mut grid.cell_mut(other).candidates;
+        if !candidates.intersection(excluded).is_empty() {
+            *candidates = candidates.difference(excluded);
+            changed.push(other);
+        }
     }
 
-    assert!(idx < CONST_MATRIX_ELEMENTS as usize);
+    changed.sort();
+    changed.dedup();
+    changed
+}
 
-    let cell = &mut *ptr.add(idx);
-    check_cell_for_patterns(cell);
+// One edited cell : the highlights around idx are updated. With
+// remove_candidates its number first leaves the peers' candidates and the
+// highlights around those peers are updated too, the pencil marks of the
+// player are left alone otherwise. Returns the cells to redraw
+// (update_patterns_at).
+pub fn update_cell_at(grid: &mut Grid, idx: CellIndex, remove_candidates: bool) -> Vec<CellIndex> {
+    let mut changed = if remove_candidates { remove_from_peers(grid, idx) } else { Vec::new() };
+    changed.push(idx);
+    update_patterns_at(grid, &changed)
 }
 
-unsafe fn check_cell_for_patterns(cell: &mut DartToRustElementFFI) {
+// Candidates with one place left in the unit : seen once in the candidate
+// masks of the empty cells and not placed
+fn hidden_singles(grid: &Grid, unit: &[CellIndex]) -> Vec<(CellIndex, Digit)> {
+    let mut placed = CandidateSet::EMPTY;
+    let mut candidates = UnitMask::default();
+    for cell in unit.iter().map(|&idx| grid.cell(idx)) {
+        match cell.value {
+            Some(digit) => placed.insert(digit),
+            None => candidates.add(cell.candidates),
+        }
+    }
+
+    candidates
+        .once()
+        .difference(placed)
+        .iter()
+        .filter_map(|digit| {
+            unit.iter()
+                .find(|&&idx| grid.cell(idx).is_empty() && grid.cell(idx).candidates.contains(digit))
+                .map(|&idx| (idx, digit))
+        })
+        .collect()
+}
+
+// Cells whose number breaks a constraint of the grid
+pub fn find_conflicts(grid: &Grid) -> Vec<CellIndex> {
+    Rules::new(grid).conflicts(grid)
+}
+
+// Cells sharing a row, column, box or jigsaw region, diagonal or cage with idx
+pub fn cell_peers(grid: &Grid, idx: CellIndex) -> Vec<CellIndex> {
+    if grid.is_standard() {
+        return grid.geometry().peers(idx).to_vec();
+    }
+    Rules::new(grid).unit_peers(idx)
+}
+
+pub fn check_cell_for_patterns(cell: &mut Cell) {
     // Reset highlights first
-    for hl in cell.requestedCandHighLightType.iter_mut() {
-        *hl = 0;
-    }
-
-    if cell.selectedNum == 0 {
-        let selected_count = cell
-            .selectedCandList
-            .iter()
-            .filter(|&&x| x != 0)
-            .count();
-
-        if selected_count == 2 {
-            for (cand, hl) in cell
-                .selectedCandList
-                .iter()
-                .zip(cell.requestedCandHighLightType.iter_mut())
-            {
-                if *cand != 0 {
-                    *hl = PatternList::PAIRS as u8;
+    cell.candidate_highlight = [0; MAX_GRID_SIZE];
+
+    if cell.is_empty() {
+        let pattern = match cell.candidates.len() {
+            2 => PatternList::PAIRS,
+            1 => PatternList::SINGLES,
+            _ => return,
+        };
+
+        for digit in cell.candidates.iter() {
+            cell.candidate_highlight[digit.index()] = pattern;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    // Cell, then a number to place (0 clears the cell) or a candidate to toggle
+    #[derive(Clone, Debug)]
+    enum Edit {
+        Value(usize, u8),
+        Candidate(usize, u8),
+    }
+
+    fn edits() -> impl Strategy<Value = Vec<Edit>> {
+        let edit = prop_oneof![
+            (0..81usize, 0..=9u8).prop_map(|(i, n)| Edit::Value(i, n)),
+            (0..81usize, 1..=9u8).prop_map(|(i, n)| Edit::Candidate(i, n)),
+        ];
+        prop::collection::vec(edit, 1..40)
+    }
+
+    // Returns the edited cell
+    fn apply(grid: &mut Grid, edit: &Edit) -> CellIndex {
+        match *edit {
+            Edit::Value(i, n) => {
+                grid.set_value(idx(i), Digit::new(n));
+                idx(i)
+            }
+            Edit::Candidate(i, n) => {
+                let digit = Digit::new(n).unwrap();
+                let candidates = &mut grid.cell_mut(idx(i)).candidates;
+                if candidates.contains(digit) {
+                    candidates.remove(digit);
+                } else {
+                    candidates.insert(digit);
                 }
+                idx(i)
             }
         }
-        else if selected_count == 1 {
-            for (cand, hl) in cell
-                .selectedCandList
-                .iter()
-                .zip(cell.requestedCandHighLightType.iter_mut())
-            {
-                if *cand != 0 {
-                    *hl = PatternList::SINGLES as u8;
+    }
+
+    #[test]
+    fn placed_number_leaves_the_peers() {
+        let mut grid = Grid::new();
+        grid.set_all_candidates();
+        grid.set_value(idx(0), Digit::new(5));
+
+        let changed = remove_from_peers(&mut grid, idx(0));
+        assert_eq!(changed, cell_peers(&grid, idx(0)));
+        assert!(changed.iter().all(|&i| grid.cell(i).candidates.len() == 8));
+        // Nothing left to take away the second time
+        assert!(remove_from_peers(&mut grid, idx(0)).is_empty());
+        assert!(remove_from_peers(&mut grid, idx(1)).is_empty());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn incremental_updates_match_the_full_pass(edits in edits(), remove_candidates in any::<bool>()) {
+            let mut grid = Grid::new();
+            grid.set_all_candidates();
+            check_grid_for_patterns(&mut grid);
+            let mut full = grid.clone();
+
+            for edit in &edits {
+                let before = grid.clone();
+                let idx = apply(&mut grid, edit);
+                let dirty = update_cell_at(&mut grid, idx, remove_candidates);
+
+                apply(&mut full, edit);
+                if remove_candidates {
+                    remove_from_peers(&mut full, idx);
                 }
-            }
+                check_grid_for_patterns(&mut full);
+                prop_assert_eq!(&grid, &full, "{:?}", edit);
 
+                // Every cell that changed is reported
+                for i in grid.geometry().all_cells().filter(|&i| grid.cell(i) != before.cell(i)) {
+                    prop_assert!(dirty.contains(&i), "{:?} {:?}", edit, i);
+                }
+            }
         }
     }
 }
 
-
 // Copyright (c) 2025, MIRKO THULKE. All rights reserved.
 // See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,447 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Puzzle library
+Holds many puzzles (givens, id, difficulty grade, tags, solved flag,
+best time) and persists them as JSON next to the game save.
+The FFI functions take the library path, load the JSON, run the query and
+write the JSON back if something changed. The active grid stays in the
+buffer allocated by create_matrix.
+*/
+
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::Path;
+use std::os::raw::{c_char, c_int};
+
+use crate::ffi::{DartToRustElementFFI};
+
+use crate::ffi::{c_char_to_str, read_grid, write_grid};
+
+use crate::error::{ffi_string, ffi_value, SudokuError};
+
+use crate::ffi::{MAX_UINT};
+
+use crate::grid::Grid;
+
+use crate::puzzle_string::{parse_puzzle_string, format_puzzle_string, to_puzzle_string};
+
+use crate::store_data::{parse_sdm};
+
+pub struct DifficultyGrade;
+
+impl DifficultyGrade {
+    pub const UNGRADED: u8 = 0;
+    pub const EASY: u8 = 1;
+    pub const MEDIUM: u8 = 2;
+    pub const HARD: u8 = 3;
+    pub const EXPERT: u8 = 4;
+    pub const EXTREME: u8 = 5;
+    // Query wildcard, matches every grade
+    pub const ANY: u8 = MAX_UINT;
+
+    pub fn name(grade: u8) -> &'static str {
+        match grade {
+            DifficultyGrade::EASY => "easy",
+            DifficultyGrade::MEDIUM => "medium",
+            DifficultyGrade::HARD => "hard",
+            DifficultyGrade::EXPERT => "expert",
+            DifficultyGrade::EXTREME => "extreme",
+            DifficultyGrade::ANY => "any",
+            _ => "ungraded",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PuzzleEntry {
+    pub id: String,
+    pub givens: String,
+    pub difficulty: u8,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub solved: bool,
+    #[serde(default)]
+    pub best_time_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PuzzleLibrary {
+    pub puzzles: Vec<PuzzleEntry>,
+}
+
+fn matches_difficulty(entry: &PuzzleEntry, difficulty: u8) -> bool {
+    difficulty == DifficultyGrade::ANY || entry.difficulty == difficulty
+}
+
+impl PuzzleLibrary {
+    pub fn load(path: &str) -> Result<Self, SudokuError> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|_| SudokuError::ParseFailed),
+            Err(_) => Err(SudokuError::ReadFailed),
+        }
+    }
+
+    // Like load, but a missing file is an empty library
+    pub fn load_or_default(path: &str) -> Result<Self, SudokuError> {
+        if Path::new(path).exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), SudokuError> {
+        match serde_json::to_string(self) {
+            Ok(json) => fs::write(path, json).map_err(|_| SudokuError::WriteFailed),
+            Err(_) => Err(SudokuError::SerializeFailed),
+        }
+    }
+
+    // Add a puzzle, returns its index
+    pub fn add(&mut self, entry: PuzzleEntry) -> Result<usize, SudokuError> {
+        // givens must be a valid puzzle string
+        parse_puzzle_string(&entry.givens)?;
+
+        if self.puzzles.iter().any(|p| p.id == entry.id) {
+            return Err(SudokuError::DuplicateId);
+        }
+
+        self.puzzles.push(entry);
+        Ok(self.puzzles.len() - 1)
+    }
+
+    // Add all puzzles of a .sdm pack, ids are <prefix>-<line number>
+    pub fn import_sdm(&mut self, content: &str, id_prefix: &str, difficulty: u8) -> Result<usize, SudokuError> {
+        let puzzles = parse_sdm(content)?;
+
+        for (i, numbers) in puzzles.iter().enumerate() {
+            self.add(PuzzleEntry {
+                id: format!("{}-{:04}", id_prefix, i + 1),
+                givens: format_puzzle_string(numbers)?,
+                difficulty,
+                tags: Vec::new(),
+                solved: false,
+                best_time_ms: None,
+            })?;
+        }
+
+        Ok(puzzles.len())
+    }
+
+    pub fn find(&self, id: &str) -> Option<usize> {
+        self.puzzles.iter().position(|p| p.id == id)
+    }
+
+    pub fn count(&self, difficulty: u8, unsolved_only: bool) -> usize {
+        self.puzzles
+            .iter()
+            .filter(|p| matches_difficulty(p, difficulty) && !(unsolved_only && p.solved))
+            .count()
+    }
+
+    // First unsolved puzzle of the grade, in library order
+    pub fn next_unsolved(&self, difficulty: u8) -> Option<usize> {
+        self.puzzles
+            .iter()
+            .position(|p| !p.solved && matches_difficulty(p, difficulty))
+    }
+
+    // Flag as solved and keep the fastest time
+    pub fn mark_solved(&mut self, index: usize, time_ms: u64) -> Result<(), SudokuError> {
+        let entry = self.puzzles.get_mut(index).ok_or(SudokuError::IndexOutOfRange)?;
+
+        entry.solved = true;
+        entry.best_time_ms = Some(entry.best_time_ms.map_or(time_ms, |best| best.min(time_ms)));
+
+        Ok(())
+    }
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl PuzzleEntry {
+    pub fn to_grid(&self) -> Result<Grid, SudokuError> {
+        Grid::from_givens(&parse_puzzle_string(&self.givens)?)
+    }
+}
+
+// Add the givens of the current grid, returns the new index
+#[no_mangle]
+pub unsafe extern "C" fn library_add_puzzle(
+    library_path: *const c_char,
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    id: *const c_char,
+    difficulty: u8,
+    tags: *const c_char, // comma separated, may be null
+) -> c_int {
+    ffi_value(|| {
+        let grid = read_grid(ptr, rows, cols)?;
+        let path_str = c_char_to_str(library_path)?;
+        let id_str = c_char_to_str(id)?;
+        let tags_list = if tags.is_null() { Vec::new() } else { split_tags(c_char_to_str(tags)?) };
+
+        let mut library = PuzzleLibrary::load_or_default(path_str)?;
+        let index = library.add(PuzzleEntry {
+            id: id_str.to_string(),
+            givens: to_puzzle_string(&grid, true),
+            difficulty,
+            tags: tags_list,
+            solved: false,
+            best_time_ms: None,
+        })?;
+        library.save(path_str)?;
+
+        Ok(index as c_int)
+    })
+}
+
+// Add a .sdm puzzle pack, returns the number of added puzzles
+#[no_mangle]
+pub unsafe extern "C" fn library_import_sdm(
+    library_path: *const c_char,
+    sdm_path: *const c_char,
+    id_prefix: *const c_char,
+    difficulty: u8,
+) -> c_int {
+    ffi_value(|| {
+        let path_str = c_char_to_str(library_path)?;
+        let sdm_str = c_char_to_str(sdm_path)?;
+        let prefix_str = c_char_to_str(id_prefix)?;
+
+        let content = fs::read_to_string(sdm_str).map_err(|_| SudokuError::ReadFailed)?;
+
+        let mut library = PuzzleLibrary::load_or_default(path_str)?;
+        let added = library.import_sdm(&content, prefix_str, difficulty)?;
+        library.save(path_str)?;
+
+        Ok(added as c_int)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn library_count(
+    library_path: *const c_char,
+    difficulty: u8,
+    unsolved_only: u8,
+) -> c_int {
+    ffi_value(|| {
+        let library = PuzzleLibrary::load(c_char_to_str(library_path)?)?;
+        Ok(library.count(difficulty, unsolved_only > 0) as c_int)
+    })
+}
+
+// Load the next unsolved puzzle of the grade, returns its index or SudokuError::NotFound
+#[no_mangle]
+pub unsafe extern "C" fn library_next_unsolved(
+    library_path: *const c_char,
+    difficulty: u8,
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+) -> c_int {
+    ffi_value(|| {
+        read_grid(ptr, rows, cols)?;
+        let library = PuzzleLibrary::load(c_char_to_str(library_path)?)?;
+        let index = library.next_unsolved(difficulty).ok_or(SudokuError::NotFound)?;
+
+        write_grid(&library.puzzles[index].to_grid()?, ptr)?;
+
+        Ok(index as c_int)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn library_load_puzzle(
+    library_path: *const c_char,
+    index: u32,
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+) -> c_int {
+    ffi_value(|| {
+        read_grid(ptr, rows, cols)?;
+        let library = PuzzleLibrary::load(c_char_to_str(library_path)?)?;
+        let entry = library.puzzles.get(index as usize).ok_or(SudokuError::IndexOutOfRange)?;
+
+        write_grid(&entry.to_grid()?, ptr)?;
+
+        Ok(0)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn library_mark_solved(
+    library_path: *const c_char,
+    index: u32,
+    time_ms: u64,
+) -> c_int {
+    ffi_value(|| {
+        let path_str = c_char_to_str(library_path)?;
+
+        let mut library = PuzzleLibrary::load(path_str)?;
+        library.mark_solved(index as usize, time_ms)?;
+        library.save(path_str)?;
+
+        Ok(0)
+    })
+}
+
+// Index of the puzzle with the given id, or SudokuError::NotFound
+#[no_mangle]
+pub unsafe extern "C" fn library_find(library_path: *const c_char, id: *const c_char) -> c_int {
+    ffi_value(|| {
+        let library = PuzzleLibrary::load(c_char_to_str(library_path)?)?;
+        let index = library.find(c_char_to_str(id)?).ok_or(SudokuError::NotFound)?;
+
+        Ok(index as c_int)
+    })
+}
+
+// Id of the puzzle at index, release with free_rust_string
+#[no_mangle]
+pub unsafe extern "C" fn library_puzzle_id(library_path: *const c_char, index: u32) -> *mut c_char {
+    ffi_string(|| {
+        let library = PuzzleLibrary::load(c_char_to_str(library_path)?)?;
+        let entry = library.puzzles.get(index as usize).ok_or(SudokuError::IndexOutOfRange)?;
+
+        Ok(entry.id.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::temp_path;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn entry(id: &str, difficulty: u8) -> PuzzleEntry {
+        PuzzleEntry {
+            id: id.to_string(),
+            givens: PUZZLE.replacen('5', ".", id.len() % 2),
+            difficulty,
+            tags: Vec::new(),
+            solved: false,
+            best_time_ms: None,
+        }
+    }
+
+    fn library() -> PuzzleLibrary {
+        let mut library = PuzzleLibrary::default();
+        for (id, difficulty) in [("e1", DifficultyGrade::EASY), ("h1", DifficultyGrade::HARD), ("e2", DifficultyGrade::EASY), ("h22", DifficultyGrade::HARD)] {
+            library.add(entry(id, difficulty)).unwrap();
+        }
+        library
+    }
+
+    #[test]
+    fn next_unsolved_of_difficulty() {
+        let mut library = library();
+
+        assert_eq!(library.next_unsolved(DifficultyGrade::HARD), Some(1));
+        library.mark_solved(1, 90_000).unwrap();
+        assert_eq!(library.next_unsolved(DifficultyGrade::HARD), Some(3));
+        library.mark_solved(3, 60_000).unwrap();
+        assert_eq!(library.next_unsolved(DifficultyGrade::HARD), None);
+
+        assert_eq!(library.next_unsolved(DifficultyGrade::EASY), Some(0));
+        assert_eq!(library.next_unsolved(DifficultyGrade::ANY), Some(0));
+        assert_eq!(library.next_unsolved(DifficultyGrade::EXPERT), None);
+
+        assert_eq!(library.count(DifficultyGrade::HARD, false), 2);
+        assert_eq!(library.count(DifficultyGrade::HARD, true), 0);
+        assert_eq!(library.count(DifficultyGrade::ANY, true), 2);
+    }
+
+    #[test]
+    fn mark_solved_keeps_the_best_time() {
+        let mut library = library();
+        library.mark_solved(0, 50_000).unwrap();
+        library.mark_solved(0, 70_000).unwrap();
+
+        assert_eq!(library.puzzles[0].best_time_ms, Some(50_000));
+        assert_eq!(library.mark_solved(9, 1), Err(SudokuError::IndexOutOfRange));
+    }
+
+    #[test]
+    fn add_checks_id_and_givens() {
+        let mut library = library();
+
+        assert_eq!(library.add(entry("e1", DifficultyGrade::EASY)), Err(SudokuError::DuplicateId));
+        let mut broken = entry("x", DifficultyGrade::EASY);
+        broken.givens.pop();
+        assert_eq!(library.add(broken), Err(SudokuError::InvalidLength));
+
+        let added = library.import_sdm(&format!("# pack\n{PUZZLE}\n\n{PUZZLE}\n"), "pack", DifficultyGrade::MEDIUM).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(library.find("pack-0002"), Some(5));
+    }
+
+    #[test]
+    fn library_next_unsolved_loads_the_puzzle() {
+        let path = temp_path("library.json");
+        library().save(path.to_str().unwrap()).unwrap();
+        let mut elements = Grid::new().to_ffi();
+
+        unsafe {
+            assert_eq!(library_next_unsolved(path.as_ptr(), DifficultyGrade::HARD, elements.as_mut_ptr(), 9, 9), 1);
+            assert_eq!(library_mark_solved(path.as_ptr(), 1, 1000), 0);
+            assert_eq!(library_next_unsolved(path.as_ptr(), DifficultyGrade::HARD, elements.as_mut_ptr(), 9, 9), 3);
+            assert_eq!(library_mark_solved(path.as_ptr(), 3, 1000), 0);
+            assert_eq!(
+                library_next_unsolved(path.as_ptr(), DifficultyGrade::HARD, elements.as_mut_ptr(), 9, 9),
+                SudokuError::NotFound.code()
+            );
+        }
+
+        let loaded = PuzzleLibrary::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(to_puzzle_string(&Grid::from_ffi(&elements).unwrap(), true), loaded.puzzles[3].givens);
+        assert_eq!(loaded.count(DifficultyGrade::ANY, true), 2);
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,510 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Standard puzzle string format
+One character per cell, row by row, starting top left.
+'1'..'9' → given number
+'A'..'G' → given number 10..16 (12 x 12 and 16 x 16 grids, any case)
+'.' or '0' → empty cell
+Whitespace (line breaks from copy & paste) is ignored.
+The length selects the grid size : 16, 36, 81, 144 or 256 characters.
+
+Example :
+53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79
+
+Pencil-mark formats (candidates per cell, mapped to selectedCandList)
+FLAT : 729 characters, 9 per cell. Position i holds digit i+1 if it is a
+       candidate, '.' or '0' otherwise. A cell with a single digit is read as
+       placed number, so a cell with a single candidate can not be written :
+       the export fails with SudokuError::NotRepresentable, GRID keeps it.
+GRID : HoDoKu style grid, one token per cell, frame characters are ignored.
+       5      → placed number
+       128    → candidates 1, 2 and 8
+       [7]    → bracketed token, always candidates (single candidate 7)
+       []     → empty cell without candidates
+Placed numbers are not flagged as givens, the formats carry no given state.
+Pencil-mark formats are only defined for the classic 9 x 9 grid.
+*/
+
+use std::os::raw::{c_char, c_int};
+
+use crate::ffi::{DartToRustElementFFI};
+
+use crate::ffi::{c_char_to_str, read_grid, write_grid};
+
+use crate::error::{ffi_status, ffi_string, SudokuError};
+
+use crate::ffi::{CONST_MATRIX_ELEMENTS};
+
+use crate::ffi::{constSelectedNumberListSize};
+
+use crate::grid::{CandidateSet, CellIndex, Digit, Geometry, Grid, GRID_SIZE, BOX_SIZE};
+
+pub const PUZZLE_STRING_EMPTY: char = '.';
+
+pub const PENCILMARK_FLAT_LEN: usize =
+    (CONST_MATRIX_ELEMENTS as usize) * (constSelectedNumberListSize as usize);
+
+pub struct PencilMarkFormat;
+
+impl PencilMarkFormat {
+    pub const FLAT: u8 = 0;
+    pub const GRID: u8 = 1;
+}
+
+// Parse a puzzle string into one number per cell (0 = empty cell),
+// Geometry::from_cells of the length gives the grid size
+pub fn parse_puzzle_string(puzzle: &str) -> Result<Vec<u8>, SudokuError> {
+    let mut numbers = Vec::with_capacity(CONST_MATRIX_ELEMENTS as usize);
+
+    for ch in puzzle.chars().filter(|ch| !ch.is_whitespace()) {
+        let num = match ch {
+            '.' | '0' => 0,
+            '1'..='9' => ch as u8 - b'0',
+            'A'..='G' => ch as u8 - b'A' + 10,
+            'a'..='g' => ch as u8 - b'a' + 10,
+            _ => return Err(SudokuError::InvalidCharacter),
+        };
+        numbers.push(num);
+    }
+
+    let geometry = Geometry::from_cells(numbers.len()).ok_or(SudokuError::InvalidLength)?;
+
+    // 'A' is no number of a 9 x 9 grid
+    if numbers.iter().any(|&num| num as usize > geometry.size()) {
+        return Err(SudokuError::InvalidCharacter);
+    }
+
+    Ok(numbers)
+}
+
+// Format numbers (0 = empty cell) as puzzle string, the length selects the
+// grid size like for parse_puzzle_string and every number must fit it
+pub fn format_puzzle_string(numbers: &[u8]) -> Result<String, SudokuError> {
+    let geometry = Geometry::from_cells(numbers.len()).ok_or(SudokuError::InvalidLength)?;
+
+    if numbers.iter().any(|&num| num as usize > geometry.size()) {
+        return Err(SudokuError::IndexOutOfRange);
+    }
+
+    Ok(numbers.iter().map(|&num| puzzle_char(num)).collect())
+}
+
+// Export the grid as puzzle string, either givens only or all placed numbers
+pub fn to_puzzle_string(grid: &Grid, givens_only: bool) -> String {
+    let numbers = if givens_only { grid.givens() } else { grid.numbers() };
+
+    // Digits of a grid are 1..=16, always a character
+    numbers.into_iter().map(puzzle_char).collect()
+}
+
+// 0..=16, checked by the callers
+fn puzzle_char(num: u8) -> char {
+    match num {
+        0 => PUZZLE_STRING_EMPTY,
+        1..=9 => (b'0' + num) as char,
+        _ => (b'A' + num - 10) as char,
+    }
+}
+
+// Parse a pencil-mark string, FLAT or GRID format is detected automatically
+pub fn parse_pencilmark_string(pencilmarks: &str) -> Result<Grid, SudokuError> {
+    let compact: Vec<char> = pencilmarks.chars().filter(|ch| !ch.is_whitespace()).collect();
+
+    let tokens = if compact.len() == PENCILMARK_FLAT_LEN
+        && compact.iter().all(|ch| ch.is_ascii_digit() || *ch == '.')
+    {
+        parse_pencilmark_flat(&compact)?
+    } else {
+        parse_pencilmark_grid(pencilmarks)?
+    };
+
+    if tokens.len() != CONST_MATRIX_ELEMENTS as usize {
+        return Err(SudokuError::InvalidLength);
+    }
+
+    let mut grid = Grid::new();
+    for (idx, (digits, bracketed)) in CellIndex::all().zip(tokens) {
+        let cell = grid.cell_mut(idx);
+
+        // A single unbracketed digit is a placed number
+        if digits.len() == 1 && !bracketed {
+            cell.value = digits.first();
+        } else {
+            cell.candidates = digits;
+        }
+    }
+
+    Ok(grid)
+}
+
+// Digits of each cell and whether the token was bracketed
+type PencilMarkToken = (CandidateSet, bool);
+
+fn parse_pencilmark_flat(chars: &[char]) -> Result<Vec<PencilMarkToken>, SudokuError> {
+    let mut tokens = Vec::with_capacity(CONST_MATRIX_ELEMENTS as usize);
+
+    for block in chars.chunks(constSelectedNumberListSize as usize) {
+        let mut digits = CandidateSet::EMPTY;
+
+        for (i, ch) in block.iter().enumerate() {
+            match ch {
+                '.' | '0' => {}
+                // position must match the digit
+                '1'..='9' if (*ch as u8 - b'1') as usize == i => digits.insert(digit_of(*ch)),
+                _ => return Err(SudokuError::InvalidCharacter),
+            }
+        }
+
+        tokens.push((digits, false));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_pencilmark_grid(pencilmarks: &str) -> Result<Vec<PencilMarkToken>, SudokuError> {
+    let mut tokens = Vec::with_capacity(CONST_MATRIX_ELEMENTS as usize);
+
+    for line in pencilmarks.lines() {
+        // Skip frame lines like .-----+-----. or :-----+-----:
+        if line.chars().all(|ch| ch.is_whitespace() || "-=+.:'*|".contains(ch)) {
+            continue;
+        }
+
+        let mut chars = line.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '[' | '(' | '{' => {
+                    let close = match ch {
+                        '[' => ']',
+                        '(' => ')',
+                        _ => '}',
+                    };
+                    let mut digits = CandidateSet::EMPTY;
+
+                    loop {
+                        match chars.next() {
+                            Some(c) if c == close => break,
+                            Some(c @ '1'..='9') => digits.insert(digit_of(c)),
+                            Some(c) if c.is_whitespace() || c == ',' => {}
+                            _ => return Err(SudokuError::InvalidCharacter), // or missing bracket
+                        }
+                    }
+
+                    tokens.push((digits, true));
+                }
+                '1'..='9' => {
+                    let mut digits = CandidateSet::single(digit_of(ch));
+
+                    while let Some(&c @ '1'..='9') = chars.peek() {
+                        digits.insert(digit_of(c));
+                        chars.next();
+                    }
+
+                    tokens.push((digits, false));
+                }
+                c if c.is_whitespace() || c == '|' || c == ':' => {}
+                _ => return Err(SudokuError::InvalidCharacter),
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Only called for '1'..='9'
+fn digit_of(ch: char) -> Digit {
+    Digit::new(ch as u8 - b'0').expect("digit character")
+}
+
+pub fn to_pencilmark_string(grid: &Grid, format: u8) -> Result<String, SudokuError> {
+    if grid.geometry() != Geometry::CLASSIC {
+        return Err(SudokuError::DimensionMismatch);
+    }
+
+    match format {
+        PencilMarkFormat::FLAT => to_pencilmark_flat(grid),
+        PencilMarkFormat::GRID => Ok(to_pencilmark_grid(grid)),
+        _ => Err(SudokuError::UnknownFormat),
+    }
+}
+
+fn to_pencilmark_flat(grid: &Grid) -> Result<String, SudokuError> {
+    // Would be read back as placed number
+    if grid.cells().iter().any(|cell| cell.is_empty() && cell.candidates.len() == 1) {
+        return Err(SudokuError::NotRepresentable);
+    }
+
+    let mut out = String::with_capacity(PENCILMARK_FLAT_LEN);
+
+    for cell in grid.cells() {
+        let digits = match cell.value {
+            Some(digit) => CandidateSet::single(digit),
+            None => cell.candidates,
+        };
+
+        for digit in Digit::all() {
+            out.push(if digits.contains(digit) { (b'0' + digit.get()) as char } else { PUZZLE_STRING_EMPTY });
+        }
+    }
+
+    Ok(out)
+}
+
+fn pencilmark_grid_token(grid: &Grid, idx: CellIndex) -> String {
+    let cell = grid.cell(idx);
+
+    if let Some(digit) = cell.value {
+        return digit.to_string();
+    }
+
+    let digits: String = cell.candidates.iter().map(|d| d.to_string()).collect();
+
+    // A single unbracketed digit would be read back as placed number
+    if digits.len() < 2 {
+        format!("[{}]", digits)
+    } else {
+        digits
+    }
+}
+
+fn to_pencilmark_grid(grid: &Grid) -> String {
+    let tokens: Vec<String> = CellIndex::all().map(|idx| pencilmark_grid_token(grid, idx)).collect();
+
+    // Width of each column is the longest token of that column
+    let widths: Vec<usize> = (0..GRID_SIZE)
+        .map(|c| (0..GRID_SIZE).map(|r| tokens[r * GRID_SIZE + c].len()).max().unwrap_or(1))
+        .collect();
+
+    let frame = |left: char, mid: char, right: char| -> String {
+        let mut line = String::new();
+        line.push(left);
+        for b in 0..BOX_SIZE {
+            let box_width: usize =
+                widths[b * BOX_SIZE..(b + 1) * BOX_SIZE].iter().map(|w| w + 1).sum::<usize>() + 1;
+            line.push_str(&"-".repeat(box_width));
+            line.push(if b < BOX_SIZE - 1 { mid } else { right });
+        }
+        line.push('\n');
+        line
+    };
+
+    let mut out = frame('.', '+', '.');
+
+    for r in 0..GRID_SIZE {
+        out.push('|');
+        for c in 0..GRID_SIZE {
+            out.push(' ');
+            out.push_str(&format!("{:<width$}", tokens[r * GRID_SIZE + c], width = widths[c]));
+            if c % BOX_SIZE == BOX_SIZE - 1 {
+                out.push_str(" |");
+            }
+        }
+        out.push('\n');
+
+        if r % BOX_SIZE == BOX_SIZE - 1 && r < GRID_SIZE - 1 {
+            out.push_str(&frame(':', '+', ':'));
+        }
+    }
+
+    out.push_str(&frame('\'', '+', '\''));
+    out
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn import_puzzle_string(
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    puzzle: *const c_char,
+) -> c_int {
+    ffi_status(|| {
+        read_grid(ptr, rows, cols)?;
+        let numbers = parse_puzzle_string(c_char_to_str(puzzle)?)?;
+        write_grid(&Grid::from_givens(&numbers)?, ptr)?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn export_puzzle_string(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    givens_only: u8,
+) -> *mut c_char {
+    // Caller releases the string with free_rust_string
+    ffi_string(|| Ok(to_puzzle_string(&read_grid(ptr, rows, cols)?, givens_only > 0)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn import_pencilmark_string(
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    pencilmarks: *const c_char,
+) -> c_int {
+    ffi_status(|| {
+        read_grid(ptr, rows, cols)?;
+        let grid = parse_pencilmark_string(c_char_to_str(pencilmarks)?)?;
+        write_grid(&grid, ptr)?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn export_pencilmark_string(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    format: u8,
+) -> *mut c_char {
+    // Caller releases the string with free_rust_string
+    ffi_string(|| to_pencilmark_string(&read_grid(ptr, rows, cols)?, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn puzzle_string_round_trip() {
+        let numbers = parse_puzzle_string(PUZZLE).unwrap();
+        assert_eq!(numbers.len(), 81);
+        assert_eq!(&numbers[..5], &[5, 3, 0, 0, 7]);
+        assert_eq!(format_puzzle_string(&numbers).unwrap(), PUZZLE);
+
+        let grid = Grid::from_givens(&numbers).unwrap();
+        assert_eq!(to_puzzle_string(&grid, true), PUZZLE);
+    }
+
+    #[test]
+    fn puzzle_string_ignores_whitespace_and_reads_zero_as_empty() {
+        let zeros = PUZZLE.replace('.', "0");
+        let spaced: String = zeros.as_bytes().chunks(9).map(|row| format!("{}\n", std::str::from_utf8(row).unwrap())).collect();
+        assert_eq!(parse_puzzle_string(&spaced).unwrap(), parse_puzzle_string(PUZZLE).unwrap());
+    }
+
+    #[test]
+    fn puzzle_string_rejects_bad_input() {
+        assert_eq!(parse_puzzle_string(&PUZZLE[1..]), Err(SudokuError::InvalidLength));
+        assert_eq!(parse_puzzle_string(&PUZZLE.replacen('.', "x", 1)), Err(SudokuError::InvalidCharacter));
+        // Letters are numbers 10..16, no number of a 9 x 9 grid
+        assert_eq!(parse_puzzle_string(&PUZZLE.replacen('.', "A", 1)), Err(SudokuError::InvalidCharacter));
+    }
+
+    #[test]
+    fn puzzle_string_of_giant_grid_uses_letters() {
+        let mut numbers = vec![0; 256];
+        numbers[0] = 16;
+        numbers[1] = 10;
+        numbers[255] = 9;
+
+        let puzzle = format_puzzle_string(&numbers).unwrap();
+        assert_eq!(&puzzle[..2], "GA");
+        assert_eq!(parse_puzzle_string(&puzzle).unwrap(), numbers);
+        assert_eq!(parse_puzzle_string(&puzzle.to_lowercase()).unwrap(), numbers);
+    }
+
+    #[test]
+    fn format_puzzle_string_checks_the_numbers() {
+        let mut numbers = parse_puzzle_string(PUZZLE).unwrap();
+        assert_eq!(format_puzzle_string(&numbers[..80]), Err(SudokuError::InvalidLength));
+
+        numbers[2] = 10;
+        assert_eq!(format_puzzle_string(&numbers), Err(SudokuError::IndexOutOfRange));
+        numbers[2] = 200;
+        assert_eq!(format_puzzle_string(&numbers), Err(SudokuError::IndexOutOfRange));
+    }
+
+    // Placed numbers, candidates in the empty cells, one cell without candidates
+    fn pencilmark_grid() -> Grid {
+        let mut grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+        grid.set_all_candidates();
+        for cell in grid.cells_mut().iter_mut().filter(|cell| cell.is_empty()) {
+            let excluded = CandidateSet::from_bits(0b1_0010_0100);
+            cell.candidates = cell.candidates.difference(excluded);
+        }
+        grid.cell_mut(CellIndex::new(2).unwrap()).candidates = CandidateSet::EMPTY;
+
+        // The formats carry no given state
+        for cell in grid.cells_mut() {
+            cell.given = false;
+        }
+        grid
+    }
+
+    #[test]
+    fn pencilmark_flat_round_trip() {
+        let grid = pencilmark_grid();
+        let flat = to_pencilmark_string(&grid, PencilMarkFormat::FLAT).unwrap();
+
+        assert_eq!(flat.len(), PENCILMARK_FLAT_LEN);
+        assert_eq!(&flat[..18], "....5......3......");
+        assert_eq!(parse_pencilmark_string(&flat).unwrap(), grid);
+    }
+
+    #[test]
+    fn pencilmark_grid_round_trip() {
+        let mut grid = pencilmark_grid();
+        // Single candidates are bracketed, not read as placed numbers
+        grid.cell_mut(CellIndex::new(3).unwrap()).candidates = CandidateSet::from_bits(0b1000);
+
+        let text = to_pencilmark_string(&grid, PencilMarkFormat::GRID).unwrap();
+        assert!(text.contains("[4]") && text.contains("[]"));
+        assert_eq!(parse_pencilmark_string(&text).unwrap(), grid);
+    }
+
+    #[test]
+    fn pencilmark_flat_rejects_single_candidates() {
+        let mut grid = pencilmark_grid();
+        grid.cell_mut(CellIndex::new(3).unwrap()).candidates = CandidateSet::from_bits(0b1000);
+
+        assert_eq!(to_pencilmark_string(&grid, PencilMarkFormat::FLAT), Err(SudokuError::NotRepresentable));
+        assert_eq!(to_pencilmark_string(&grid, 7), Err(SudokuError::UnknownFormat));
+    }
+
+    #[test]
+    fn pencilmark_flat_checks_digit_positions() {
+        let flat = to_pencilmark_string(&pencilmark_grid(), PencilMarkFormat::FLAT).unwrap();
+        // Digit 5 at the position of digit 1
+        let moved = format!("5...{}", &flat[4..]);
+        assert_eq!(parse_pencilmark_string(&moved), Err(SudokuError::InvalidCharacter));
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,1176 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Game session
+A GameSession owns everything of one running game : the grid, the undo /
+redo history, the settings and the cached analysis (conflicts, solutions).
+Dart never sees a pointer to it, only an opaque u64 handle.
+
+Handle registry
+create → new handle, never reused, 0 is never a valid handle
+every call looks the handle up, unknown or freed → SudokuError::InvalidHandle
+free   → removes the session, Rust drops it (no layout from Dart involved)
+A call that looked the handle up just before free finds the session taken
+out under its lock and fails with InvalidHandle too.
+Every session has its own lock, the registry is only locked for the lookup.
+Solution count and solve run without holding the session lock : the grid is
+copied, solved, and the result is kept only if the grid did not change
+meanwhile. Conflicts are found without solving.
+
+Cell buffers are only copied, session_read / session_write take the element
+count of the Dart buffer and reject everything but size * size.
+
+Grid sizes
+session_create_sized starts an empty 4x4, 6x6, 12x12 or 16x16 game. The
+session buffers hold GridElementFFI (16 digits per cell), so every size goes
+through session_read / session_write, only the element count differs.
+
+Killer cages
+Part of the puzzle, not of the moves : session_add_cage / session_clear_cages
+clear the undo history like a new puzzle. set_all_candidates only sets the
+numbers the sum combinations of the cage allow.
+
+Variants
+session_add_variant / session_remove_variant take a VariantKind (diagonal,
+anti-knight, anti-king, non-consecutive), session_add_line adds a thermo,
+arrow or little killer by its cells. Like cages they start the history
+over, removing a line kind removes every line of it. Conflicts, auto candidate removal and the solver follow every
+constraint of the grid.
+
+Jigsaw
+session_set_regions takes one region number per cell (size regions of size
+connected cells) and replaces the boxes, session_remove_variant with
+VariantKind::REGIONS brings the boxes back. session_region_of tells Dart
+which region to draw a cell in.
+
+Redraw
+Every edit, undo and redo keeps the cells it changed, pattern highlights
+included (session_dirty_cells), so Dart only redraws those. With
+auto_patterns the highlights are updated incrementally around the changed
+cells, loading a puzzle or changing its rules marks every cell.
+*/
+
+use std::collections::HashMap;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+#[cfg(feature = "storage")]
+use std::os::raw::c_char;
+
+use crate::error::{ffi_boundary, ffi_status, ffi_value, SudokuError};
+
+use crate::ffi::GridElementFFI;
+
+#[cfg(feature = "storage")]
+use crate::ffi::c_char_to_str;
+
+use crate::grid::{CandidateSet, CellIndex, Digit, Geometry, Grid};
+
+use crate::killer::{cell_candidates, restrict_to_cages, Cage};
+
+use crate::constraint::{Regions, Variant};
+
+use crate::process_data::{check_grid_for_patterns, find_conflicts, remove_from_peers, update_patterns_at};
+
+use crate::hints::variant_hints;
+
+use crate::solver::count_and_solve;
+
+#[cfg(feature = "storage")]
+use crate::storage::{load_grid, parse_puzzle_string, save_grid};
+
+pub type SessionHandle = u64;
+
+pub const INVALID_SESSION: SessionHandle = 0;
+
+pub const DEFAULT_HISTORY_LIMIT: usize = 200;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SessionSettings {
+    // Recompute pattern highlights after every change
+    pub auto_patterns: bool,
+    // Placing a number removes it from the candidates of its peers
+    pub auto_remove_candidates: bool,
+    // Undo steps kept, oldest are dropped first
+    pub history_limit: usize,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        SessionSettings { auto_patterns: true, auto_remove_candidates: false, history_limit: DEFAULT_HISTORY_LIMIT }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    // Cells whose number breaks a constraint (peer, cage, variant rule)
+    pub conflicts: Vec<CellIndex>,
+    // 0, 1 or 2 (= more than one)
+    pub solution_count: usize,
+    pub solution: Option<Grid>,
+}
+
+impl Analysis {
+    // One search counts and keeps the first solution, a grid with conflicts has none
+    fn new(grid: &Grid) -> Analysis {
+        let conflicts = find_conflicts(grid);
+        let (solution_count, solution) = if conflicts.is_empty() { count_and_solve(grid, 2) } else { (0, None) };
+        Analysis { conflicts, solution_count, solution }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GameSession {
+    grid: Grid,
+    undo: Vec<Grid>,
+    redo: Vec<Grid>,
+    settings: SessionSettings,
+    analysis: Option<Analysis>,
+    // Cells changed by the last edit, undo or redo
+    dirty: Vec<CellIndex>,
+}
+
+// Cells whose content differs, same geometry
+fn changed_cells(before: &Grid, after: &Grid) -> Vec<CellIndex> {
+    after.geometry().all_cells().filter(|&idx| before.cell(idx) != after.cell(idx)).collect()
+}
+
+impl GameSession {
+    pub fn new() -> GameSession {
+        GameSession::default()
+    }
+
+    // Empty grid of the given box geometry
+    pub fn with_geometry(geometry: Geometry) -> GameSession {
+        GameSession::from_grid(Grid::with_geometry(geometry))
+    }
+
+    // New game, history starts empty
+    pub fn from_grid(grid: Grid) -> GameSession {
+        let mut session = GameSession::new();
+        session.load(grid);
+        session
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn settings(&self) -> SessionSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: SessionSettings) {
+        self.settings = settings;
+        self.trim_history();
+        self.dirty.clear();
+        self.refresh();
+    }
+
+    // Replace the game, not undoable
+    pub fn load(&mut self, grid: Grid) {
+        self.grid = grid;
+        self.undo.clear();
+        self.redo.clear();
+        self.analysis = None;
+        self.refresh();
+        self.dirty = self.grid.geometry().all_cells().collect();
+    }
+
+    // Cells to redraw after the last change, sorted
+    pub fn dirty_cells(&self) -> &[CellIndex] {
+        &self.dirty
+    }
+
+    // Apply a change as one undo step, returns false if nothing changed
+    pub fn edit(&mut self, change: impl FnOnce(&mut Grid)) -> bool {
+        let before = self.grid.clone();
+        change(&mut self.grid);
+
+        let changed = changed_cells(&before, &self.grid);
+        self.dirty = if self.settings.auto_patterns { update_patterns_at(&mut self.grid, &changed) } else { changed };
+
+        if self.grid == before {
+            return false;
+        }
+
+        self.undo.push(before);
+        self.redo.clear();
+        self.trim_history();
+        self.analysis = None;
+        true
+    }
+
+    // Cell index and digit must fit the grid size
+    fn check_cell(&self, idx: CellIndex, digit: Option<Digit>) -> Result<(), SudokuError> {
+        let geometry = self.grid.geometry();
+        let digit_ok = digit.is_none_or(|d| geometry.digit(d.get()).is_some());
+
+        if geometry.cell(idx.get()).is_none() || !digit_ok {
+            return Err(SudokuError::IndexOutOfRange);
+        }
+        Ok(())
+    }
+
+    // None clears the cell, givens can not be changed
+    pub fn set_value(&mut self, idx: CellIndex, value: Option<Digit>) -> Result<bool, SudokuError> {
+        self.check_cell(idx, value)?;
+        if self.grid.cell(idx).given {
+            return Err(SudokuError::ReadOnlyCell);
+        }
+
+        let auto_remove = self.settings.auto_remove_candidates;
+
+        Ok(self.edit(|grid| {
+            grid.set_value(idx, value);
+
+            if value.is_some() {
+                grid.cell_mut(idx).candidates = CandidateSet::EMPTY;
+
+                if auto_remove {
+                    remove_from_peers(grid, idx);
+                }
+            }
+        }))
+    }
+
+    pub fn toggle_candidate(&mut self, idx: CellIndex, digit: Digit) -> Result<bool, SudokuError> {
+        self.check_cell(idx, Some(digit))?;
+        if !self.grid.cell(idx).is_empty() {
+            return Err(SudokuError::ReadOnlyCell);
+        }
+
+        Ok(self.edit(|grid| {
+            let candidates = &mut grid.cell_mut(idx).candidates;
+            if candidates.contains(digit) {
+                candidates.remove(digit);
+            } else {
+                candidates.insert(digit);
+            }
+        }))
+    }
+
+    pub fn erase(&mut self, erase_givens: bool) -> bool {
+        self.edit(|grid| grid.erase(erase_givens))
+    }
+
+    pub fn set_all_candidates(&mut self) -> bool {
+        self.edit(|grid| {
+            grid.set_all_candidates();
+            restrict_to_cages(grid);
+        })
+    }
+
+    // Puzzle setup, clears the history
+    pub fn add_cage(&mut self, cage: Cage) -> Result<(), SudokuError> {
+        let mut grid = self.grid.clone();
+        grid.add_cage(cage)?;
+        self.load(grid);
+        Ok(())
+    }
+
+    pub fn clear_cages(&mut self) {
+        let mut grid = self.grid.clone();
+        grid.clear_cages();
+        self.load(grid);
+    }
+
+    // Variant rules are part of the puzzle like cages
+    pub fn add_variant(&mut self, variant: Variant) -> Result<(), SudokuError> {
+        let mut grid = self.grid.clone();
+        grid.add_variant(variant)?;
+        self.load(grid);
+        Ok(())
+    }
+
+    // false if the kind was not set, the history is kept then
+    pub fn remove_variant(&mut self, kind: u8) -> bool {
+        let mut grid = self.grid.clone();
+        if !grid.remove_variant(kind) {
+            return false;
+        }
+        self.load(grid);
+        true
+    }
+
+    // Jigsaw regions replace the boxes, clears the history
+    pub fn set_regions(&mut self, regions: Regions) -> Result<(), SudokuError> {
+        let mut grid = self.grid.clone();
+        grid.set_regions(regions)?;
+        self.load(grid);
+        Ok(())
+    }
+
+    // Not placed in a peer and allowed by the cage of idx
+    pub fn candidates(&self, idx: CellIndex) -> CandidateSet {
+        cell_candidates(&self.grid, idx)
+    }
+
+    // Fill in the solution as one undo step
+    pub fn solve(&mut self) -> Result<bool, SudokuError> {
+        let solution = self.analysis().solution.clone().ok_or(SudokuError::NotFound)?;
+        Ok(self.edit(|grid| *grid = solution))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(previous) => {
+                self.dirty = changed_cells(&self.grid, &previous);
+                self.redo.push(std::mem::replace(&mut self.grid, previous));
+                self.analysis = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.dirty = changed_cells(&self.grid, &next);
+                self.undo.push(std::mem::replace(&mut self.grid, next));
+                self.analysis = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Computed on first use after a change
+    pub fn analysis(&mut self) -> &Analysis {
+        self.analysis.get_or_insert_with(|| Analysis::new(&self.grid))
+    }
+
+    // Cells breaking a constraint, no solving involved
+    pub fn conflicts(&self) -> Vec<CellIndex> {
+        match &self.analysis {
+            Some(analysis) => analysis.conflicts.clone(),
+            None => find_conflicts(&self.grid),
+        }
+    }
+
+    // Keep an analysis made outside of the session, dropped if the grid changed meanwhile
+    fn store_analysis(&mut self, grid: &Grid, analysis: Analysis) {
+        if self.grid == *grid {
+            self.analysis = Some(analysis);
+        }
+    }
+
+    // Pattern highlights, also needed when auto_patterns is off
+    pub fn update_patterns(&mut self) {
+        let before = self.grid.clone();
+        check_grid_for_patterns(&mut self.grid);
+        self.dirty = changed_cells(&before, &self.grid);
+    }
+
+    fn refresh(&mut self) {
+        if self.settings.auto_patterns {
+            self.update_patterns();
+        }
+    }
+
+    fn trim_history(&mut self) {
+        let excess = self.undo.len().saturating_sub(self.settings.history_limit);
+        self.undo.drain(..excess);
+    }
+}
+
+// Handle registry
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+// None once released, for calls that looked the handle up before
+type SharedSession = Arc<Mutex<Option<GameSession>>>;
+
+// A panic inside a session call must not lock out the session or the registry
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn sessions() -> MutexGuard<'static, HashMap<SessionHandle, SharedSession>> {
+    static SESSIONS: OnceLock<Mutex<HashMap<SessionHandle, SharedSession>>> = OnceLock::new();
+
+    lock(SESSIONS.get_or_init(|| Mutex::new(HashMap::new())))
+}
+
+pub fn register_session(session: GameSession) -> SessionHandle {
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    sessions().insert(handle, Arc::new(Mutex::new(Some(session))));
+    handle
+}
+
+// Waits for a call still running on the session
+pub fn release_session(handle: SessionHandle) -> Result<GameSession, SudokuError> {
+    let session = sessions().remove(&handle).ok_or(SudokuError::InvalidHandle)?;
+    let session = lock(&session).take().ok_or(SudokuError::InvalidHandle)?;
+    Ok(session)
+}
+
+// Only this session is locked while f runs
+pub fn with_session<R>(
+    handle: SessionHandle,
+    f: impl FnOnce(&mut GameSession) -> Result<R, SudokuError>,
+) -> Result<R, SudokuError> {
+    let session = sessions().get(&handle).cloned().ok_or(SudokuError::InvalidHandle)?;
+    run_locked(&session, f)
+}
+
+// Released while waiting for the lock → InvalidHandle
+fn run_locked<R>(
+    session: &SharedSession,
+    f: impl FnOnce(&mut GameSession) -> Result<R, SudokuError>,
+) -> Result<R, SudokuError> {
+    let mut session = lock(session);
+    f(session.as_mut().ok_or(SudokuError::InvalidHandle)?)
+}
+
+// Analysis of the current grid, solved on a copy so the session stays usable
+pub fn session_analysis(handle: SessionHandle) -> Result<Analysis, SudokuError> {
+    let grid = match with_session(handle, |session| Ok(session.analysis.clone().ok_or_else(|| session.grid.clone())))? {
+        Ok(analysis) => return Ok(analysis),
+        Err(grid) => grid,
+    };
+
+    let analysis = Analysis::new(&grid);
+    with_session(handle, |session| {
+        session.store_analysis(&grid, analysis.clone());
+        Ok(())
+    })?;
+    Ok(analysis)
+}
+
+// Checked against the grid size of the session
+fn cell_index(session: &GameSession, idx: u8) -> Result<CellIndex, SudokuError> {
+    session.grid().geometry().cell(idx as usize).ok_or(SudokuError::IndexOutOfRange)
+}
+
+fn digit(session: &GameSession, value: u8) -> Result<Digit, SudokuError> {
+    session.grid().geometry().digit(value).ok_or(SudokuError::IndexOutOfRange)
+}
+
+// 1 if something changed, 0 if not
+fn changed(changed: bool) -> Result<c_int, SudokuError> {
+    Ok(changed as c_int)
+}
+
+// Session handle, INVALID_SESSION (0) on error
+#[no_mangle]
+pub extern "C" fn session_create() -> SessionHandle {
+    ffi_boundary(|| Ok(register_session(GameSession::new()))).unwrap_or(INVALID_SESSION)
+}
+
+// size 4, 6, 9, 12 or 16, INVALID_SESSION (0) for other sizes
+#[no_mangle]
+pub extern "C" fn session_create_sized(size: u8) -> SessionHandle {
+    ffi_boundary(|| {
+        let geometry = Geometry::from_size(size as usize).ok_or(SudokuError::DimensionMismatch)?;
+        Ok(register_session(GameSession::with_geometry(geometry)))
+    })
+    .unwrap_or(INVALID_SESSION)
+}
+
+// Rows (= columns) of the session grid
+#[no_mangle]
+pub extern "C" fn session_grid_size(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| Ok(session.grid().size() as c_int)))
+}
+
+// Number of cell idx, 0 = empty
+#[no_mangle]
+pub extern "C" fn session_get_value(handle: SessionHandle, idx: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        Ok(session.grid().value(idx).map_or(0, Digit::get) as c_int)
+    }))
+}
+
+// Candidates of cell idx as bitmask, bit 0 = number 1
+#[no_mangle]
+pub extern "C" fn session_get_candidates(handle: SessionHandle, idx: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        Ok(session.grid().cell(idx).candidates.bits() as c_int)
+    }))
+}
+
+// cells : len cell indices of one connected cage
+#[no_mangle]
+pub unsafe extern "C" fn session_add_cage(handle: SessionHandle, cells: *const u8, len: u32, sum: u16) -> c_int {
+    ffi_status(|| {
+        if cells.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+        let indices = std::slice::from_raw_parts(cells, len as usize);
+
+        with_session(handle, |session| {
+            let cells = indices.iter().map(|&i| cell_index(session, i)).collect::<Result<Vec<_>, _>>()?;
+            let cage = Cage::new(session.grid().geometry(), cells, sum)?;
+            session.add_cage(cage)
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn session_clear_cages(handle: SessionHandle) -> c_int {
+    ffi_status(|| with_session(handle, |session| {
+        session.clear_cages();
+        Ok(())
+    }))
+}
+
+#[no_mangle]
+pub extern "C" fn session_cage_count(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| Ok(session.grid().cages().len() as c_int)))
+}
+
+// Cage number of cell idx, SudokuError::NotFound if the cell has no cage
+#[no_mangle]
+pub extern "C" fn session_cage_of(handle: SessionHandle, idx: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        let cages = session.grid().cages();
+        Ok(cages.iter().position(|cage| cage.contains(idx)).ok_or(SudokuError::NotFound)? as c_int)
+    }))
+}
+
+#[no_mangle]
+pub extern "C" fn session_cage_sum(handle: SessionHandle, cage: u32) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let cage = session.grid().cages().get(cage as usize).ok_or(SudokuError::IndexOutOfRange)?;
+        Ok(cage.sum() as c_int)
+    }))
+}
+
+// Candidates a cell can still take as bitmask, bit 0 = number 1
+#[no_mangle]
+pub extern "C" fn session_cage_candidates(handle: SessionHandle, idx: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        Ok(session.candidates(idx).bits() as c_int)
+    }))
+}
+
+// kind = VariantKind, regions are set with their map
+#[no_mangle]
+pub extern "C" fn session_add_variant(handle: SessionHandle, kind: u8) -> c_int {
+    ffi_status(|| with_session(handle, |session| session.add_variant(Variant::from_kind(kind)?)))
+}
+
+// Thermo, arrow (circle first) or little killer, sum only for little killers
+#[no_mangle]
+pub unsafe extern "C" fn session_add_line(handle: SessionHandle, kind: u8, cells: *const u8, len: u32, sum: u16) -> c_int {
+    ffi_status(|| {
+        if cells.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+        let cells = std::slice::from_raw_parts(cells, len as usize);
+
+        with_session(handle, |session| {
+            let variant = Variant::from_parts(session.grid().geometry(), kind, cells, sum)?;
+            session.add_variant(variant)
+        })
+    })
+}
+
+// Candidate eliminations the variant rules allow right now
+#[no_mangle]
+pub extern "C" fn session_hint_count(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| Ok(variant_hints(session.grid()).len() as c_int)))
+}
+
+// 1 = removed, 0 = kind was not set
+#[no_mangle]
+pub extern "C" fn session_remove_variant(handle: SessionHandle, kind: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| Ok(session.remove_variant(kind) as c_int)))
+}
+
+// 1 = set, 0 = not set
+#[no_mangle]
+pub extern "C" fn session_has_variant(handle: SessionHandle, kind: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| Ok(session.grid().has_variant(kind) as c_int)))
+}
+
+// map = one region number 0..size per cell, len = cells of the grid
+#[no_mangle]
+pub unsafe extern "C" fn session_set_regions(handle: SessionHandle, map: *const u8, len: u32) -> c_int {
+    ffi_status(|| {
+        if map.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+        let map = std::slice::from_raw_parts(map, len as usize).to_vec();
+
+        with_session(handle, |session| {
+            let regions = Regions::new(session.grid().geometry(), map)?;
+            session.set_regions(regions)
+        })
+    })
+}
+
+// Jigsaw region of idx, the box number without regions
+#[no_mangle]
+pub extern "C" fn session_region_of(handle: SessionHandle, idx: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        Ok(session.grid().region_of(idx) as c_int)
+    }))
+}
+
+#[no_mangle]
+pub extern "C" fn session_free(handle: SessionHandle) -> c_int {
+    ffi_status(|| release_session(handle).map(drop))
+}
+
+// Copy the session grid into a Dart buffer of len elements, len must be
+// size * size of the session grid
+#[no_mangle]
+pub unsafe extern "C" fn session_read(handle: SessionHandle, ptr: *mut GridElementFFI, len: u32) -> c_int {
+    ffi_status(|| {
+        if ptr.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+
+        with_session(handle, |session| {
+            session.grid().write_elements(std::slice::from_raw_parts_mut(ptr, len as usize))
+        })
+    })
+}
+
+// Take over the cells edited on the Dart side as one undo step, the rules
+// of the puzzle (cages, variants, regions) stay as they are
+#[no_mangle]
+pub unsafe extern "C" fn session_write(handle: SessionHandle, ptr: *const GridElementFFI, len: u32) -> c_int {
+    ffi_value(|| {
+        if ptr.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+
+        let slice = std::slice::from_raw_parts(ptr, len as usize);
+        with_session(handle, |session| {
+            let mut grid = session.grid().clone();
+            grid.read_elements(slice)?;
+            changed(session.edit(|g| *g = grid))
+        })
+    })
+}
+
+// value 0 clears the cell
+#[no_mangle]
+pub extern "C" fn session_set_value(handle: SessionHandle, idx: u8, value: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        let value = if value == 0 { None } else { Some(digit(session, value)?) };
+
+        changed(session.set_value(idx, value)?)
+    }))
+}
+
+#[no_mangle]
+pub extern "C" fn session_toggle_candidate(handle: SessionHandle, idx: u8, value: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| {
+        let idx = cell_index(session, idx)?;
+        let digit = digit(session, value)?;
+
+        changed(session.toggle_candidate(idx, digit)?)
+    }))
+}
+
+#[no_mangle]
+pub extern "C" fn session_erase(handle: SessionHandle, erase_givens: u8) -> c_int {
+    ffi_value(|| with_session(handle, |session| changed(session.erase(erase_givens > 0))))
+}
+
+#[no_mangle]
+pub extern "C" fn session_set_all_candidates(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| changed(session.set_all_candidates())))
+}
+
+#[no_mangle]
+pub extern "C" fn session_update_patterns(handle: SessionHandle) -> c_int {
+    ffi_status(|| with_session(handle, |session| {
+        session.update_patterns();
+        Ok(())
+    }))
+}
+
+// Cells to redraw after the last call that changed the grid, written to
+// out (at most len), returns the number of dirty cells
+#[no_mangle]
+pub unsafe extern "C" fn session_dirty_cells(handle: SessionHandle, out: *mut u8, len: u32) -> c_int {
+    ffi_value(|| {
+        if out.is_null() {
+            return Err(SudokuError::NullPointer);
+        }
+        let out = std::slice::from_raw_parts_mut(out, len as usize);
+
+        with_session(handle, |session| {
+            let dirty = session.dirty_cells();
+            for (slot, idx) in out.iter_mut().zip(dirty) {
+                *slot = idx.get() as u8;
+            }
+            Ok(dirty.len() as c_int)
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn session_undo(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| changed(session.undo())))
+}
+
+#[no_mangle]
+pub extern "C" fn session_redo(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| changed(session.redo())))
+}
+
+#[no_mangle]
+pub extern "C" fn session_set_settings(
+    handle: SessionHandle,
+    auto_patterns: u8,
+    auto_remove_candidates: u8,
+    history_limit: u32,
+) -> c_int {
+    ffi_status(|| with_session(handle, |session| {
+        session.set_settings(SessionSettings {
+            auto_patterns: auto_patterns > 0,
+            auto_remove_candidates: auto_remove_candidates > 0,
+            history_limit: history_limit as usize,
+        });
+        Ok(())
+    }))
+}
+
+// 0 = no solution, 1 = unique, 2 = more than one
+#[no_mangle]
+pub extern "C" fn session_solution_count(handle: SessionHandle) -> c_int {
+    ffi_value(|| Ok(session_analysis(handle)?.solution_count as c_int))
+}
+
+// Number of cells in conflict with a peer
+#[no_mangle]
+pub extern "C" fn session_conflict_count(handle: SessionHandle) -> c_int {
+    ffi_value(|| with_session(handle, |session| Ok(session.conflicts().len() as c_int)))
+}
+
+#[no_mangle]
+pub extern "C" fn session_solve(handle: SessionHandle) -> c_int {
+    ffi_value(|| {
+        // Solved outside of the session lock, solve() then finds it cached
+        session_analysis(handle)?;
+        with_session(handle, |session| changed(session.solve()?))
+    })
+}
+
+// New game from a puzzle string, the length selects the grid size
+#[cfg(feature = "storage")]
+#[no_mangle]
+pub unsafe extern "C" fn session_new_puzzle(handle: SessionHandle, puzzle: *const c_char) -> c_int {
+    ffi_status(|| {
+        let grid = Grid::from_givens(&parse_puzzle_string(c_char_to_str(puzzle)?)?)?;
+        with_session(handle, |session| {
+            session.load(grid);
+            Ok(())
+        })
+    })
+}
+
+#[cfg(feature = "storage")]
+#[no_mangle]
+pub unsafe extern "C" fn session_save(handle: SessionHandle, path: *const c_char, format: u8) -> c_int {
+    ffi_status(|| {
+        let path_str = c_char_to_str(path)?;
+        with_session(handle, |session| save_grid(session.grid(), path_str, format))
+    })
+}
+
+#[cfg(feature = "storage")]
+#[no_mangle]
+pub unsafe extern "C" fn session_load(handle: SessionHandle, path: *const c_char) -> c_int {
+    ffi_status(|| {
+        let grid = load_grid(c_char_to_str(path)?)?;
+        with_session(handle, |session| {
+            session.load(grid);
+            Ok(())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::grid::{MAX_GRID_SIZE, GRID_CELLS};
+    use crate::process_data::cell_peers;
+    use crate::solver::solve;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn puzzle() -> Grid {
+        let numbers: Vec<u8> = PUZZLE.bytes().map(|b| if b == b'.' { 0 } else { b - b'0' }).collect();
+        Grid::from_givens(&numbers).unwrap()
+    }
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    #[test]
+    fn analysis_counts_and_solves_in_one_search() {
+        let handle = register_session(GameSession::from_grid(puzzle()));
+
+        assert_eq!(session_solution_count(handle), 1);
+        let solution = with_session(handle, |session| Ok(session.analysis.clone())).unwrap().unwrap().solution;
+        // Pattern highlights differ, the session keeps them up to date
+        assert_eq!(solution.unwrap().numbers(), solve(&puzzle()).unwrap().numbers());
+
+        assert_eq!(session_solve(handle), 1);
+        assert_eq!(with_session(handle, |session| Ok(session.grid().is_complete())), Ok(true));
+        assert_eq!(session_free(handle), 0);
+    }
+
+    #[test]
+    fn conflicts_are_found_without_solving() {
+        let mut session = GameSession::from_grid(puzzle());
+        // 5 is a given in the same row
+        session.set_value(idx(2), Digit::new(5)).unwrap();
+
+        assert_eq!(session.conflicts(), vec![idx(0), idx(2)]);
+        assert!(session.analysis.is_none());
+
+        let handle = register_session(session);
+        assert_eq!(session_conflict_count(handle), 2);
+        assert_eq!(session_solution_count(handle), 0);
+        assert_eq!(session_solve(handle), SudokuError::NotFound.code());
+        assert_eq!(session_free(handle), 0);
+    }
+
+    #[test]
+    fn analysis_of_an_old_grid_is_dropped() {
+        let mut session = GameSession::from_grid(puzzle());
+        let before = session.grid().clone();
+        let analysis = Analysis::new(&before);
+
+        session.set_value(idx(2), Digit::new(4)).unwrap();
+        session.store_analysis(&before, analysis.clone());
+        assert!(session.analysis.is_none());
+
+        session.undo();
+        session.store_analysis(&before, analysis.clone());
+        assert_eq!(session.analysis, Some(analysis));
+    }
+
+    // Read the session into a Dart buffer, change it and write it back
+    fn write_back(handle: SessionHandle, change: impl FnOnce(&mut [GridElementFFI])) -> c_int {
+        let mut elements = Grid::new().to_elements();
+        unsafe {
+            assert_eq!(session_read(handle, elements.as_mut_ptr(), GRID_CELLS as u32), 0);
+            change(&mut elements);
+            session_write(handle, elements.as_ptr(), GRID_CELLS as u32)
+        }
+    }
+
+    #[test]
+    fn session_buffers_fit_every_grid_size() {
+        for geometry in Geometry::SUPPORTED {
+            let size = geometry.size();
+            let handle = session_create_sized(size as u8);
+            let mut elements = vec![GridElementFFI::default(); geometry.cells()];
+
+            unsafe {
+                assert_eq!(session_read(handle, elements.as_mut_ptr(), elements.len() as u32), 0);
+                assert_eq!(elements[size + 1].row, 1);
+                assert_eq!(elements[size + 1].col, 1);
+
+                // Last cell gets the highest digit, its peer the highest candidate
+                let last = geometry.cells() - 1;
+                elements[last].selectedNum = size as u8;
+                elements[last - 1].selectedCandList[size - 1] = 1;
+                // Above the size, dropped (none for 16x16)
+                elements[0].selectedCandList[size..].fill(1);
+                assert_eq!(session_write(handle, elements.as_ptr(), elements.len() as u32), 1);
+                assert_eq!(session_get_value(handle, last as u8), size as c_int);
+                assert_eq!(session_get_candidates(handle, (last - 1) as u8), 1 << (size - 1));
+
+                let mut read = vec![GridElementFFI::default(); geometry.cells()];
+                assert_eq!(session_read(handle, read.as_mut_ptr(), read.len() as u32), 0);
+                assert_eq!(read[last].selectedNum, size as u8);
+                assert_eq!(read[last - 1].selectedCandList[size - 1], 1);
+                assert_eq!(read[0].selectedCandList, [0; MAX_GRID_SIZE]);
+
+                // Buffers of another size are rejected
+                let short = (geometry.cells() - 1) as u32;
+                assert_eq!(session_read(handle, read.as_mut_ptr(), short), SudokuError::InvalidLength.code());
+                assert_eq!(session_write(handle, read.as_ptr(), short), SudokuError::InvalidLength.code());
+            }
+            release_session(handle).unwrap();
+        }
+    }
+
+    #[test]
+    fn session_write_keeps_cages() {
+        let solution = solve(&puzzle()).unwrap();
+        let cells = vec![idx(2), idx(3), idx(12)];
+        let sum = cells.iter().map(|&i| solution.value(i).unwrap().get() as u16).sum();
+        let mut session = GameSession::from_grid(puzzle());
+        session.add_cage(Cage::new(Geometry::CLASSIC, cells.clone(), sum).unwrap()).unwrap();
+        let handle = register_session(session);
+
+        assert_eq!(write_back(handle, |elements| elements[2].selectedNum = solution.value(idx(2)).unwrap().get()), 1);
+
+        with_session(handle, |session| {
+            assert_eq!(session.grid().cages().len(), 1);
+            // Only the written cell and the highlights around it, no rule change
+            assert!(session.dirty_cells().contains(&idx(2)));
+            assert!(session.dirty_cells().len() < GRID_CELLS);
+            assert!(session.can_undo());
+            Ok(())
+        })
+        .unwrap();
+
+        // Solved against the cage of the session
+        assert_eq!(session_solve(handle), 1);
+        let solved = release_session(handle).unwrap();
+        assert_eq!(solved.grid().numbers(), solution.numbers());
+        assert_eq!(cells.iter().map(|&i| solved.grid().value(i).unwrap().get() as u16).sum::<u16>(), sum);
+    }
+
+    #[test]
+    fn session_write_keeps_variants() {
+        let mut session = GameSession::new();
+        session.add_variant(Variant::Diagonal).unwrap();
+        session.add_variant(Variant::NonConsecutive).unwrap();
+        let handle = register_session(session);
+
+        // Corners of the main diagonal, only the variant makes them conflict
+        assert_eq!(write_back(handle, |elements| elements[0].selectedNum = 5), 1);
+        assert_eq!(write_back(handle, |elements| elements[80].selectedNum = 5), 1);
+
+        with_session(handle, |session| {
+            assert_eq!(session.grid().variants(), [Variant::Diagonal, Variant::NonConsecutive]);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(session_conflict_count(handle), 2);
+
+        // A 4 next to the 5, cell 0 now conflicts twice
+        assert_eq!(write_back(handle, |elements| elements[1].selectedNum = 4), 1);
+        assert_eq!(session_conflict_count(handle), 3);
+        release_session(handle).unwrap();
+    }
+
+    #[test]
+    fn session_write_keeps_regions() {
+        // Boxes 0 and 1 trade (0,2) and (2,3)
+        let mut map = Regions::from_boxes(Geometry::CLASSIC).map().to_vec();
+        map.swap(2, 21);
+        let mut session = GameSession::new();
+        session.set_regions(Regions::new(Geometry::CLASSIC, map).unwrap()).unwrap();
+        let handle = register_session(session);
+
+        // Box 0 but different regions
+        assert_eq!(write_back(handle, |elements| elements[2].selectedNum = 5), 1);
+        assert_eq!(write_back(handle, |elements| elements[10].selectedNum = 5), 1);
+
+        with_session(handle, |session| {
+            assert_eq!(session.grid().region_of(idx(2)), 1);
+            assert_eq!(session.grid().region_of(idx(21)), 0);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(session_conflict_count(handle), 0);
+
+        // Region 0 but different boxes
+        assert_eq!(write_back(handle, |elements| elements[21].selectedNum = 5), 1);
+        assert_eq!(session_conflict_count(handle), 2);
+        release_session(handle).unwrap();
+    }
+
+    #[test]
+    fn sessions_are_locked_one_by_one() {
+        let first = register_session(GameSession::from_grid(puzzle()));
+        let second = register_session(GameSession::new());
+
+        // With one lock for all sessions the inner call would never return
+        let value = with_session(first, |_| with_session(second, |session| Ok(session.grid().value(idx(0)))));
+        assert_eq!(value, Ok(None));
+
+        assert_eq!(release_session(first).unwrap().grid().numbers(), puzzle().numbers());
+        assert_eq!(with_session(first, |_| Ok(())), Err(SudokuError::InvalidHandle));
+        assert_eq!(session_free(second), 0);
+    }
+
+    fn values(session: &GameSession, cells: &[usize]) -> Vec<u8> {
+        cells.iter().map(|&i| session.grid().value(idx(i)).map_or(0, Digit::get)).collect()
+    }
+
+    // Row 0 of the solution is 534678912, cells 2, 3 and 5 are empty
+    fn fill_row(session: &mut GameSession) {
+        for (i, value) in [(2, 4), (3, 6), (5, 8)] {
+            assert_eq!(session.set_value(idx(i), Digit::new(value)), Ok(true));
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_the_edits_in_order() {
+        let mut session = GameSession::from_grid(puzzle());
+        assert!(!session.undo());
+        fill_row(&mut session);
+
+        // Last edit first
+        for expected in [[4, 6, 0], [4, 0, 0], [0, 0, 0]] {
+            assert!(session.undo());
+            assert_eq!(values(&session, &[2, 3, 5]), expected);
+        }
+        assert!(!session.can_undo());
+        assert!(!session.undo());
+        assert_eq!(session.grid(), &GameSession::from_grid(puzzle()).grid);
+
+        for expected in [[4, 0, 0], [4, 6, 0], [4, 6, 8]] {
+            assert!(session.redo());
+            assert_eq!(values(&session, &[2, 3, 5]), expected);
+        }
+        assert!(session.dirty_cells().contains(&idx(5)));
+        assert!(!session.can_redo());
+        assert!(!session.redo());
+    }
+
+    #[test]
+    fn new_edit_clears_the_redo_stack() {
+        let mut session = GameSession::from_grid(puzzle());
+        fill_row(&mut session);
+        session.undo();
+        session.undo();
+
+        // Nothing changes, redo stays possible
+        assert_eq!(session.set_value(idx(5), None), Ok(false));
+        assert!(session.can_redo());
+
+        assert_eq!(session.set_value(idx(3), Digit::new(2)), Ok(true));
+        assert!(!session.can_redo());
+        assert!(!session.redo());
+        assert_eq!(values(&session, &[2, 3, 5]), [4, 2, 0]);
+
+        assert!(session.undo());
+        assert_eq!(values(&session, &[2, 3, 5]), [4, 0, 0]);
+    }
+
+    #[test]
+    fn history_limit_drops_the_oldest_steps() {
+        let mut session = GameSession::from_grid(puzzle());
+        session.set_settings(SessionSettings { history_limit: 2, ..SessionSettings::default() });
+        fill_row(&mut session);
+
+        assert!(session.undo());
+        assert!(session.undo());
+        assert!(!session.undo());
+        // The first edit can not be taken back anymore
+        assert_eq!(values(&session, &[2, 3, 5]), [4, 0, 0]);
+
+        // Lowering the limit trims the steps already kept
+        session.redo();
+        session.redo();
+        session.set_settings(SessionSettings { history_limit: 1, ..SessionSettings::default() });
+        assert!(session.undo());
+        assert!(!session.undo());
+        assert_eq!(values(&session, &[2, 3, 5]), [4, 6, 0]);
+
+        session.set_settings(SessionSettings { history_limit: 0, ..SessionSettings::default() });
+        assert_eq!(session.set_value(idx(5), Digit::new(8)), Ok(true));
+        assert!(!session.can_undo());
+    }
+
+    #[test]
+    fn settings_are_set_over_ffi() {
+        let handle = register_session(GameSession::from_grid(puzzle()));
+        assert_eq!(session_set_all_candidates(handle), 1);
+        let candidates_of_3 = || session_get_candidates(handle, 3);
+        let four = 1 << 3;
+
+        // Off by default, the player's pencil marks stay
+        assert_eq!(session_set_value(handle, 2, 4), 1);
+        assert_ne!(candidates_of_3() & four, 0);
+        assert_eq!(session_undo(handle), 1);
+
+        assert_eq!(session_set_settings(handle, 0, 1, 1), 0);
+        with_session(handle, |session| {
+            assert_eq!(
+                session.settings(),
+                SessionSettings { auto_patterns: false, auto_remove_candidates: true, history_limit: 1 }
+            );
+            // The step of set_all_candidates fits into the limit
+            assert!(session.can_undo());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(session_set_value(handle, 2, 4), 1);
+        assert_eq!(candidates_of_3() & four, 0);
+        with_session(handle, |session| {
+            // Without auto_patterns only the changed cells are dirty, no highlights
+            let changed = session.dirty_cells().to_vec();
+            assert!(changed.contains(&idx(2)) && changed.contains(&idx(3)));
+            assert!(changed.iter().all(|&i| cell_peers(session.grid(), idx(2)).contains(&i) || i == idx(2)));
+            Ok(())
+        })
+        .unwrap();
+        // Only the set_value step is kept
+        assert_eq!(session_undo(handle), 1);
+        assert_eq!(session_undo(handle), 0);
+        assert_ne!(candidates_of_3() & four, 0);
+
+        assert_eq!(session_free(handle), 0);
+        assert_eq!(session_set_settings(handle, 1, 0, 10), SudokuError::InvalidHandle.code());
+    }
+
+    #[test]
+    fn call_looked_up_before_free_gets_invalid_handle() {
+        let handle = register_session(GameSession::from_grid(puzzle()));
+        // As with_session holds it between the lookup and the lock
+        let shared = sessions().get(&handle).cloned().unwrap();
+
+        assert_eq!(release_session(handle).unwrap().grid().numbers(), puzzle().numbers());
+        assert_eq!(run_locked(&shared, |_| Ok(())), Err(SudokuError::InvalidHandle));
+        assert_eq!(session_free(handle), SudokuError::InvalidHandle.code());
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
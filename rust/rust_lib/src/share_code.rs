@@ -0,0 +1,308 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Shareable game state codes
+Turns the current game (givens, placed numbers, candidates) into a short
+URL-safe string that can be pasted into a chat, and back into the
+create_matrix buffer. Highlights and user pattern selections are not part
+of the code.
+
+Layout (version 1), bit packed with BitWriter, MSB first :
+  version                      8 bits
+  per cell 2 bit kind + payload :
+    0 empty                    -
+    1 given                    4 bits number
+    2 placed                   4 bits number
+    3 candidates               9 bits selectedCandList
+  CRC-16/CCITT of the bytes above, 16 bits
+Encoded as base64url without padding.
+
+Version 2 is used for every grid size but 9x9, version 1 codes stay valid :
+  version                      8 bits
+  grid size                    8 bits (4, 6, 12 or 16)
+  cells as above, numbers take 5 bits for 16x16, candidates one bit per digit
+*/
+
+use std::os::raw::{c_char, c_int};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+use crate::ffi::{DartToRustElementFFI};
+
+use crate::ffi::{c_char_to_str, read_grid, write_grid};
+
+use crate::error::{ffi_status, ffi_string, SudokuError};
+
+use crate::grid::{CandidateSet, Geometry, Grid, MAX_GRID_SIZE};
+
+use crate::binary_format::{bits_per_num, BitWriter, BitReader};
+
+// Classic 9x9 codes
+pub const SHARE_CODE_VERSION: u8 = 1;
+// Codes with grid size
+pub const SHARE_CODE_VERSION_SIZED: u8 = 2;
+
+struct ShareCellKind;
+
+impl ShareCellKind {
+    const EMPTY: u32 = 0;
+    const GIVEN: u32 = 1;
+    const PLACED: u32 = 2;
+    const CANDIDATES: u32 = 3;
+}
+
+// CRC-16/CCITT-FALSE
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+
+    crc
+}
+
+pub fn encode_game_state(grid: &Grid) -> String {
+    let geometry = grid.geometry();
+    let mut bits = BitWriter::default();
+
+    if geometry == Geometry::CLASSIC {
+        bits.write(SHARE_CODE_VERSION as u32, 8);
+    } else {
+        bits.write(SHARE_CODE_VERSION_SIZED as u32, 8);
+        bits.write(geometry.size() as u32, 8);
+    }
+
+    for cell in grid.cells() {
+        if let Some(digit) = cell.value {
+            let kind = if cell.given { ShareCellKind::GIVEN } else { ShareCellKind::PLACED };
+            bits.write(kind, 2);
+            bits.write(digit.get() as u32, bits_per_num(geometry));
+        } else if !cell.candidates.is_empty() {
+            bits.write(ShareCellKind::CANDIDATES, 2);
+            bits.write_flags(&cell.candidates.to_flag_vec(geometry.size()));
+        } else {
+            bits.write(ShareCellKind::EMPTY, 2);
+        }
+    }
+
+    let mut bytes = bits.into_bytes();
+    bytes.extend_from_slice(&crc16(&bytes).to_be_bytes());
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn decode_game_state(code: &str) -> Result<Grid, SudokuError> {
+    let bytes = URL_SAFE_NO_PAD.decode(code.trim()).map_err(|_| SudokuError::InvalidCharacter)?;
+
+    if bytes.len() < 3 {
+        return Err(SudokuError::InvalidLength);
+    }
+
+    let (payload, crc) = bytes.split_at(bytes.len() - 2);
+    if crc16(payload).to_be_bytes() != crc {
+        return Err(SudokuError::ChecksumMismatch); // code mistyped or cut off
+    }
+
+    let mut reader = BitReader::new(payload);
+    let geometry = match reader.read(8).map(|v| v as u8) {
+        Some(SHARE_CODE_VERSION) => Geometry::CLASSIC,
+        Some(SHARE_CODE_VERSION_SIZED) => {
+            let size = reader.read(8).ok_or(SudokuError::CorruptData)?;
+            Geometry::from_size(size as usize).ok_or(SudokuError::DimensionMismatch)?
+        }
+        _ => return Err(SudokuError::UnsupportedVersion),
+    };
+
+    let mut grid = Grid::with_geometry(geometry);
+
+    for cell in grid.cells_mut() {
+        let kind = reader.read(2).ok_or(SudokuError::CorruptData)?; // truncated data
+        match kind {
+            ShareCellKind::GIVEN | ShareCellKind::PLACED => {
+                let num = reader.read(bits_per_num(geometry)).ok_or(SudokuError::CorruptData)? as u8;
+                cell.value = Some(geometry.digit(num).ok_or(SudokuError::CorruptData)?);
+                cell.given = kind == ShareCellKind::GIVEN;
+            }
+            ShareCellKind::CANDIDATES => {
+                let mut flags = [0; MAX_GRID_SIZE];
+                reader.read_flags(&mut flags[..geometry.size()]).ok_or(SudokuError::CorruptData)?;
+                cell.candidates = CandidateSet::from_flags(&flags);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(grid)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn encode_share_code(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+) -> *mut c_char {
+    // Caller releases the string with free_rust_string
+    ffi_string(|| Ok(encode_game_state(&read_grid(ptr, rows, cols)?)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn decode_share_code(
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    code: *const c_char,
+) -> c_int {
+    ffi_status(|| {
+        read_grid(ptr, rows, cols)?;
+
+        // Decode completely before the grid is touched
+        let grid = decode_game_state(c_char_to_str(code)?)?;
+        write_grid(&grid, ptr)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{CStr, CString};
+
+    use crate::ffi::free_rust_string;
+    use crate::grid::{CellIndex, Digit};
+    use crate::puzzle_string::parse_puzzle_string;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn idx(i: usize) -> CellIndex {
+        CellIndex::new(i).unwrap()
+    }
+
+    // Givens, a placed number and a few candidate cells
+    fn sample_grid() -> Grid {
+        let mut grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+        grid.cell_mut(idx(2)).value = Digit::new(4);
+        grid.cell_mut(idx(3)).candidates = CandidateSet::from_flags(&[0, 1, 0, 0, 0, 1, 0, 0, 0]);
+        grid.cell_mut(idx(78)).candidates = Geometry::CLASSIC.all_candidates();
+        grid
+    }
+
+    #[test]
+    fn share_code_round_trip() {
+        let grid = sample_grid();
+        let code = encode_game_state(&grid);
+
+        assert!(code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_game_state(&code).unwrap(), grid);
+        assert_eq!(decode_game_state(&format!("  {code}\n")).unwrap(), grid);
+    }
+
+    #[test]
+    fn share_code_round_trip_keeps_other_grid_sizes() {
+        for geometry in Geometry::SUPPORTED {
+            let mut grid = Grid::with_geometry(geometry);
+            let last = geometry.cell(geometry.cells() - 1).unwrap();
+            grid.cell_mut(idx(0)).value = geometry.digit(geometry.size() as u8);
+            grid.cell_mut(idx(0)).given = true;
+            grid.cell_mut(last).candidates = geometry.all_candidates();
+
+            let decoded = decode_game_state(&encode_game_state(&grid)).unwrap();
+            assert_eq!(decoded.geometry(), geometry);
+            assert_eq!(decoded, grid);
+        }
+    }
+
+    #[test]
+    fn mistyped_code_fails_the_checksum() {
+        let code = encode_game_state(&sample_grid());
+
+        for position in [0, code.len() / 2, code.len() - 4] {
+            let mut chars: Vec<char> = code.chars().collect();
+            chars[position] = if chars[position] == 'A' { 'B' } else { 'A' };
+            let mistyped: String = chars.into_iter().collect();
+            assert_eq!(decode_game_state(&mistyped).unwrap_err(), SudokuError::ChecksumMismatch);
+        }
+
+        // Cut off, the checksum is then read from the cell data
+        let bytes = URL_SAFE_NO_PAD.decode(&code).unwrap();
+        let cut_off = URL_SAFE_NO_PAD.encode(&bytes[..bytes.len() - 3]);
+        assert_eq!(decode_game_state(&cut_off).unwrap_err(), SudokuError::ChecksumMismatch);
+    }
+
+    #[test]
+    fn decode_rejects_bad_codes() {
+        assert_eq!(decode_game_state("not a code!").unwrap_err(), SudokuError::InvalidCharacter);
+        assert_eq!(decode_game_state("AQ").unwrap_err(), SudokuError::InvalidLength);
+
+        // Valid checksum, but a version this build does not know
+        let mut bytes = vec![SHARE_CODE_VERSION_SIZED + 1, 0];
+        bytes.extend_from_slice(&crc16(&bytes).to_be_bytes());
+        assert_eq!(decode_game_state(&URL_SAFE_NO_PAD.encode(&bytes)).unwrap_err(), SudokuError::UnsupportedVersion);
+
+        // Valid checksum, but the cells are cut off
+        let mut bytes = vec![SHARE_CODE_VERSION, 0];
+        bytes.extend_from_slice(&crc16(&bytes).to_be_bytes());
+        assert_eq!(decode_game_state(&URL_SAFE_NO_PAD.encode(&bytes)).unwrap_err(), SudokuError::CorruptData);
+    }
+
+    #[test]
+    fn share_code_through_ffi() {
+        let elements = sample_grid().to_ffi();
+        let mut decoded = Grid::new().to_ffi();
+        let before = decoded.clone();
+
+        unsafe {
+            let code = encode_share_code(elements.as_ptr(), 9, 9);
+            assert!(!code.is_null());
+            let code_string = CString::from(CStr::from_ptr(code));
+            free_rust_string(code);
+
+            let mistyped = CString::new(code_string.to_str().unwrap().replace('A', "B").replace('-', "A")).unwrap();
+            assert_eq!(decode_share_code(decoded.as_mut_ptr(), 9, 9, mistyped.as_ptr()), SudokuError::ChecksumMismatch.code());
+            // A rejected code leaves the game on screen alone
+            assert_eq!(decoded, before);
+
+            assert_eq!(decode_share_code(decoded.as_mut_ptr(), 9, 9, code_string.as_ptr()), 0);
+        }
+
+        // The FFI buffer carries nine candidate highlights only
+        assert_eq!(Grid::from_ffi(&decoded).unwrap(), Grid::from_ffi(&elements).unwrap());
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,640 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Backtracking solver
+Works on the placed numbers of a Grid, candidates are ignored. The rules
+come from the constraints of the grid (constraint.rs) : every unit keeps a
+u16 mask of its used numbers, neighbour rules (anti-knight, anti-king,
+non-consecutive) keep a counter per cell and number of the neighbours
+blocking it, line rules (thermo, arrow, little killer) check their bounds. The search branches on the cell with the fewest possible
+numbers, or on the number with the fewest places left in a full size unit
+when that is smaller (hidden single).
+Killer cages of the grid are kept as sum / used mask per cage, a number is
+only possible if the rest of the cage can still reach the sum. Every Geometry of the grid is supported.
+
+Multi-grids (Samurai, multi_grid.rs) keep one SearchState per grid. A board
+position is one variable : its possible numbers are the intersection over
+the grids covering it, a number is placed in all of them at once.
+*/
+
+use crate::constraint::Rules;
+
+use crate::dlx::DancingLinks;
+
+use crate::grid::{CandidateSet, Cell, CellIndex, Digit, Geometry, Grid, UnitMask, MAX_GRID_SIZE};
+
+use crate::multi_grid::MultiGrid;
+
+pub(crate) struct CageState {
+    sum: u16,
+    cells: usize,
+    used: u16,
+    placed_sum: u16,
+    placed: usize,
+}
+
+impl CageState {
+    // One state per cage of the grid and the cage of every cell
+    pub(crate) fn of_grid(grid: &Grid) -> (Vec<CageState>, Vec<Option<usize>>) {
+        let mut cages = Vec::new();
+        let mut cage_of = vec![None; grid.geometry().cells()];
+
+        for (c, cage) in grid.cages().iter().enumerate() {
+            cages.push(CageState { sum: cage.sum(), cells: cage.cells().len(), used: 0, placed_sum: 0, placed: 0 });
+            for &idx in cage.cells() {
+                cage_of[idx.get()] = Some(c);
+            }
+        }
+
+        (cages, cage_of)
+    }
+
+    // digit still leaves a reachable sum for the other empty cells
+    pub(crate) fn fits(&self, digit: Digit, all: CandidateSet) -> bool {
+        let d = digit.get() as u16;
+        if self.used & (1 << digit.index()) != 0 || self.placed_sum + d > self.sum {
+            return false;
+        }
+
+        let rest = self.sum - self.placed_sum - d;
+        let left = self.cells - self.placed - 1;
+        let free: Vec<u16> = all
+            .difference(CandidateSet::from_bits(self.used))
+            .iter()
+            .filter(|&other| other != digit)
+            .map(|other| other.get() as u16)
+            .collect();
+
+        if free.len() < left {
+            return false;
+        }
+
+        let min: u16 = free[..left].iter().sum();
+        let max: u16 = free[free.len() - left..].iter().sum();
+        (min..=max).contains(&rest)
+    }
+
+    pub(crate) fn place(&mut self, digit: Digit) {
+        self.used |= 1 << digit.index();
+        self.placed_sum += digit.get() as u16;
+        self.placed += 1;
+    }
+
+    pub(crate) fn unplace(&mut self, digit: Digit) {
+        self.used &= !(1 << digit.index());
+        self.placed_sum -= digit.get() as u16;
+        self.placed -= 1;
+    }
+}
+
+struct SearchState<'a> {
+    geometry: Geometry,
+    rules: Rules<'a>,
+    numbers: Vec<u8>,
+    used: Vec<u16>, // one mask per unit of the rules
+    blocked: Vec<u8>, // cells x size, neighbours excluding the number
+    has_neighbours: bool,
+    cages: Vec<CageState>,
+    cage_of: Vec<Option<usize>>,
+}
+
+impl<'a> SearchState<'a> {
+    // None if the placed numbers already conflict
+    fn new(grid: &'a Grid) -> Option<SearchState<'a>> {
+        let geometry = grid.geometry();
+        let rules = Rules::new(grid);
+        let (cages, cage_of) = CageState::of_grid(grid);
+        let mut state = SearchState {
+            geometry,
+            numbers: vec![0; geometry.cells()],
+            used: vec![0; rules.units().len()],
+            blocked: vec![0; geometry.cells() * geometry.size()],
+            has_neighbours: geometry.all_cells().any(|idx| rules.neighbours(idx).next().is_some()),
+            rules,
+            cages,
+            cage_of,
+        };
+
+        for idx in geometry.all_cells() {
+            if let Some(digit) = grid.value(idx) {
+                if !state.possible(idx).contains(digit) {
+                    return None;
+                }
+                state.place(idx, digit);
+            }
+        }
+
+        Some(state)
+    }
+
+    fn possible(&self, idx: CellIndex) -> CandidateSet {
+        let all = self.geometry.all_candidates();
+        let used = self.rules.cell_units(idx).iter().fold(0, |acc, &u| acc | self.used[u]);
+        let mut possible = all.difference(CandidateSet::from_bits(used));
+
+        if self.has_neighbours {
+            let base = idx.get() * self.geometry.size();
+            possible = possible.iter().filter(|digit| self.blocked[base + digit.index()] == 0).collect();
+        }
+
+        if self.rules.has_lines(idx) {
+            let value = |other: CellIndex| self.numbers[other.get()];
+            possible = possible.iter().filter(|&digit| self.rules.fits_lines(idx, digit, &value)).collect();
+        }
+
+        match self.cage_of[idx.get()] {
+            Some(c) => possible.iter().filter(|&digit| self.cages[c].fits(digit, all)).collect(),
+            None => possible,
+        }
+    }
+
+    // Neighbour rules of idx holding digit, +1 / -1 per blocked number
+    fn block(&mut self, idx: CellIndex, digit: Digit, placed: bool) {
+        let size = self.geometry.size();
+        for (n, constraint) in self.rules.neighbours(idx) {
+            for excluded in constraint.excluded_by(digit, size).iter() {
+                let counter = &mut self.blocked[n.get() * size + excluded.index()];
+                if placed {
+                    *counter += 1;
+                } else {
+                    *counter -= 1;
+                }
+            }
+        }
+    }
+
+    fn place(&mut self, idx: CellIndex, digit: Digit) {
+        self.numbers[idx.get()] = digit.get();
+        for &u in self.rules.cell_units(idx) {
+            self.used[u] |= 1 << digit.index();
+        }
+        self.block(idx, digit, true);
+        if let Some(c) = self.cage_of[idx.get()] {
+            self.cages[c].place(digit);
+        }
+    }
+
+    fn unplace(&mut self, idx: CellIndex, digit: Digit) {
+        self.numbers[idx.get()] = 0;
+        for &u in self.rules.cell_units(idx) {
+            self.used[u] &= !(1 << digit.index());
+        }
+        self.block(idx, digit, false);
+        if let Some(c) = self.cage_of[idx.get()] {
+            self.cages[c].unplace(digit);
+        }
+    }
+
+    // Smallest branch : the empty cell with the fewest possible numbers, or a
+    // number with the fewest places left in one full size unit. None if the
+    // grid is full, an empty branch is a dead end.
+    fn best_branch(&self) -> Option<Vec<(CellIndex, Digit)>> {
+        let g = self.geometry;
+        let possible: Vec<CandidateSet> = g
+            .all_cells()
+            .map(|idx| if self.numbers[idx.get()] == 0 { self.possible(idx) } else { CandidateSet::EMPTY })
+            .collect();
+
+        let mut best: Option<(CellIndex, CandidateSet)> = None;
+        for idx in g.all_cells().filter(|idx| self.numbers[idx.get()] == 0) {
+            let p = possible[idx.get()];
+            if best.is_none_or(|(_, b)| p.len() < b.len()) {
+                best = Some((idx, p));
+                if p.len() <= 1 {
+                    break;
+                }
+            }
+        }
+
+        let (idx, cell_possible) = best?;
+        if cell_possible.len() <= 1 {
+            return Some(cell_possible.iter().map(|digit| (idx, digit)).collect());
+        }
+
+        let mut branch: Vec<(CellIndex, Digit)> = cell_possible.iter().map(|digit| (idx, digit)).collect();
+        for (slot, unit) in self.rules.units().iter().enumerate().filter(|(_, unit)| unit.len() == g.size()) {
+            let missing = g.all_candidates().difference(CandidateSet::from_bits(self.used[slot]));
+            let places = |digit: Digit| -> Vec<(CellIndex, Digit)> {
+                unit.iter().filter(|&&p| possible[p.get()].contains(digit)).map(|&p| (p, digit)).collect()
+            };
+
+            match unit_branch(unit.iter().map(|p| possible[p.get()]), missing, branch.len()) {
+                UnitBranch::DeadEnd => return Some(Vec::new()),
+                UnitBranch::Single(digit) => return Some(places(digit)),
+                UnitBranch::Fewest(digit) => branch = places(digit),
+                UnitBranch::None => {}
+            }
+        }
+
+        Some(branch)
+    }
+
+    // Counts solutions up to limit, the first one found is kept in `first`
+    fn search(&mut self, limit: usize, found: &mut usize, first: &mut Option<Vec<u8>>) {
+        let Some(branch) = self.best_branch() else {
+            *found += 1;
+            if first.is_none() {
+                *first = Some(self.numbers.clone());
+            }
+            return;
+        };
+
+        for (idx, digit) in branch {
+            self.place(idx, digit);
+            self.search(limit, found, first);
+            self.unplace(idx, digit);
+
+            if *found >= limit {
+                return;
+            }
+        }
+    }
+}
+
+enum UnitBranch {
+    DeadEnd,       // a missing number has no place left
+    Single(Digit), // hidden single
+    Fewest(Digit), // fewer places than the best branch so far
+    None,
+}
+
+// Number of a full size unit with the fewest places, from the possible
+// numbers of its cells (empty for filled cells)
+fn unit_branch(possible: impl Iterator<Item = CandidateSet> + Clone, missing: CandidateSet, best: usize) -> UnitBranch {
+    let mut mask = UnitMask::default();
+    for set in possible.clone() {
+        mask.add(set);
+    }
+
+    if !missing.difference(mask.seen).is_empty() {
+        return UnitBranch::DeadEnd;
+    }
+    if let Some(digit) = missing.intersection(mask.once()).first() {
+        return UnitBranch::Single(digit);
+    }
+    if best <= 2 {
+        return UnitBranch::None;
+    }
+
+    let mut counts = [0u8; MAX_GRID_SIZE];
+    for set in possible {
+        for digit in set.intersection(missing).iter() {
+            counts[digit.index()] += 1;
+        }
+    }
+    missing
+        .iter()
+        .min_by_key(|digit| counts[digit.index()])
+        .filter(|digit| (counts[digit.index()] as usize) < best)
+        .map_or(UnitBranch::None, UnitBranch::Fewest)
+}
+
+// Search over the board positions of a MultiGrid, one SearchState per grid
+struct MultiSearch<'a> {
+    states: Vec<SearchState<'a>>,
+    vars: Vec<&'a [(usize, CellIndex)]>, // grid cells of every board position
+    var_of: Vec<Vec<usize>>,            // per grid and cell
+    numbers: Vec<u8>,                   // per variable
+}
+
+impl<'a> MultiSearch<'a> {
+    fn new(multi: &'a MultiGrid) -> Option<MultiSearch<'a>> {
+        let states = multi.grids().iter().map(SearchState::new).collect::<Option<Vec<_>>>()?;
+        let vars: Vec<&[(usize, CellIndex)]> = multi.positions().map(|pos| multi.cells_at(pos)).collect();
+
+        let mut var_of = vec![vec![0; multi.geometry().cells()]; multi.grids().len()];
+        for (v, cells) in vars.iter().enumerate() {
+            for &(g, idx) in cells.iter() {
+                var_of[g][idx.get()] = v;
+            }
+        }
+
+        let numbers = vars.iter().map(|cells| states[cells[0].0].numbers[cells[0].1.get()]).collect();
+        Some(MultiSearch { states, vars, var_of, numbers })
+    }
+
+    fn possible(&self, v: usize) -> CandidateSet {
+        self.vars[v]
+            .iter()
+            .fold(self.states[0].geometry.all_candidates(), |acc, &(g, idx)| acc.intersection(self.states[g].possible(idx)))
+    }
+
+    fn place(&mut self, v: usize, digit: Digit) {
+        self.numbers[v] = digit.get();
+        for &(g, idx) in self.vars[v] {
+            self.states[g].place(idx, digit);
+        }
+    }
+
+    fn unplace(&mut self, v: usize, digit: Digit) {
+        self.numbers[v] = 0;
+        for &(g, idx) in self.vars[v] {
+            self.states[g].unplace(idx, digit);
+        }
+    }
+
+    // Same choice as SearchState::best_branch, hidden singles over the full
+    // size units of every grid
+    fn best_branch(&self) -> Option<Vec<(usize, Digit)>> {
+        let possible: Vec<CandidateSet> = (0..self.vars.len())
+            .map(|v| if self.numbers[v] == 0 { self.possible(v) } else { CandidateSet::EMPTY })
+            .collect();
+
+        let mut best: Option<(usize, CandidateSet)> = None;
+        for v in (0..self.vars.len()).filter(|&v| self.numbers[v] == 0) {
+            if best.is_none_or(|(_, b)| possible[v].len() < b.len()) {
+                best = Some((v, possible[v]));
+                if possible[v].len() <= 1 {
+                    break;
+                }
+            }
+        }
+
+        let (v, var_possible) = best?;
+        let mut branch: Vec<(usize, Digit)> = var_possible.iter().map(|digit| (v, digit)).collect();
+        if branch.len() <= 1 {
+            return Some(branch);
+        }
+
+        for (g, state) in self.states.iter().enumerate() {
+            let size = state.geometry.size();
+            for (slot, unit) in state.rules.units().iter().enumerate().filter(|(_, unit)| unit.len() == size) {
+                let missing = state.geometry.all_candidates().difference(CandidateSet::from_bits(state.used[slot]));
+                let vars = unit.iter().map(|p| self.var_of[g][p.get()]);
+                let places = |digit: Digit| -> Vec<(usize, Digit)> {
+                    vars.clone().filter(|&pv| possible[pv].contains(digit)).map(|pv| (pv, digit)).collect()
+                };
+
+                match unit_branch(vars.clone().map(|pv| possible[pv]), missing, branch.len()) {
+                    UnitBranch::DeadEnd => return Some(Vec::new()),
+                    UnitBranch::Single(digit) => return Some(places(digit)),
+                    UnitBranch::Fewest(digit) => branch = places(digit),
+                    UnitBranch::None => {}
+                }
+            }
+        }
+
+        Some(branch)
+    }
+
+    fn search(&mut self, limit: usize, found: &mut usize, first: &mut Option<Vec<u8>>) {
+        let Some(branch) = self.best_branch() else {
+            *found += 1;
+            if first.is_none() {
+                *first = Some(self.numbers.clone());
+            }
+            return;
+        };
+
+        for (v, digit) in branch {
+            self.place(v, digit);
+            self.search(limit, found, first);
+            self.unplace(v, digit);
+
+            if *found >= limit {
+                return;
+            }
+        }
+    }
+}
+
+// Interchangeable solvers : same answers, different speed per puzzle type
+pub trait SolverBackend {
+    fn name(&self) -> &'static str;
+
+    // Fill all empty cells, None if there is no solution
+    fn solve(&self, grid: &Grid) -> Option<Grid>;
+
+    // Number of solutions, counting stops at limit
+    fn count_solutions(&self, grid: &Grid, limit: usize) -> usize;
+
+    fn has_unique_solution(&self, grid: &Grid) -> bool {
+        self.count_solutions(grid, 2) == 1
+    }
+}
+
+// The search of this file, behind solve and count_solutions
+pub struct Backtracking;
+
+impl SolverBackend for Backtracking {
+    fn name(&self) -> &'static str {
+        "backtracking"
+    }
+
+    fn solve(&self, grid: &Grid) -> Option<Grid> {
+        solve(grid)
+    }
+
+    fn count_solutions(&self, grid: &Grid, limit: usize) -> usize {
+        count_solutions(grid, limit)
+    }
+}
+
+pub const BACKENDS: [&dyn SolverBackend; 2] = [&Backtracking, &DancingLinks];
+
+// Dancing links when every rule is an exact cover column, killer sums and
+// line rules only filter its rows and the backtracking search is faster there
+pub fn backend_for(grid: &Grid) -> &'static dyn SolverBackend {
+    let has_lines = grid.variants().iter().any(|variant| !variant.constraint().cells().is_empty());
+    if grid.cages().is_empty() && !has_lines {
+        &DancingLinks
+    } else {
+        &Backtracking
+    }
+}
+
+// Fill all empty cells, None if there is no solution.
+// Givens and placed numbers are kept, solved cells are not flagged as givens.
+pub fn solve(grid: &Grid) -> Option<Grid> {
+    let mut state = SearchState::new(grid)?;
+    let mut found = 0;
+    let mut first = None;
+
+    state.search(1, &mut found, &mut first);
+
+    first.map(|numbers| filled(grid, &numbers))
+}
+
+// grid with the numbers of a solution in its empty cells
+pub(crate) fn filled(grid: &Grid, numbers: &[u8]) -> Grid {
+    let mut solved = grid.clone();
+    for idx in grid.geometry().all_cells() {
+        if solved.value(idx).is_none() {
+            let cell = solved.cell_mut(idx);
+            cell.value = Digit::new(numbers[idx.get()]);
+            cell.candidates = CandidateSet::EMPTY;
+        }
+    }
+    solved
+}
+
+// Number of solutions, counting stops at limit
+pub fn count_solutions(grid: &Grid, limit: usize) -> usize {
+    count_and_solve(grid, limit).0
+}
+
+// count_solutions and the first solution found, one search for both
+pub fn count_and_solve(grid: &Grid, limit: usize) -> (usize, Option<Grid>) {
+    let Some(mut state) = SearchState::new(grid) else {
+        return (0, None);
+    };
+    let mut found = 0;
+    let mut first = None;
+
+    state.search(limit, &mut found, &mut first);
+    (found, first.map(|numbers| filled(grid, &numbers)))
+}
+
+pub fn has_unique_solution(grid: &Grid) -> bool {
+    count_solutions(grid, 2) == 1
+}
+
+// Fill all empty cells of every grid, shared cells get one number
+pub fn solve_multi(multi: &MultiGrid) -> Option<MultiGrid> {
+    count_and_solve_multi(multi, 1).1
+}
+
+// Number of solutions of the whole board, counting stops at limit
+pub fn count_multi_solutions(multi: &MultiGrid, limit: usize) -> usize {
+    count_and_solve_multi(multi, limit).0
+}
+
+// count_multi_solutions and the first solution found, one search for both
+pub fn count_and_solve_multi(multi: &MultiGrid, limit: usize) -> (usize, Option<MultiGrid>) {
+    let Some(mut state) = MultiSearch::new(multi) else {
+        return (0, None);
+    };
+    let mut found = 0;
+    let mut first = None;
+
+    state.search(limit, &mut found, &mut first);
+
+    let solved = first.map(|numbers| {
+        let mut solved = multi.clone();
+        for (pos, num) in multi.positions().zip(numbers) {
+            if let Some(cell) = solved.cell(pos).filter(|cell| cell.value.is_none()) {
+                let cell = Cell { value: Digit::new(num), candidates: CandidateSet::EMPTY, ..*cell };
+                solved.set_cell(pos, cell);
+            }
+        }
+        solved
+    });
+    (found, solved)
+}
+
+// Complete and no constraint of the grid broken
+pub fn is_solved(grid: &Grid) -> bool {
+    grid.is_complete() && grid.is_consistent()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    use crate::generator::{generate_solution_sized, XorShift64};
+
+    // Random solution of any geometry with a random subset of its cells kept as givens
+    fn puzzles() -> impl Strategy<Value = (Grid, Grid)> {
+        let geometries = prop::sample::select(Geometry::SUPPORTED.to_vec());
+        let keeps = geometries.prop_flat_map(|geometry| (Just(geometry), prop::collection::vec(any::<bool>(), geometry.cells())));
+        (any::<u64>(), keeps).prop_map(|(seed, (geometry, keep))| {
+            let solution = generate_solution_sized(geometry, &mut XorShift64::new(seed));
+            let numbers: Vec<u8> = solution.numbers().into_iter().zip(keep).map(|(n, k)| if k { n } else { 0 }).collect();
+            (Grid::from_givens(&numbers).unwrap(), solution)
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn backends_solve_consistently((puzzle, solution) in puzzles()) {
+            let count = Backtracking.count_solutions(&puzzle, 2);
+            prop_assert!(count >= 1);
+
+            for backend in BACKENDS {
+                let solved = backend.solve(&puzzle).unwrap();
+                prop_assert!(is_solved(&solved), "{}", backend.name());
+                for i in puzzle.geometry().all_cells().filter(|&i| puzzle.cell(i).given) {
+                    prop_assert_eq!(solved.value(i), puzzle.value(i));
+                }
+                if count == 1 {
+                    prop_assert_eq!(solved.numbers(), solution.numbers());
+                }
+                prop_assert_eq!(backend.count_solutions(&puzzle, 2), count, "{}", backend.name());
+            }
+        }
+
+        #[test]
+        fn backends_reject_conflicting_givens(row in 0..9usize, cols in (0..9usize, 0..9usize), digit in 1..=9u8) {
+            prop_assume!(cols.0 != cols.1);
+            let mut numbers = vec![0; 81];
+            numbers[row * 9 + cols.0] = digit;
+            numbers[row * 9 + cols.1] = digit;
+            let puzzle = Grid::from_givens(&numbers).unwrap();
+
+            for backend in BACKENDS {
+                prop_assert!(backend.solve(&puzzle).is_none(), "{}", backend.name());
+                prop_assert_eq!(backend.count_solutions(&puzzle, 2), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn backends_solve_every_geometry() {
+        for geometry in Geometry::SUPPORTED {
+            let solution = generate_solution_sized(geometry, &mut XorShift64::new(11));
+            // Every other cell emptied
+            let numbers: Vec<u8> = solution.numbers().iter().enumerate().map(|(i, &n)| if i % 2 == 0 { 0 } else { n }).collect();
+            let puzzle = Grid::from_givens(&numbers).unwrap();
+
+            for backend in BACKENDS {
+                let solved = backend.solve(&puzzle).unwrap();
+                assert!(is_solved(&solved), "{} {geometry:?}", backend.name());
+                assert_eq!(solved.geometry(), geometry);
+                assert_eq!(backend.count_solutions(&puzzle, 2), count_solutions(&puzzle, 2), "{} {geometry:?}", backend.name());
+            }
+
+            let (count, first) = count_and_solve(&puzzle, 2);
+            assert_eq!(count, count_solutions(&puzzle, 2));
+            assert!(is_solved(&first.unwrap()));
+
+            let empty = Grid::with_geometry(geometry);
+            assert_eq!(count_solutions(&empty, 2), 2, "{geometry:?}");
+        }
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
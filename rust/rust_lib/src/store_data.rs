@@ -32,32 +32,73 @@ Contact: MIRKO THULKE (for permission requests)
 ✅ How this works
 DartToRustElementFFI → raw struct for FFI, fixed-size arrays.
 SerializableElement → JSON-friendly version with Vec<u8> instead of fixed-size arrays.
-Conversion implemented via From.
+Conversion from / to the Cells of a Grid, lists hold one entry per digit.
 Save → flatten the matrix → convert to SerializableElement → JSON → file.
-Load → parse JSON → rebuild DartToRustElementFFI structs → copy back into Dart’s allocated memory.
+Load → parse JSON → rebuild the Grid → copy back into Dart’s allocated memory.
+rows / cols hold the grid size (4, 6, 9, 12 or 16), 9 x 9 saves are unchanged.
+Killer cages are stored as "cages" (cell indices and sum), the key is left
+out for games without cages and defaults to none on load.
+Variant rules are stored the same way as "variants" (VariantKind, plus the
+region map for jigsaw regions, cells and sum for thermo, arrow and little
+killer lines). Region maps are validated on load, a map that is no
+partition of the grid fails with SudokuError::InvalidRegions.
+
+Multi-grids (Samurai) are saved by save_multi_grid as MultiGridData : the
+grid origins, one element per board cell (row / col on the board, shared
+cells only once) and the cages and variants of every grid.
 Credits to ChatGPT !
+
+Puzzle files (SadMan / HoDoKu)
+.sdk → one puzzle. Optional '#' metadata lines and [Puzzle] section,
+       followed by 9 lines of 9 characters ('.' or '0' for empty cells).
+.sdm → many puzzles, one 81-character puzzle string per line.
+Other grid sizes use size lines of size characters, see puzzle_string.rs.
+Only givens are stored, other sections like [State] are skipped on load.
+
+save_data_format writes JSON or the compact binary format (binary_format.rs),
+load_data detects the format from the file content.
 */
 
 // for JSON storage upon shutdown:
 use serde::{Serialize, Deserialize};
 use std::fs;
 use std::os::raw::c_int;
-use std::ffi::CStr;
 use std::os::raw::c_char;
 
+use crate::error::{ffi_status, ffi_value, SudokuError};
+
 
 // process_data.rs
 use crate::ffi::{DartToRustElementFFI};
 
+use crate::ffi::{c_char_to_str, read_grid, write_grid};
+
+use crate::ffi::{NumStateListIndex};
+
+use crate::grid::{CandidateSet, Cell, CellIndex, Digit, Geometry, Grid, MAX_GRID_SIZE};
+
+use crate::killer::Cage;
+
+use crate::constraint::{Variant, VariantKind};
+
+use crate::multi_grid::MultiGrid;
+
+use crate::puzzle_string::{parse_puzzle_string, format_puzzle_string};
+
+use crate::binary_format::{encode_binary, decode_binary, is_binary};
+
+
 
+use crate::ffi::{constSelectedNumStateListSize};
 
-use crate::ffi::{constSelectedNumberListSize,
-constSelectedNumStateListSize,
-constSelectedPatternListSize,
-constRequestedElementHighLightTypeSize,
-constRequestedCandHighLightTypeSize};
 
 
+pub struct SaveFormat;
+
+impl SaveFormat {
+    pub const JSON: u8 = 0;
+    pub const BINARY: u8 = 1;
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SerializableElement {
@@ -72,57 +113,283 @@ pub struct SerializableElement {
 }
 
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializableCage {
+    cells: Vec<u8>,
+    sum: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializableVariant {
+    kind: u8,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    regions: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cells: Vec<u8>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    sum: u16,
+}
+
+fn is_zero(sum: &u16) -> bool {
+    *sum == 0
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AppData {
     rows: u8,
     cols: u8,
     elements: Vec<SerializableElement>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cages: Vec<SerializableCage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variants: Vec<SerializableVariant>,
 }
 
 
+// Cages and variants of one grid of a MultiGridData
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializableRules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cages: Vec<SerializableCage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variants: Vec<SerializableVariant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiGridData {
+    rows: u8, // grid size, as in AppData
+    cols: u8,
+    origins: Vec<[u8; 2]>,
+    elements: Vec<SerializableElement>,
+    grids: Vec<SerializableRules>,
+}
+
+// Copy as many values as fit, shorter lists leave the rest at 0
+fn copy_list(dst: &mut [u8], src: &[u8]) {
+    let len = dst.len().min(src.len());
+    dst[..len].copy_from_slice(&src[..len]);
+}
+
+impl SerializableElement {
+    // Lists hold one entry per digit of the grid size
+    fn from_cell(geometry: Geometry, idx: CellIndex, cell: &Cell) -> SerializableElement {
+        let mut num_state = vec![0; constSelectedNumStateListSize as usize];
+        num_state[NumStateListIndex::GIVENS as usize] = cell.given as u8;
+        num_state[NumStateListIndex::FUTUREUSE as usize] = cell.future_use as u8;
 
-impl From<&DartToRustElementFFI> for SerializableElement {
-    fn from(e: &DartToRustElementFFI) -> Self {
         SerializableElement {
-            row: e.row,
-            col: e.col,
-            selectedNum: e.selectedNum,
-            selectedNumStateList: e.selectedNumStateList.to_vec(),
-            selectedCandList: e.selectedCandList.to_vec(),
-            selectedPatternList: e.selectedPatternList.to_vec(),
-            requestedElementHighLightType: e.requestedElementHighLightType.to_vec(),
-            requestedCandHighLightType: e.requestedCandHighLightType.to_vec(),
-        }
-    }
-}
-
-impl From<&SerializableElement> for DartToRustElementFFI {
-    fn from(e: &SerializableElement) -> Self {
-        let mut s = DartToRustElementFFI {
-            row: e.row,
-            col: e.col,
-            selectedNum: e.selectedNum,
-            selectedNumStateList: [0; constSelectedNumStateListSize as usize],
-            selectedCandList: [0; constSelectedNumberListSize as usize],
-            selectedPatternList: [0; constSelectedPatternListSize as usize],
-            requestedElementHighLightType: [0; constRequestedElementHighLightTypeSize as usize],
-            requestedCandHighLightType: [0; constRequestedCandHighLightTypeSize as usize ],
+            row: geometry.row(idx) as u8,
+            col: geometry.col(idx) as u8,
+            selectedNum: cell.value.map_or(0, Digit::get),
+            selectedNumStateList: num_state,
+            selectedCandList: cell.candidates.to_flag_vec(geometry.size()),
+            selectedPatternList: cell.patterns.to_vec(),
+            requestedElementHighLightType: cell.element_highlight.to_vec(),
+            requestedCandHighLightType: cell.candidate_highlight[..geometry.size()].to_vec(),
+        }
+    }
+
+    // Numbers outside the grid size are read as empty cell
+    fn to_cell(&self, geometry: Geometry) -> Cell {
+        let flag = |i: u8| self.selectedNumStateList.get(i as usize).is_some_and(|&v| v != 0);
+
+        let mut cell = Cell {
+            value: geometry.digit(self.selectedNum),
+            given: flag(NumStateListIndex::GIVENS),
+            future_use: flag(NumStateListIndex::FUTUREUSE),
+            candidates: CandidateSet::from_flags(&self.selectedCandList).intersection(geometry.all_candidates()),
+            ..Cell::default()
         };
-        s.selectedNumStateList[..e.selectedNumStateList.len().min(constSelectedNumStateListSize  as usize)]
-            .copy_from_slice(&e.selectedNumStateList);
-        s.selectedCandList[..e.selectedCandList.len().min(constSelectedNumberListSize  as usize)]
-            .copy_from_slice(&e.selectedCandList);
-        s.selectedPatternList[..e.selectedPatternList.len().min(constSelectedPatternListSize  as usize)]
-            .copy_from_slice(&e.selectedPatternList);
-        s.requestedElementHighLightType[..e.requestedElementHighLightType.len().min(constRequestedElementHighLightTypeSize  as usize)]
-            .copy_from_slice(&e.requestedElementHighLightType);
-        /* s.requestedCandHighLightType[..e.requestedCandHighLightType.len().min(constRequestedCandHighLightTypeSize  as usize)]
-            .copy_from_slice(&e.requestedCandHighLightType); */
+        copy_list(&mut cell.patterns, &self.selectedPatternList);
+        copy_list(&mut cell.element_highlight, &self.requestedElementHighLightType);
+        // requestedCandHighLightType is recomputed by Rust, not restored
+        cell.candidate_highlight = [0; MAX_GRID_SIZE];
 
-        s
+        cell
     }
 }
 
+impl SerializableRules {
+    fn from_grid(grid: &Grid) -> SerializableRules {
+        SerializableRules {
+            cages: grid
+                .cages()
+                .iter()
+                .map(|cage| SerializableCage {
+                    cells: cage.cells().iter().map(|idx| idx.get() as u8).collect(),
+                    sum: cage.sum(),
+                })
+                .collect(),
+            variants: grid
+                .variants()
+                .iter()
+                .map(|variant| {
+                    let (cells, sum) = variant.parts();
+                    match variant {
+                        Variant::Regions(_) => SerializableVariant { kind: variant.kind(), regions: cells, cells: Vec::new(), sum },
+                        _ => SerializableVariant { kind: variant.kind(), regions: Vec::new(), cells, sum },
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn add_to(&self, grid: &mut Grid) -> Result<(), SudokuError> {
+        let geometry = grid.geometry();
+
+        for cage in &self.cages {
+            let cells = cage
+                .cells
+                .iter()
+                .map(|&i| geometry.cell(i as usize).ok_or(SudokuError::IndexOutOfRange))
+                .collect::<Result<Vec<_>, _>>()?;
+            grid.add_cage(Cage::new(geometry, cells, cage.sum)?)?;
+        }
+
+        for variant in &self.variants {
+            let cells = if variant.kind == VariantKind::REGIONS { &variant.regions } else { &variant.cells };
+            grid.add_variant(Variant::from_parts(geometry, variant.kind, cells, variant.sum)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+// rows = cols = grid size, 4, 6, 9, 12 or 16
+fn geometry_of(rows: u8, cols: u8) -> Result<Geometry, SudokuError> {
+    Geometry::from_size(rows as usize)
+        .filter(|_| rows == cols)
+        .ok_or(SudokuError::DimensionMismatch)
+}
+
+impl AppData {
+    pub fn from_grid(grid: &Grid) -> AppData {
+        let geometry = grid.geometry();
+        let rules = SerializableRules::from_grid(grid);
+
+        AppData {
+            rows: geometry.size() as u8,
+            cols: geometry.size() as u8,
+            elements: geometry
+                .all_cells()
+                .map(|idx| SerializableElement::from_cell(geometry, idx, grid.cell(idx)))
+                .collect(),
+            cages: rules.cages,
+            variants: rules.variants,
+        }
+    }
+
+    pub fn to_grid(&self) -> Result<Grid, SudokuError> {
+        let geometry = geometry_of(self.rows, self.cols)?;
+
+        if self.elements.len() != geometry.cells() {
+            return Err(SudokuError::CorruptData);
+        }
+
+        let mut grid = Grid::with_geometry(geometry);
+        for (cell, e) in grid.cells_mut().iter_mut().zip(&self.elements) {
+            *cell = e.to_cell(geometry);
+        }
+
+        SerializableRules { cages: self.cages.clone(), variants: self.variants.clone() }.add_to(&mut grid)?;
+        Ok(grid)
+    }
+}
+
+impl MultiGridData {
+    pub fn from_multi_grid(multi: &MultiGrid) -> MultiGridData {
+        let geometry = multi.geometry();
+
+        MultiGridData {
+            rows: geometry.size() as u8,
+            cols: geometry.size() as u8,
+            origins: multi.origins().iter().map(|&(row, col)| [row as u8, col as u8]).collect(),
+            elements: multi
+                .positions()
+                .map(|pos| {
+                    let (g, idx) = multi.cells_at(pos)[0];
+                    let mut e = SerializableElement::from_cell(geometry, idx, multi.grids()[g].cell(idx));
+                    e.row = (pos / multi.cols()) as u8;
+                    e.col = (pos % multi.cols()) as u8;
+                    e
+                })
+                .collect(),
+            grids: multi.grids().iter().map(SerializableRules::from_grid).collect(),
+        }
+    }
+
+    // Every board cell exactly once, one rules entry per grid
+    pub fn to_multi_grid(&self) -> Result<MultiGrid, SudokuError> {
+        let geometry = geometry_of(self.rows, self.cols)?;
+        let origins: Vec<(usize, usize)> = self.origins.iter().map(|&[row, col]| (row as usize, col as usize)).collect();
+        let mut multi = MultiGrid::new(geometry, &origins)?;
+
+        if self.elements.len() != multi.positions().count() || self.grids.len() != origins.len() {
+            return Err(SudokuError::CorruptData);
+        }
+
+        let mut loaded = vec![false; multi.rows() * multi.cols()];
+        for e in &self.elements {
+            let (row, col) = (e.row as usize, e.col as usize);
+            let pos = row * multi.cols() + col;
+            if col >= multi.cols() || loaded.get(pos) != Some(&false) || !multi.set_cell(pos, e.to_cell(geometry)) {
+                return Err(SudokuError::CorruptData);
+            }
+            loaded[pos] = true;
+        }
+
+        for (g, rules) in self.grids.iter().enumerate() {
+            let mut grid = multi.grids()[g].clone();
+            rules.add_to(&mut grid)?;
+            for cage in grid.cages() {
+                multi.add_cage(g, cage.clone())?;
+            }
+            for variant in grid.variants() {
+                multi.add_variant(g, variant.clone())?;
+            }
+        }
+
+        Ok(multi)
+    }
+}
+
+pub fn save_grid(grid: &Grid, path: &str, format: u8) -> Result<(), SudokuError> {
+    let bytes = match format {
+        SaveFormat::JSON => serde_json::to_string(&AppData::from_grid(grid)).map_err(|_| SudokuError::SerializeFailed)?.into_bytes(),
+        SaveFormat::BINARY => encode_binary(grid),
+        _ => return Err(SudokuError::UnknownFormat),
+    };
+
+    fs::write(path, bytes).map_err(|_| SudokuError::WriteFailed)
+}
+
+// Format is detected from the file content
+pub fn load_grid(path: &str) -> Result<Grid, SudokuError> {
+    let bytes = fs::read(path).map_err(|_| SudokuError::ReadFailed)?;
+
+    if is_binary(&bytes) {
+        return decode_binary(&bytes);
+    }
+
+    let json = String::from_utf8(bytes).map_err(|_| SudokuError::InvalidUtf8)?;
+    let data = serde_json::from_str::<AppData>(&json).map_err(|_| SudokuError::ParseFailed)?;
+
+    data.to_grid()
+}
+
+// JSON only, the binary format holds one grid
+pub fn save_multi_grid(multi: &MultiGrid, path: &str) -> Result<(), SudokuError> {
+    let json = serde_json::to_string(&MultiGridData::from_multi_grid(multi)).map_err(|_| SudokuError::SerializeFailed)?;
+    fs::write(path, json).map_err(|_| SudokuError::WriteFailed)
+}
+
+pub fn load_multi_grid(path: &str) -> Result<MultiGrid, SudokuError> {
+    let json = fs::read_to_string(path).map_err(|_| SudokuError::ReadFailed)?;
+    let data = serde_json::from_str::<MultiGridData>(&json).map_err(|_| SudokuError::ParseFailed)?;
+
+    data.to_multi_grid()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn save_data(
     ptr: *const DartToRustElementFFI,
@@ -130,79 +397,305 @@ pub unsafe extern "C" fn save_data(
     cols: u8,
     path: *const c_char, // <- new argument
 ) -> c_int {
-    if ptr.is_null() || path.is_null() {
-        return -1;
+    save_data_format(ptr, rows, cols, path, SaveFormat::JSON)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn save_data_format(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    path: *const c_char,
+    format: u8, // SaveFormat
+) -> c_int {
+    ffi_status(|| {
+        let grid = read_grid(ptr, rows, cols)?;
+        save_grid(&grid, c_char_to_str(path)?, format)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn load_data(
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    path: *const c_char, // <- new argument
+) -> c_int {
+    ffi_status(|| {
+        read_grid(ptr, rows, cols)?;
+        let grid = load_grid(c_char_to_str(path)?)?;
+        write_grid(&grid, ptr)?;
+        Ok(())
+    })
+}
+
+// Read the givens of a .sdk file
+pub fn parse_sdk(content: &str) -> Result<Vec<u8>, SudokuError> {
+    let mut grid = String::new();
+    let mut in_puzzle = true;
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            // metadata like #A author, #D description, #L level
+            continue;
+        }
+
+        if line.starts_with('[') {
+            // only the [Puzzle] section holds the givens
+            in_puzzle = line.eq_ignore_ascii_case("[Puzzle]");
+            continue;
+        }
+
+        if in_puzzle {
+            grid.push_str(line);
+        }
     }
 
-    // Convert C string to Rust &str
-    let c_str = CStr::from_ptr(path);
-    let path_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return -6, // invalid UTF-8
-    };
+    parse_puzzle_string(&grid)
+}
 
-    let slice = std::slice::from_raw_parts(ptr, (rows as usize) * (cols as usize));
+// Write givens in .sdk layout, 9 lines of 9 characters for the classic grid
+pub fn format_sdk(numbers: &[u8]) -> Result<String, SudokuError> {
+    let puzzle = format_puzzle_string(numbers)?;
+    let size = Geometry::from_cells(numbers.len()).ok_or(SudokuError::InvalidLength)?.size();
+    let mut out = String::from("[Puzzle]\n");
 
-    let data = AppData {
-        rows,
-        cols,
-        elements: slice.iter().map(SerializableElement::from).collect(),
-    };
+    // One character per number, rows split on character boundaries
+    for row in puzzle.as_bytes().chunks(size) {
+        out.push_str(std::str::from_utf8(row).map_err(|_| SudokuError::InvalidUtf8)?);
+        out.push('\n');
+    }
+
+    Ok(out)
+}
 
-    match serde_json::to_string(&data) {
-        Ok(json) => match fs::write(path_str, json) {
-            Ok(_) => 0,
-            Err(_) => -2,
-        },
-        Err(_) => -3,
+// Read all puzzles of a .sdm file, empty lines and '#' comments are skipped
+pub fn parse_sdm(content: &str) -> Result<Vec<Vec<u8>>, SudokuError> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_puzzle_string)
+        .collect()
+}
+
+// Write puzzles in .sdm layout, one puzzle per line
+pub fn format_sdm(puzzles: &[Vec<u8>]) -> Result<String, SudokuError> {
+    let mut out = String::new();
+
+    for numbers in puzzles {
+        out.push_str(&format_puzzle_string(numbers)?);
+        out.push('\n');
     }
+
+    Ok(out)
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn load_data(
+pub unsafe extern "C" fn save_sdk(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    path: *const c_char,
+) -> c_int {
+    ffi_status(|| {
+        let grid = read_grid(ptr, rows, cols)?;
+        fs::write(c_char_to_str(path)?, format_sdk(&grid.givens())?).map_err(|_| SudokuError::WriteFailed)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn load_sdk(
     ptr: *mut DartToRustElementFFI,
     rows: u8,
     cols: u8,
-    path: *const c_char, // <- new argument
+    path: *const c_char,
 ) -> c_int {
-    if ptr.is_null() || path.is_null() {
-        return -1;
-    }
+    ffi_status(|| {
+        read_grid(ptr, rows, cols)?;
+        let content = fs::read_to_string(c_char_to_str(path)?).map_err(|_| SudokuError::ReadFailed)?;
+        write_grid(&Grid::from_givens(&parse_sdk(&content)?)?, ptr)?;
+        Ok(())
+    })
+}
 
-    let c_str = CStr::from_ptr(path);
-    let path_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return -6, // invalid UTF-8
-    };
+// Number of puzzles in a .sdm file, negative on error
+#[no_mangle]
+pub unsafe extern "C" fn sdm_puzzle_count(path: *const c_char) -> c_int {
+    ffi_value(|| {
+        let content = fs::read_to_string(c_char_to_str(path)?).map_err(|_| SudokuError::ReadFailed)?;
+        Ok(parse_sdm(&content)?.len() as c_int)
+    })
+}
 
-    match fs::read_to_string(path_str) {
-        Ok(json) => match serde_json::from_str::<AppData>(&json) {
-            Ok(data) => {
-                if data.rows != rows || data.cols != cols {
-                    return -4;
-                }
+// Load puzzle number `index` (0 based) of a .sdm file as givens
+#[no_mangle]
+pub unsafe extern "C" fn load_sdm_puzzle(
+    ptr: *mut DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    path: *const c_char,
+    index: u32,
+) -> c_int {
+    ffi_status(|| {
+        read_grid(ptr, rows, cols)?;
+        let content = fs::read_to_string(c_char_to_str(path)?).map_err(|_| SudokuError::ReadFailed)?;
+        let puzzles = parse_sdm(&content)?;
+        let numbers = puzzles.get(index as usize).ok_or(SudokuError::IndexOutOfRange)?;
+
+        write_grid(&Grid::from_givens(numbers)?, ptr)?;
+        Ok(())
+    })
+}
 
-                let slice =
-                    std::slice::from_raw_parts_mut(ptr, (rows as usize) * (cols as usize));
+// Append the givens of the current grid as new line to a .sdm file
+#[no_mangle]
+pub unsafe extern "C" fn append_sdm_puzzle(
+    ptr: *const DartToRustElementFFI,
+    rows: u8,
+    cols: u8,
+    path: *const c_char,
+) -> c_int {
+    ffi_status(|| {
+        let grid = read_grid(ptr, rows, cols)?;
+        let path_str = c_char_to_str(path)?;
+
+        // Missing file starts a new pack
+        let mut puzzles = match fs::read_to_string(path_str) {
+            Ok(content) => parse_sdm(&content)?,
+            Err(_) => Vec::new(),
+        };
+        puzzles.push(grid.givens());
 
-                if slice.len() != data.elements.len() {
-                    return -5;
-                }
+        fs::write(path_str, format_sdm(&puzzles)?).map_err(|_| SudokuError::WriteFailed)
+    })
+}
 
-                for (dst, src) in slice.iter_mut().zip(data.elements.iter()) {
-                    *dst = DartToRustElementFFI::from(src);
-                    print!("RUST : Loading JSON from file.");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    use proptest::prelude::*;
+
+    use crate::test_util::temp_path;
+
+    // Values 0..=9 (given or placed) and candidate bits for the empty cells
+    fn grids() -> impl Strategy<Value = Grid> {
+        prop::collection::vec((0..=9u8, any::<bool>(), 0..512u16), 81).prop_map(|cells| {
+            let mut grid = Grid::new();
+            for (i, (value, given, bits)) in cells.into_iter().enumerate() {
+                let cell = grid.cell_mut(CellIndex::new(i).unwrap());
+                cell.value = Digit::new(value);
+                cell.given = given && cell.value.is_some();
+                if cell.value.is_none() {
+                    cell.candidates = CandidateSet::from_bits(bits);
                 }
-
-                0
             }
-            Err(_) => -2,
-        },
-        Err(_) => -3,
+            grid
+        })
+    }
+
+    #[test]
+    fn save_data_error_codes() {
+        let elements = Grid::new().to_ffi();
+        let path = temp_path("save_errors");
+        let missing_dir = temp_path("no_such_dir/save.json");
+        let not_utf8 = CString::new(vec![0xff, 0xfe]).unwrap();
+
+        unsafe {
+            assert_eq!(save_data(std::ptr::null(), 9, 9, path.as_ptr()), SudokuError::NullPointer.code());
+            assert_eq!(save_data(elements.as_ptr(), 9, 9, std::ptr::null()), SudokuError::NullPointer.code());
+            assert_eq!(save_data(elements.as_ptr(), 9, 8, path.as_ptr()), SudokuError::DimensionMismatch.code());
+            assert_eq!(save_data(elements.as_ptr(), 9, 9, not_utf8.as_ptr()), SudokuError::InvalidUtf8.code());
+            assert_eq!(save_data(elements.as_ptr(), 9, 9, missing_dir.as_ptr()), SudokuError::WriteFailed.code());
+            assert_eq!(save_data_format(elements.as_ptr(), 9, 9, path.as_ptr(), 7), SudokuError::UnknownFormat.code());
+        }
+
+        // Nothing was written by the failed calls
+        assert!(fs::metadata(path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn load_data_error_codes() {
+        let mut elements = Grid::new().to_ffi();
+        let before = elements.clone();
+        let missing = temp_path("load_missing");
+        let garbage = temp_path("load_garbage");
+        fs::write(garbage.to_str().unwrap(), "not a save file").unwrap();
+
+        unsafe {
+            assert_eq!(load_data(std::ptr::null_mut(), 9, 9, garbage.as_ptr()), SudokuError::NullPointer.code());
+            assert_eq!(load_data(elements.as_mut_ptr(), 9, 9, std::ptr::null()), SudokuError::NullPointer.code());
+            assert_eq!(load_data(elements.as_mut_ptr(), 8, 8, garbage.as_ptr()), SudokuError::DimensionMismatch.code());
+            assert_eq!(load_data(elements.as_mut_ptr(), 9, 9, missing.as_ptr()), SudokuError::ReadFailed.code());
+            assert_eq!(load_data(elements.as_mut_ptr(), 9, 9, garbage.as_ptr()), SudokuError::ParseFailed.code());
+        }
+
+        fs::remove_file(garbage.to_str().unwrap()).unwrap();
+        // A failed load leaves the game on screen alone
+        assert_eq!(elements, before);
+    }
+
+    #[test]
+    fn save_data_then_load_data() {
+        let mut grid = Grid::new();
+        grid.cell_mut(CellIndex::new(4).unwrap()).value = Digit::new(6);
+        grid.set_all_candidates();
+        let elements = grid.to_ffi();
+        let mut loaded = Grid::new().to_ffi();
+        let path = temp_path("save_load");
+
+        unsafe {
+            assert_eq!(save_data(elements.as_ptr(), 9, 9, path.as_ptr()), 0);
+            assert_eq!(load_data(loaded.as_mut_ptr(), 9, 9, path.as_ptr()), 0);
+        }
+
+        fs::remove_file(path.to_str().unwrap()).unwrap();
+
+        // JSON does not keep candidate highlights, update_matrix recomputes them
+        for (element, saved) in loaded.iter_mut().zip(&elements) {
+            element.requestedCandHighLightType = saved.requestedCandHighLightType;
+        }
+        assert_eq!(loaded, elements);
     }
-}
 
+    #[test]
+    fn sdk_round_trip() {
+        let numbers = parse_puzzle_string(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        )
+        .unwrap();
+        let sdk = format_sdk(&numbers).unwrap();
+
+        assert_eq!(sdk.lines().nth(1), Some("53..7...."));
+        assert_eq!(parse_sdk(&format!("#A author\n{sdk}")).unwrap(), numbers);
+        assert_eq!(format_sdk(&numbers[..80]), Err(SudokuError::InvalidLength));
+    }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn save_and_load_round_trip(grid in grids()) {
+            for (name, format) in [("json", SaveFormat::JSON), ("binary", SaveFormat::BINARY)] {
+                let path = temp_path(&format!("round_trip.{name}"));
+                let path = path.to_str().unwrap();
+                save_grid(&grid, path, format).unwrap();
+                let loaded = load_grid(path).unwrap();
+                fs::remove_file(path).unwrap();
+
+                prop_assert_eq!(loaded.numbers(), grid.numbers());
+                prop_assert_eq!(loaded.givens(), grid.givens());
+                for i in grid.geometry().all_cells() {
+                    prop_assert_eq!(loaded.cell(i).candidates, grid.cell(i).candidates);
+                }
+            }
+        }
+    }
+}
 
 
 // Copyright (c) 2025, MIRKO THULKE. All rights reserved.
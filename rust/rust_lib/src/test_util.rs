@@ -0,0 +1,46 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+/* 
+Helpers shared by the unit tests of the storage modules
+(store_data, binary_format, puzzle_library). Test builds only.
+*/
+
+use std::ffi::CString;
+
+// File in the temp directory, the process id keeps parallel test runs apart
+pub(crate) fn temp_path(name: &str) -> CString {
+    let path = std::env::temp_dir().join(format!("rust_backend_{}_{}", std::process::id(), name));
+    CString::new(path.to_str().unwrap()).unwrap()
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,121 @@
+/* 
+##############################################################################
+
+Author: MIRKO THULKE
+Copyright (c) 2025, MIRKO THULKE
+All rights reserved.
+
+Date: 2025, VERSAILLES, FRANCE
+
+License: "All Rights Reserved – View Only"
+
+Permission is hereby granted to view and share this code in its original,
+unmodified form for educational or reference purposes only.
+
+Any other use, including but not limited to copying, modification,
+redistribution, commercial use, or inclusion in other projects, is strictly
+prohibited without the express written permission of the author.
+
+The Software is provided "AS IS", without warranty of any kind, express or
+implied, including but not limited to the warranties of merchantability,
+fitness for a particular purpose, and noninfringement. In no event shall the
+author be liable for any claim, damages, or other liability arising from the
+use of the Software.
+
+Contact: MIRKO THULKE (for permission requests)
+
+##############################################################################
+*/
+
+
+
+/* 
+sudoku-cli smoke test
+Runs the built binary once per command on a few puzzle strings and save
+files in the temp directory, checks the printed lines and the exit code.
+
+  cargo test --features cli --test cli
+*/
+
+use std::fs;
+use std::process::{Command, Output};
+
+use rust_backend::grid::Grid;
+use rust_backend::solver::solve;
+use rust_backend::storage::{parse_puzzle_string, to_puzzle_string};
+
+const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sudoku-cli")).args(args).output().unwrap()
+}
+
+fn lines(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout).lines().map(String::from).collect()
+}
+
+fn solution() -> String {
+    let grid = Grid::from_givens(&parse_puzzle_string(PUZZLE).unwrap()).unwrap();
+    to_puzzle_string(&solve(&grid).unwrap(), false)
+}
+
+#[test]
+fn solve_and_grade_print_one_line_per_puzzle() {
+    let output = cli(&["solve", PUZZLE]);
+    assert!(output.status.success());
+    assert_eq!(lines(&output), [solution()]);
+
+    // A bad puzzle fails its own line and the exit code, not the others
+    let output = cli(&["grade", PUZZLE, "12", PUZZLE]);
+    assert!(!output.status.success());
+    let graded = lines(&output);
+    assert_eq!(graded.len(), 3);
+    assert_eq!(graded[0], "easy");
+    assert!(graded[1].starts_with("error: "));
+    assert_eq!(graded[2], "easy");
+
+    let file = std::env::temp_dir().join("sudoku_cli_smoke.sdm");
+    fs::write(&file, format!("# pack\n{PUZZLE}\n\n{}\n", &solution())).unwrap();
+    let output = cli(&["validate", "--file", file.to_str().unwrap()]);
+    fs::remove_file(&file).unwrap();
+    assert!(output.status.success());
+    assert_eq!(lines(&output), ["ok", "ok"]);
+}
+
+#[test]
+fn generate_prints_valid_puzzles() {
+    let output = cli(&["generate", "--count", "2", "--seed", "7", "--size", "4"]);
+    assert!(output.status.success());
+    let puzzles = lines(&output);
+    assert_eq!(puzzles.len(), 2);
+
+    let mut args = vec!["validate"];
+    args.extend(puzzles.iter().map(String::as_str));
+    assert_eq!(lines(&cli(&args)), ["ok", "ok"]);
+
+    assert!(!cli(&["generate", "--size", "5"]).status.success());
+}
+
+#[test]
+fn convert_and_hints_read_save_files() {
+    for (name, binary) in [("json", false), ("binary", true)] {
+        let file = std::env::temp_dir().join(format!("sudoku_cli_smoke.{name}"));
+        let path = file.to_str().unwrap();
+
+        let mut args = vec!["convert", PUZZLE, "--output", path];
+        if binary {
+            args.push("--binary");
+        }
+        assert!(cli(&args).status.success(), "{name}");
+        assert_eq!(lines(&cli(&["convert", path])), [PUZZLE], "{name}");
+
+        let hints = lines(&cli(&["hints", path]));
+        fs::remove_file(&file).unwrap();
+        assert!(hints.iter().any(|line| line.starts_with("single : ")), "{name}");
+        assert_eq!(hints.last().unwrap(), "grade : easy", "{name}");
+    }
+}
+
+
+// Copyright (c) 2025, MIRKO THULKE. All rights reserved.
+// See LICENSE file in the project root for details.
\ No newline at end of file
@@ -0,0 +1,33 @@
+#!/usr/bin/env bash
+set -e  # Exit on any error
+
+
+# ------------------------------------------------------------
+# generate_bindings.sh
+# Generate Dart bindings for rust/rust_lib/src/api with
+# flutter_rust_bridge (config: flutter_rust_bridge.yaml).
+# Output: lib/src/rust (Dart) and rust/rust_lib/src/frb_generated.rs
+# Codegen version must match the flutter_rust_bridge crate (2.11.1).
+# The app does not depend on the pub package yet, add
+# flutter_rust_bridge: 2.11.1 to pubspec.yaml before using the output
+# and build the Rust library with --features api.
+# ------------------------------------------------------------
+
+
+# Resolve script directory
+SCRIPT_DIR="$(cd "$(dirname "$0")" && pwd)"
+PROJECT_DIR="$SCRIPT_DIR/.."
+FRB_VERSION="2.11.1"
+
+cd "$PROJECT_DIR"
+
+if ! flutter_rust_bridge_codegen --version 2>/dev/null | grep -q "$FRB_VERSION"; then
+    echo "Installing flutter_rust_bridge_codegen $FRB_VERSION..."
+    cargo install flutter_rust_bridge_codegen --version "$FRB_VERSION" --locked
+fi
+
+echo "Generating Dart bindings..."
+flutter_rust_bridge_codegen generate
+
+echo "Bindings generated. Resulting files:"
+ls -R lib/src/rust